@@ -0,0 +1,225 @@
+use std::{fs, ops::RangeInclusive, path::Path};
+
+use crate::{
+    error::BspError,
+    lump::{lumps, BspFormat, LumpInfo, LUMP_COUNT},
+};
+
+/// `VBSP` read as a little-endian integer.
+const VBSP_IDENT: u32 = u32::from_le_bytes(*b"VBSP");
+/// Versions written by the Source compilers: 19 (HL2, CS:S), 20 (Orange Box, TF2, L4D) and 21 (L4D2, Portal 2, CS:GO).
+pub const SUPPORTED_VERSIONS: RangeInclusive<i32> = 19..=21;
+/// Ident, version, lump entries and map revision.
+pub const HEADER_SIZE: usize = 8 + LUMP_COUNT * 16 + 4;
+
+/// A compiled map, kept in memory with the lump table of its header.
+#[derive(Debug, Clone)]
+pub struct Bsp {
+    pub version: i32,
+    /// Number of times the map was saved in Hammer before it was compiled.
+    pub map_revision: i32,
+    pub format: BspFormat,
+    pub lumps: [LumpInfo; LUMP_COUNT],
+    data: Vec<u8>,
+}
+
+/// An entity of the entity lump, with its keys in file order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BspEntity {
+    pub properties: Vec<(String, String)>,
+}
+
+impl BspEntity {
+    /// Value of the first key matching `key`, ignoring case.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.properties.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn classname(&self) -> &str {
+        self.get("classname").unwrap_or_default()
+    }
+}
+
+impl Bsp {
+    pub fn load(path: &Path) -> Result<Self, BspError> {
+        Self::parse(fs::read(path)?)
+    }
+
+    /// Reads the header of a BSP file, the lumps are read on demand.
+    pub fn parse(data: Vec<u8>) -> Result<Self, BspError> {
+        if data.len() < HEADER_SIZE {
+            return Err(if data.starts_with(b"VBSP") || data.len() < 4 { BspError::Truncated } else { BspError::NotABsp });
+        }
+        if read_u32(&data, 0) != VBSP_IDENT {
+            return Err(BspError::NotABsp);
+        }
+        let version = read_i32(&data, 4);
+        if !SUPPORTED_VERSIONS.contains(&version) {
+            return Err(BspError::UnsupportedVersion(version));
+        }
+        let map_revision = read_i32(&data, HEADER_SIZE - 4);
+
+        let entries: Vec<[i32; 4]> = (0..LUMP_COUNT)
+            .map(|lump| {
+                let start = 8 + lump * 16;
+                [0, 4, 8, 12].map(|field| read_i32(&data, start + field))
+            })
+            .collect();
+
+        // Left 4 Dead 2 moved the version first, only one of the layouts fits in the file
+        let mut formats = vec![BspFormat::Source];
+        if version == 21 {
+            formats.push(BspFormat::Left4Dead2);
+        }
+        let mut first_error = None;
+        for format in formats {
+            match lump_table(&entries, format, data.len()) {
+                Ok(lumps) => return Ok(Self { version, map_revision, format, lumps, data }),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        Err(first_error.unwrap_or(BspError::NotABsp))
+    }
+
+    /// Size of the whole file in bytes.
+    pub fn file_size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn lump(&self, index: usize) -> LumpInfo {
+        self.lumps[index]
+    }
+
+    /// Raw data of a lump, failing if it is compressed.
+    pub fn lump_data(&self, index: usize) -> Result<&[u8], BspError> {
+        let lump = self.lumps[index];
+        if lump.is_compressed() {
+            return Err(BspError::CompressedLump(index));
+        }
+        Ok(&self.data[lump.offset..lump.offset + lump.length])
+    }
+
+    /// Number of elements of `element_size` bytes in a lump, compressed lumps included.
+    pub fn lump_count(&self, index: usize, element_size: usize) -> usize {
+        self.lumps[index].data_size() / element_size
+    }
+
+    /// Text of the entity lump, as VBSP wrote it.
+    pub fn entities_text(&self) -> Result<String, BspError> {
+        let data = self.lump_data(lumps::ENTITIES)?;
+        let end = data.iter().position(|byte| *byte == 0).unwrap_or(data.len());
+        Ok(String::from_utf8_lossy(&data[..end]).into_owned())
+    }
+
+    /// Entities of the entity lump, the world first.
+    pub fn entities(&self) -> Result<Vec<BspEntity>, BspError> {
+        Ok(parse_entities(&self.entities_text()?))
+    }
+
+    /// Whether VRAD wrote LDR lightmaps.
+    pub fn has_ldr_lighting(&self) -> bool {
+        !self.lumps[lumps::LIGHTING].is_empty()
+    }
+
+    /// Whether VRAD wrote HDR lightmaps.
+    pub fn has_hdr_lighting(&self) -> bool {
+        !self.lumps[lumps::LIGHTING_HDR].is_empty()
+    }
+
+    /// Whether VVIS wrote visibility data.
+    pub fn has_visibility(&self) -> bool {
+        !self.lumps[lumps::VISIBILITY].is_empty()
+    }
+
+    /// Number of visibility clusters, `None` without visibility data or if it is compressed.
+    pub fn vis_clusters(&self) -> Option<u32> {
+        let data = self.lump_data(lumps::VISIBILITY).ok()?;
+        (data.len() >= 4).then(|| read_u32(data, 0))
+    }
+
+    /// Size of a leaf: version 19 stores the ambient lighting of each leaf in it.
+    pub fn leaf_size(&self) -> usize {
+        if self.version <= 19 { 56 } else { 32 }
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.lump_count(lumps::LEAFS, self.leaf_size())
+    }
+}
+
+fn lump_table(entries: &[[i32; 4]], format: BspFormat, file_size: usize) -> Result<[LumpInfo; LUMP_COUNT], BspError> {
+    let mut lumps = [LumpInfo::default(); LUMP_COUNT];
+    for (index, entry) in entries.iter().enumerate() {
+        let [offset, length, version] = match format {
+            BspFormat::Source => [entry[0], entry[1], entry[2]],
+            BspFormat::Left4Dead2 => [entry[1], entry[2], entry[0]],
+        };
+        let (Ok(offset), Ok(length)) = (usize::try_from(offset), usize::try_from(length)) else {
+            return Err(BspError::LumpOutOfBounds(index));
+        };
+        if length > 0 && (offset < HEADER_SIZE || offset.checked_add(length).is_none_or(|end| end > file_size)) {
+            return Err(BspError::LumpOutOfBounds(index));
+        }
+        lumps[index] = LumpInfo {
+            offset,
+            length,
+            version,
+            uncompressed_size: usize::try_from(entry[3]).unwrap_or_default(),
+        };
+    }
+    Ok(lumps)
+}
+
+/// Reads the `{ "key" "value" ... }` blocks of the entity lump.
+fn parse_entities(text: &str) -> Vec<BspEntity> {
+    let mut entities = Vec::new();
+    let mut current: Option<BspEntity> = None;
+    let mut key: Option<String> = None;
+    let mut chars = text.char_indices();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '{' => current = Some(BspEntity::default()),
+            '}' => {
+                entities.extend(current.take());
+                key = None;
+            }
+            '"' => {
+                let mut end = text.len();
+                for (index, c) in chars.by_ref() {
+                    if c == '"' {
+                        end = index;
+                        break;
+                    }
+                }
+                let token = text[start + 1..end].to_string();
+                match key.take() {
+                    Some(name) => {
+                        if let Some(entity) = &mut current {
+                            entity.properties.push((name, token));
+                        }
+                    }
+                    None => key = Some(token),
+                }
+            }
+            _ => {}
+        }
+    }
+    entities
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+fn read_i32(data: &[u8], offset: usize) -> i32 {
+    read_u32(data, offset) as i32
+}
@@ -0,0 +1,36 @@
+use std::{fmt, io};
+
+/// Error returned when reading a BSP file.
+#[derive(Debug)]
+pub enum BspError {
+    Io(io::Error),
+    /// The file does not start with `VBSP`.
+    NotABsp,
+    UnsupportedVersion(i32),
+    /// The file ends inside the header.
+    Truncated,
+    LumpOutOfBounds(usize),     // (lump index)
+    /// The lump is LZMA compressed (console and some CS:GO maps), its data cannot be read.
+    CompressedLump(usize),      // (lump index)
+    InvalidPakfile(&'static str), // (reason)
+}
+
+impl fmt::Display for BspError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read map: {e}"),
+            Self::NotABsp => write!(f, "not a Source BSP file"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported BSP version {version}"),
+            Self::Truncated => write!(f, "the file is too short for a BSP header"),
+            Self::LumpOutOfBounds(lump) => write!(f, "lump {lump} points outside of the file"),
+            Self::CompressedLump(lump) => write!(f, "lump {lump} is compressed"),
+            Self::InvalidPakfile(reason) => write!(f, "invalid pakfile: {reason}"),
+        }
+    }
+}
+
+impl From<io::Error> for BspError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
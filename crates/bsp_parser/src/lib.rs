@@ -0,0 +1,23 @@
+//! Reading of compiled Source maps (.bsp), for inspecting what a compile produced.
+//!
+//! [`Bsp`] reads the header of versions 19 to 21, Left 4 Dead 2's lump layout included, and gives access
+//! to the lumps: the entity text, the packed files, lighting and visibility presence and how much of each
+//! `MAX_MAP_*` limit is used. [`BspSummary`] gathers these counts in one value.
+
+mod bsp;
+pub use bsp::{Bsp, BspEntity, HEADER_SIZE, SUPPORTED_VERSIONS};
+
+mod error;
+pub use error::BspError;
+
+mod limits;
+pub use limits::LimitUsage;
+
+mod lump;
+pub use lump::{lumps, BspFormat, LumpInfo, LUMP_COUNT};
+
+mod pakfile;
+pub use pakfile::PakEntry;
+
+mod summary;
+pub use summary::BspSummary;
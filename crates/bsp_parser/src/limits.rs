@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{bsp::Bsp, lump::lumps};
+
+/// A `MAX_MAP_*` limit of the Source engine and what a lump counts against it.
+struct MapLimit {
+    name: &'static str,
+    lump: usize,
+    /// Size of one element in bytes, 1 for limits on the size of the lump.
+    element_size: usize,
+    max: u64,
+}
+
+/// Limits of `bspfile.h` in the Source SDK 2013, checked in this order.
+const MAP_LIMITS: &[MapLimit] = &[
+    MapLimit { name: "MAX_MAP_PLANES", lump: lumps::PLANES, element_size: 20, max: 65536 },
+    MapLimit { name: "MAX_MAP_BRUSHES", lump: lumps::BRUSHES, element_size: 12, max: 8192 },
+    MapLimit { name: "MAX_MAP_BRUSHSIDES", lump: lumps::BRUSHSIDES, element_size: 8, max: 65536 },
+    MapLimit { name: "MAX_MAP_TEXINFO", lump: lumps::TEXINFO, element_size: 72, max: 12288 },
+    MapLimit { name: "MAX_MAP_TEXDATA", lump: lumps::TEXDATA, element_size: 32, max: 2048 },
+    MapLimit { name: "MAX_MAP_VERTS", lump: lumps::VERTEXES, element_size: 12, max: 65536 },
+    MapLimit { name: "MAX_MAP_NODES", lump: lumps::NODES, element_size: 32, max: 65536 },
+    MapLimit { name: "MAX_MAP_FACES", lump: lumps::FACES, element_size: 56, max: 65536 },
+    MapLimit { name: "MAX_MAP_EDGES", lump: lumps::EDGES, element_size: 4, max: 256_000 },
+    MapLimit { name: "MAX_MAP_SURFEDGES", lump: lumps::SURFEDGES, element_size: 4, max: 512_000 },
+    MapLimit { name: "MAX_MAP_MODELS", lump: lumps::MODELS, element_size: 48, max: 1024 },
+    MapLimit { name: "MAX_MAP_LEAFFACES", lump: lumps::LEAFFACES, element_size: 2, max: 65536 },
+    MapLimit { name: "MAX_MAP_LEAFBRUSHES", lump: lumps::LEAFBRUSHES, element_size: 2, max: 65536 },
+    MapLimit { name: "MAX_MAP_AREAPORTALS", lump: lumps::AREAPORTALS, element_size: 12, max: 1024 },
+    MapLimit { name: "MAX_MAP_DISPINFO", lump: lumps::DISPINFO, element_size: 176, max: 2048 },
+    MapLimit { name: "MAX_MAP_OVERLAYS", lump: lumps::OVERLAYS, element_size: 352, max: 512 },
+    MapLimit { name: "MAX_MAP_ENTSTRING", lump: lumps::ENTITIES, element_size: 1, max: 256 * 1024 },
+    MapLimit { name: "MAX_MAP_VISIBILITY", lump: lumps::VISIBILITY, element_size: 1, max: 0x100_0000 },
+    MapLimit { name: "MAX_MAP_LIGHTING", lump: lumps::LIGHTING, element_size: 1, max: 0x100_0000 },
+    MapLimit { name: "MAX_MAP_LIGHTING (HDR)", lump: lumps::LIGHTING_HDR, element_size: 1, max: 0x100_0000 },
+];
+
+/// How much of an engine limit the map uses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitUsage {
+    /// Name of the limit in the SDK (`MAX_MAP_PLANES`).
+    pub name: String,
+    pub used: u64,
+    pub max: u64,
+}
+
+impl LimitUsage {
+    /// Used part of the limit, above 1.0 when it is exceeded.
+    pub fn fraction(&self) -> f64 {
+        self.used as f64 / self.max as f64
+    }
+
+    pub fn percent(&self) -> f64 {
+        self.fraction() * 100.0
+    }
+}
+
+impl Bsp {
+    /// Usage of every `MAX_MAP_*` limit the lumps count against.
+    pub fn limits(&self) -> Vec<LimitUsage> {
+        let leaf_limit = LimitUsage {
+            name: "MAX_MAP_LEAFS".to_string(),
+            used: self.leaf_count() as u64,
+            max: 65536,
+        };
+        MAP_LIMITS.iter()
+            .map(|limit| LimitUsage {
+                name: limit.name.to_string(),
+                used: self.lump_count(limit.lump, limit.element_size) as u64,
+                max: limit.max,
+            })
+            .chain(std::iter::once(leaf_limit))
+            .collect()
+    }
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+/// Number of lump entries in the header of every Source BSP.
+pub const LUMP_COUNT: usize = 64;
+
+/// Indices of the lumps read by this crate, as numbered in `bspfile.h`.
+pub mod lumps {
+    pub const ENTITIES: usize = 0;
+    pub const PLANES: usize = 1;
+    pub const TEXDATA: usize = 2;
+    pub const VERTEXES: usize = 3;
+    pub const VISIBILITY: usize = 4;
+    pub const NODES: usize = 5;
+    pub const TEXINFO: usize = 6;
+    pub const FACES: usize = 7;
+    pub const LIGHTING: usize = 8;
+    pub const LEAFS: usize = 10;
+    pub const EDGES: usize = 12;
+    pub const SURFEDGES: usize = 13;
+    pub const MODELS: usize = 14;
+    pub const LEAFFACES: usize = 16;
+    pub const LEAFBRUSHES: usize = 17;
+    pub const BRUSHES: usize = 18;
+    pub const BRUSHSIDES: usize = 19;
+    pub const AREAPORTALS: usize = 21;
+    pub const DISPINFO: usize = 26;
+    pub const PAKFILE: usize = 40;
+    pub const OVERLAYS: usize = 45;
+    pub const LIGHTING_HDR: usize = 53;
+}
+
+/// Where a lump is stored in the file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LumpInfo {
+    pub offset: usize,
+    pub length: usize,
+    pub version: i32,
+    /// Size of the data once decompressed, 0 when the lump is stored as is.
+    pub uncompressed_size: usize,
+}
+
+impl LumpInfo {
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.uncompressed_size != 0
+    }
+
+    /// Size of the data the lump holds, decompressed.
+    pub fn data_size(&self) -> usize {
+        if self.is_compressed() { self.uncompressed_size } else { self.length }
+    }
+}
+
+/// Layout of the lump entries of the header, which a few games changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BspFormat {
+    /// `offset, length, version, uncompressed size`, used by most Source games.
+    #[default]
+    Source,
+    /// `version, offset, length, uncompressed size`, used by Left 4 Dead 2.
+    Left4Dead2,
+}
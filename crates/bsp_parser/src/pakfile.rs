@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bsp::{read_u16, read_u32, Bsp},
+    error::BspError,
+    lump::lumps,
+};
+
+const END_OF_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+const END_OF_DIRECTORY_SIZE: usize = 22;
+const DIRECTORY_ENTRY_SIGNATURE: u32 = 0x0201_4b50;
+const DIRECTORY_ENTRY_SIZE: usize = 46;
+
+/// A file packed into the map, as listed by the zip directory of the pakfile lump.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PakEntry {
+    /// Path inside the game folder, using `/`.
+    pub name: String,
+    pub size: u64,
+    pub compressed_size: u64,
+    /// Zip compression method, 0 when stored as is like BSPZIP does.
+    pub compression: u16,
+}
+
+impl Bsp {
+    /// Raw zip archive of the pakfile lump, empty when nothing is packed.
+    pub fn pakfile_data(&self) -> Result<&[u8], BspError> {
+        self.lump_data(lumps::PAKFILE)
+    }
+
+    /// Files packed into the map, in archive order.
+    pub fn pakfile_entries(&self) -> Result<Vec<PakEntry>, BspError> {
+        let data = self.pakfile_data()?;
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // The directory is found from its end record, after which only the archive comment comes
+        let end = (0..=data.len().saturating_sub(END_OF_DIRECTORY_SIZE))
+            .rev()
+            .find(|&offset| read_u32(data, offset) == END_OF_DIRECTORY_SIGNATURE)
+            .ok_or(BspError::InvalidPakfile("no end of central directory"))?;
+        let entry_count = read_u16(data, end + 10) as usize;
+        let mut offset = read_u32(data, end + 16) as usize;
+
+        let mut entries = Vec::with_capacity(entry_count);
+        for _ in 0..entry_count {
+            if offset + DIRECTORY_ENTRY_SIZE > end || read_u32(data, offset) != DIRECTORY_ENTRY_SIGNATURE {
+                return Err(BspError::InvalidPakfile("broken central directory"));
+            }
+            let name_length = read_u16(data, offset + 28) as usize;
+            let extra_length = read_u16(data, offset + 30) as usize;
+            let comment_length = read_u16(data, offset + 32) as usize;
+            let name = data.get(offset + DIRECTORY_ENTRY_SIZE..offset + DIRECTORY_ENTRY_SIZE + name_length)
+                .ok_or(BspError::InvalidPakfile("file name outside of the archive"))?;
+
+            entries.push(PakEntry {
+                name: String::from_utf8_lossy(name).replace('\\', "/"),
+                size: read_u32(data, offset + 24) as u64,
+                compressed_size: read_u32(data, offset + 20) as u64,
+                compression: read_u16(data, offset + 10),
+            });
+            offset += DIRECTORY_ENTRY_SIZE + name_length + extra_length + comment_length;
+        }
+        Ok(entries)
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{bsp::Bsp, error::BspError, limits::LimitUsage, lump::{lumps, BspFormat}};
+
+/// What came out of a compile, read from the .bsp.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BspSummary {
+    pub version: i32,
+    pub map_revision: i32,
+    pub format: BspFormat,
+    pub file_size: u64,
+    pub entities: usize,
+    pub leafs: usize,
+    pub has_visibility: bool,
+    /// Clusters of the visibility data, `None` without it.
+    pub vis_clusters: Option<u32>,
+    pub ldr_lighting: bool,
+    pub hdr_lighting: bool,
+    pub packed_files: usize,
+    /// Size of the pakfile lump in bytes.
+    pub pakfile_size: u64,
+    pub limits: Vec<LimitUsage>,
+}
+
+impl BspSummary {
+    /// The limit the map is closest to.
+    pub fn closest_limit(&self) -> Option<&LimitUsage> {
+        self.limits.iter().max_by(|a, b| a.fraction().total_cmp(&b.fraction()))
+    }
+
+    /// Limits used above `fraction`, the most used first.
+    pub fn limits_above(&self, fraction: f64) -> Vec<&LimitUsage> {
+        let mut limits: Vec<&LimitUsage> = self.limits.iter().filter(|limit| limit.fraction() > fraction).collect();
+        limits.sort_by(|a, b| b.fraction().total_cmp(&a.fraction()));
+        limits
+    }
+}
+
+impl Bsp {
+    /// Reads the counts describing the compiled map. Fails only on an unreadable entity lump or pakfile.
+    pub fn summary(&self) -> Result<BspSummary, BspError> {
+        let entities = self.entities()?.len();
+        let pakfile = self.pakfile_entries()?;
+        Ok(BspSummary {
+            version: self.version,
+            map_revision: self.map_revision,
+            format: self.format,
+            file_size: self.file_size() as u64,
+            entities,
+            leafs: self.leaf_count(),
+            has_visibility: self.has_visibility(),
+            vis_clusters: self.vis_clusters(),
+            ldr_lighting: self.has_ldr_lighting(),
+            hdr_lighting: self.has_hdr_lighting(),
+            packed_files: pakfile.len(),
+            pakfile_size: self.lump(lumps::PAKFILE).length as u64,
+            limits: self.limits(),
+        })
+    }
+}
+
+impl fmt::Display for BspSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} leafs, {} entities, ", self.leafs, self.entities)?;
+        match (self.has_visibility, self.vis_clusters) {
+            (true, Some(clusters)) => write!(f, "vis present ({clusters} clusters), ")?,
+            (true, None) => write!(f, "vis present, ")?,
+            (false, _) => write!(f, "no vis, ")?,
+        }
+        let lighting = match (self.ldr_lighting, self.hdr_lighting) {
+            (true, true) => "LDR + HDR lighting",
+            (true, false) => "LDR lighting",
+            (false, true) => "HDR lighting",
+            (false, false) => "fullbright",
+        };
+        write!(f, "{lighting}, {} packed files", self.packed_files)?;
+        if let Some(limit) = self.closest_limit() {
+            write!(f, ", {:.0}% of {}", limit.percent(), limit.name)?;
+        }
+        Ok(())
+    }
+}
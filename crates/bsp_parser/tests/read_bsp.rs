@@ -0,0 +1,172 @@
+use bsp_parser::{lumps, Bsp, BspError, BspFormat, HEADER_SIZE};
+
+/// Writes a BSP holding the given lumps, in the lump layout of `format`.
+fn build_bsp(version: i32, format: BspFormat, lump_data: &[(usize, Vec<u8>)]) -> Vec<u8> {
+    let mut header = vec![0u8; HEADER_SIZE];
+    header[..4].copy_from_slice(b"VBSP");
+    header[4..8].copy_from_slice(&version.to_le_bytes());
+    header[HEADER_SIZE - 4..].copy_from_slice(&7i32.to_le_bytes());
+
+    let mut body = Vec::new();
+    for (index, data) in lump_data {
+        let offset = (HEADER_SIZE + body.len()) as i32;
+        let fields = match format {
+            BspFormat::Source => [offset, data.len() as i32, 0, 0],
+            BspFormat::Left4Dead2 => [0, offset, data.len() as i32, 0],
+        };
+        for (field, value) in fields.iter().enumerate() {
+            let start = 8 + index * 16 + field * 4;
+            header[start..start + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        body.extend_from_slice(data);
+    }
+    header.extend(body);
+    header
+}
+
+/// A stored zip archive like BSPZIP writes, with the given files.
+fn build_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = Vec::new();
+    let mut directory = Vec::new();
+    for (name, data) in files {
+        let local_offset = zip.len() as u32;
+        zip.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        zip.extend_from_slice(&[0; 14]);
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&0u16.to_le_bytes());
+        zip.extend_from_slice(name.as_bytes());
+        zip.extend_from_slice(data);
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&[0; 16]);
+        directory.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        directory.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        directory.extend_from_slice(&[0; 12]);
+        directory.extend_from_slice(&local_offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+    let directory_offset = zip.len() as u32;
+    zip.extend_from_slice(&directory);
+    zip.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    zip.extend_from_slice(&[0; 4]);
+    zip.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    zip.extend_from_slice(&directory_offset.to_le_bytes());
+    zip.extend_from_slice(&0u16.to_le_bytes());
+    zip
+}
+
+const ENTITIES: &str = "{\n\"classname\" \"worldspawn\"\n\"skyname\" \"sky_day01_01\"\n}\n\
+{\n\"classname\" \"info_player_start\"\n\"origin\" \"0 0 64\"\n}\n\0";
+
+fn compiled_map(version: i32, format: BspFormat) -> Vec<u8> {
+    let mut visibility = 42u32.to_le_bytes().to_vec();
+    visibility.extend([0; 12]);
+    build_bsp(version, format, &[
+        (lumps::ENTITIES, ENTITIES.as_bytes().to_vec()),
+        (lumps::PLANES, vec![0; 20 * 49152]),
+        (lumps::VISIBILITY, visibility),
+        (lumps::LEAFS, vec![0; 56 * 10]),
+        (lumps::LIGHTING_HDR, vec![0; 64]),
+        (lumps::PAKFILE, build_zip(&[("maps/test.nav", b"NAV"), ("materials\\custom\\wall.vmt", b"VMT")])),
+    ])
+}
+
+#[test]
+fn reads_header_and_lumps() {
+    let bsp = Bsp::parse(compiled_map(20, BspFormat::Source)).unwrap();
+    assert_eq!(bsp.version, 20);
+    assert_eq!(bsp.map_revision, 7);
+    assert_eq!(bsp.format, BspFormat::Source);
+    assert_eq!(bsp.lump_count(lumps::PLANES, 20), 49152);
+    // Leafs lost their ambient lighting after version 19
+    assert_eq!(bsp.leaf_count(), 56 * 10 / 32);
+    assert_eq!(Bsp::parse(compiled_map(19, BspFormat::Source)).unwrap().leaf_count(), 10);
+}
+
+#[test]
+fn reads_entities() {
+    let bsp = Bsp::parse(compiled_map(20, BspFormat::Source)).unwrap();
+    assert!(bsp.entities_text().unwrap().starts_with("{\n\"classname\" \"worldspawn\""));
+
+    let entities = bsp.entities().unwrap();
+    assert_eq!(entities.len(), 2);
+    assert_eq!(entities[0].classname(), "worldspawn");
+    assert_eq!(entities[0].get("SKYNAME"), Some("sky_day01_01"));
+    assert_eq!(entities[1].get("origin"), Some("0 0 64"));
+}
+
+#[test]
+fn detects_lighting_and_visibility() {
+    let bsp = Bsp::parse(compiled_map(20, BspFormat::Source)).unwrap();
+    assert!(bsp.has_visibility());
+    assert_eq!(bsp.vis_clusters(), Some(42));
+    assert!(!bsp.has_ldr_lighting());
+    assert!(bsp.has_hdr_lighting());
+
+    let unlit = Bsp::parse(build_bsp(20, BspFormat::Source, &[(lumps::ENTITIES, b"\0".to_vec())])).unwrap();
+    assert!(!unlit.has_visibility());
+    assert_eq!(unlit.vis_clusters(), None);
+    assert!(!unlit.has_hdr_lighting());
+}
+
+#[test]
+fn lists_pakfile() {
+    let bsp = Bsp::parse(compiled_map(20, BspFormat::Source)).unwrap();
+    let entries = bsp.pakfile_entries().unwrap();
+    let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["maps/test.nav", "materials/custom/wall.vmt"]);
+    assert_eq!(entries[0].size, 3);
+    assert_eq!(entries[0].compression, 0);
+
+    let empty = Bsp::parse(build_bsp(20, BspFormat::Source, &[])).unwrap();
+    assert!(empty.pakfile_entries().unwrap().is_empty());
+}
+
+#[test]
+fn rejects_broken_pakfile() {
+    let bsp = Bsp::parse(build_bsp(20, BspFormat::Source, &[(lumps::PAKFILE, vec![1; 40])])).unwrap();
+    assert!(matches!(bsp.pakfile_entries(), Err(BspError::InvalidPakfile(_))));
+}
+
+#[test]
+fn reads_left_4_dead_2_layout() {
+    let bsp = Bsp::parse(compiled_map(21, BspFormat::Left4Dead2)).unwrap();
+    assert_eq!(bsp.format, BspFormat::Left4Dead2);
+    assert_eq!(bsp.entities().unwrap().len(), 2);
+    assert_eq!(bsp.pakfile_entries().unwrap().len(), 2);
+
+    // Only version 21 may use it
+    assert!(matches!(Bsp::parse(compiled_map(20, BspFormat::Left4Dead2)), Err(BspError::LumpOutOfBounds(_))));
+}
+
+#[test]
+fn rejects_invalid_files() {
+    assert!(matches!(Bsp::parse(b"VBSP".to_vec()), Err(BspError::Truncated)));
+    assert!(matches!(Bsp::parse(vec![0; HEADER_SIZE]), Err(BspError::NotABsp)));
+    assert!(matches!(Bsp::parse(build_bsp(29, BspFormat::Source, &[])), Err(BspError::UnsupportedVersion(29))));
+
+    let mut data = build_bsp(20, BspFormat::Source, &[(lumps::PLANES, vec![0; 40])]);
+    data.truncate(data.len() - 1);
+    assert!(matches!(Bsp::parse(data), Err(BspError::LumpOutOfBounds(lumps::PLANES))));
+}
+
+#[test]
+fn summarizes_map() {
+    let summary = Bsp::parse(compiled_map(20, BspFormat::Source)).unwrap().summary().unwrap();
+    assert_eq!(summary.entities, 2);
+    assert_eq!(summary.packed_files, 2);
+
+    let planes = summary.closest_limit().unwrap();
+    assert_eq!(planes.name, "MAX_MAP_PLANES");
+    assert_eq!(planes.used, 49152);
+    assert_eq!(summary.limits_above(0.5).len(), 1);
+    assert_eq!(
+        summary.to_string(),
+        "17 leafs, 2 entities, vis present (42 clusters), HDR lighting, 2 packed files, 75% of MAX_MAP_PLANES"
+    );
+}
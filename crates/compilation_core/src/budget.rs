@@ -0,0 +1,170 @@
+//! Limit budgets: the `MAX_MAP_*` usage of each compiled map, kept across compiles to warn when
+//! a map gets close to a limit or its usage grows sharply.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bsp_parser::{BspSummary, LimitUsage};
+use serde::{Deserialize, Serialize};
+use vmflow_config_types::preset::LimitBudget;
+
+/// Compiles kept per map, the oldest are dropped.
+const MAX_ENTRIES_PER_MAP: usize = 100;
+
+/// Maps compiled at the same time record into the same file.
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Limit usage of a map after one successful compile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetEntry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub limits: Vec<LimitUsage>,
+}
+
+impl BudgetEntry {
+    pub fn now(limits: Vec<LimitUsage>) -> Self {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        Self { timestamp, limits }
+    }
+
+    pub fn limit(&self, name: &str) -> Option<&LimitUsage> {
+        self.limits.iter().find(|limit| limit.name == name)
+    }
+}
+
+/// Limit usage history of every compiled map, by map source path.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BudgetHistory {
+    pub maps: BTreeMap<PathBuf, Vec<BudgetEntry>>,
+}
+
+impl BudgetHistory {
+    /// Reads the history file, a missing file gives an empty history.
+    ///
+    /// A file that cannot be parsed is an error rather than an empty history, saving over it would lose
+    /// the usage of every map.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Compiles of a map, the oldest first.
+    pub fn entries(&self, map_path: &Path) -> &[BudgetEntry] {
+        self.maps.get(map_path).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn record(&mut self, map_path: &Path, entry: BudgetEntry) {
+        let entries = self.maps.entry(map_path.to_path_buf()).or_default();
+        entries.push(entry);
+        if entries.len() > MAX_ENTRIES_PER_MAP {
+            entries.drain(..entries.len() - MAX_ENTRIES_PER_MAP);
+        }
+    }
+}
+
+/// Why a limit of a compiled map is worth a warning.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AlertKind {
+    /// The map uses more than the limit, the engine refuses to load it.
+    Exceeded,
+    /// The usage is above the warning threshold of the preset.
+    NearLimit,
+    /// The usage grew by more than the jump threshold since the last compile.
+    Jump(u64), // (previously used)
+}
+
+/// A limit of a compiled map crossing a threshold of the budget.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LimitAlert {
+    pub limit: LimitUsage,
+    pub kind: AlertKind,
+}
+
+impl LimitAlert {
+    /// Whether the map is broken rather than at risk.
+    pub fn is_error(&self) -> bool {
+        self.kind == AlertKind::Exceeded
+    }
+}
+
+impl fmt::Display for LimitAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limit = &self.limit;
+        match &self.kind {
+            AlertKind::Exceeded => write!(f, "{} exceeded: {}/{} ({:.0}%)", limit.name, limit.used, limit.max, limit.percent()),
+            AlertKind::NearLimit => write!(f, "{:.0}% of {} used ({}/{})", limit.percent(), limit.name, limit.used, limit.max),
+            AlertKind::Jump(previous) => {
+                let previous_percent = *previous as f64 / limit.max as f64 * 100.0;
+                write!(
+                    f,
+                    "{} jumped from {previous_percent:.0}% to {:.0}% since the last compile ({previous} -> {})",
+                    limit.name,
+                    limit.percent(),
+                    limit.used
+                )
+            }
+        }
+    }
+}
+
+/// Compares the limits of a compile with the budget and with the previous compile of the map.
+pub fn check_budget(limits: &[LimitUsage], previous: Option<&BudgetEntry>, budget: &LimitBudget) -> Vec<LimitAlert> {
+    let mut alerts = Vec::new();
+    for limit in limits {
+        let percent = limit.percent();
+        if percent > 100.0 {
+            alerts.push(LimitAlert { limit: limit.clone(), kind: AlertKind::Exceeded });
+        } else if percent >= f64::from(budget.warn_percent) {
+            alerts.push(LimitAlert { limit: limit.clone(), kind: AlertKind::NearLimit });
+        }
+
+        if let Some(before) = previous.and_then(|entry| entry.limit(&limit.name))
+            && limit.used > before.used
+            && percent - before.percent() >= f64::from(budget.jump_percent)
+        {
+            alerts.push(LimitAlert { limit: limit.clone(), kind: AlertKind::Jump(before.used) });
+        }
+    }
+    alerts
+}
+
+/// Checks the limits of a compiled map and, with a history file, records them in it.
+///
+/// Without a history file, jumps cannot be found: only the thresholds are checked.
+/// A history file that cannot be read is left as it is and returned as an error.
+pub fn record_compile(
+    history_path: Option<&Path>,
+    map_path: &Path,
+    summary: &BspSummary,
+    budget: &LimitBudget,
+) -> io::Result<Vec<LimitAlert>> {
+    let Some(history_path) = history_path else {
+        return Ok(check_budget(&summary.limits, None, budget));
+    };
+
+    // The GUI and the CLI may name the same map by different paths
+    let map_path = fs::canonicalize(map_path).unwrap_or_else(|_| map_path.to_path_buf());
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut history = BudgetHistory::load(history_path)?;
+    let alerts = check_budget(&summary.limits, history.entries(&map_path).last(), budget);
+    history.record(&map_path, BudgetEntry::now(summary.limits.clone()));
+    history.save(history_path)?;
+    Ok(alerts)
+}
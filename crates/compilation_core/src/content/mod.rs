@@ -0,0 +1,319 @@
+//! Detection of the custom content a map uses: the loose files it references that the game
+//! does not ship in its VPKs, and the files made for the level (.nav, radar, soundscapes...).
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use vmf_parser::{Vmf, VmfError};
+
+mod pcf;
+mod search_paths;
+mod vpk;
+pub use pcf::particle_system_names;
+pub use search_paths::SearchPaths;
+
+/// Material parameters pointing to textures, without the `.vtf` extension.
+const TEXTURE_PARAMETERS: &[&str] = &[
+    "$basetexture",
+    "$basetexture2",
+    "$bumpmap",
+    "$bumpmap2",
+    "$normalmap",
+    "$detail",
+    "$envmap",
+    "$envmapmask",
+    "$blendmodulatetexture",
+    "$selfillummask",
+    "$phongexponenttexture",
+    "$lightwarptexture",
+];
+
+/// Files making up a compiled model next to its .mdl.
+const MODEL_EXTENSIONS: &[&str] = &["vvd", "phy", "ani", "vtx", "dx90.vtx", "dx80.vtx", "sw.vtx"];
+
+/// Extensions of the sound files entities play, relative to `sound/`.
+const SOUND_EXTENSIONS: &[&str] = &[".wav", ".mp3", ".ogg"];
+
+/// Characters put before a sound path to change how it plays (`)`, `#`, `*`...).
+const SOUND_PREFIXES: &[char] = &['*', '#', '@', '>', '<', '^', ')', '(', '}', '$', '!', '?', '&', '~', '`', '+', '%'];
+
+/// Files made for a single level, `{map}` standing for the map name.
+const LEVEL_FILES: &[&str] = &[
+    "maps/{map}.nav",
+    "maps/{map}.txt",
+    "maps/{map}.kv",
+    "maps/{map}_level_sounds.txt",
+    "maps/{map}_particles.txt",
+    "maps/{map}_commentary.txt",
+    "scripts/soundscapes_{map}.txt",
+    "resource/overviews/{map}.txt",
+    "resource/overviews/{map}_radar.dds",
+    "resource/overviews/{map}_radar_spectate.dds",
+    "materials/overviews/{map}.vmt",
+];
+
+/// Where the files of a game are found, and which ones it ships.
+#[derive(Debug, Default)]
+pub struct GameContent {
+    /// Folders searched for loose files, in order.
+    pub roots: Vec<PathBuf>,
+    /// Files in the mounted VPKs, lowercase.
+    stock: HashSet<String>,
+    /// VPKs that could not be read, their files are not known to be stock.
+    pub errors: Vec<String>,
+}
+
+impl GameContent {
+    /// Reads the search paths of the game and lists the files of its VPKs.
+    pub fn load(game_dir: &Path) -> Self {
+        let search_paths = SearchPaths::read(game_dir);
+        let mut stock = HashSet::new();
+        let mut errors = Vec::new();
+        for vpk in &search_paths.vpks {
+            if let Err(e) = vpk::list_files(vpk, &mut stock) {
+                errors.push(format!("{}: {e}", vpk.display()));
+            }
+        }
+        Self { roots: content_roots(game_dir, search_paths.folders), stock, errors }
+    }
+
+    /// Whether the game ships the file in one of its VPKs.
+    pub fn is_stock(&self, relative: &str) -> bool {
+        self.stock.contains(relative)
+    }
+
+    /// First loose file at `relative` under the roots, unless the game ships it.
+    pub fn find_custom(&self, relative: &str) -> Option<PathBuf> {
+        if self.is_stock(relative) {
+            return None;
+        }
+        self.roots.iter().map(|root| root.join(relative)).find(|path| path.is_file())
+    }
+
+    /// Custom files with the extension under a folder of the roots (`particles`, `pcf`), first root first.
+    ///
+    /// A file found in several roots is only returned for the first one, like the game loads it.
+    pub fn custom_files(&self, folder: &str, extension: &str) -> Vec<(String, PathBuf)> {
+        let mut files: BTreeMap<String, PathBuf> = BTreeMap::new();
+        for root in &self.roots {
+            let mut pending = vec![root.join(folder)];
+            while let Some(dir) = pending.pop() {
+                let Ok(entries) = fs::read_dir(&dir) else {
+                    continue;
+                };
+                for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+                    if path.is_dir() {
+                        pending.push(path);
+                        continue;
+                    }
+                    if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(extension)) {
+                        continue;
+                    }
+                    let Ok(relative) = path.strip_prefix(root) else {
+                        continue;
+                    };
+                    let relative = normalize(&relative.to_string_lossy());
+                    if !self.is_stock(&relative) && !files.contains_key(&relative) {
+                        files.insert(relative, path);
+                    }
+                }
+            }
+        }
+        files.into_iter().collect()
+    }
+}
+
+/// Effect names of the `info_particle_system` entities of a map, lowercase.
+pub fn collect_particle_effects(vmf_path: &Path) -> Result<BTreeSet<String>, VmfError> {
+    let vmf = Vmf::load(vmf_path)?;
+    Ok(vmf.entities_by_class("info_particle_system")
+        .filter_map(|entity| entity.get("effect_name"))
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect())
+}
+
+/// Folders custom content is looked up in: the game folder, each `custom/*` folder, then the other search paths.
+fn content_roots(game_dir: &Path, search_folders: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut roots = vec![game_dir.to_path_buf()];
+    if let Ok(entries) = fs::read_dir(game_dir.join("custom")) {
+        let mut custom: Vec<PathBuf> = entries.filter_map(Result::ok).map(|e| e.path()).filter(|p| p.is_dir()).collect();
+        custom.sort();
+        roots.extend(custom);
+    }
+    for folder in search_folders {
+        if !roots.contains(&folder) {
+            roots.push(folder);
+        }
+    }
+    roots
+}
+
+/// Finds the custom materials, textures, models, sounds, particles and level files of a map.
+///
+/// Returns `(path relative to the game folder, file on disk)` pairs, the path using `/`.
+/// Materials used inside models are not found, they are stored in the compiled .mdl.
+pub fn collect_referenced_content(vmf_path: &Path, content: &GameContent) -> Result<Vec<(String, PathBuf)>, VmfError> {
+    let vmf = Vmf::load(vmf_path)?;
+    let map_name = vmf_path.file_stem().unwrap_or_default().to_string_lossy().to_lowercase();
+    let mut found = Collector { content, found: BTreeMap::new() };
+
+    for side in vmf.all_solids().flat_map(|solid| &solid.sides) {
+        found.add_material(&side.material);
+    }
+    if let Some(skyname) = &vmf.world.skyname {
+        found.add_skybox(skyname);
+    }
+    let world_values = vmf.world.properties.iter().map(|(key, value)| (key.as_str(), value.as_str()));
+    let entity_values = vmf.entities.iter().flat_map(|entity| &entity.properties).map(|(key, value)| (key.as_str(), value.as_str()));
+    for (key, value) in world_values.chain(entity_values) {
+        found.add_key_value(key, value);
+    }
+
+    for level_file in LEVEL_FILES {
+        found.add_file(&level_file.replace("{map}", &map_name));
+    }
+    found.add_particle_manifest(&format!("maps/{map_name}_particles.txt"));
+
+    Ok(found.found.into_iter().collect())
+}
+
+struct Collector<'a> {
+    content: &'a GameContent,
+    found: BTreeMap<String, PathBuf>,
+}
+
+impl Collector<'_> {
+    /// Adds what an entity or world key points to, judging by the key and the value.
+    fn add_key_value(&mut self, key: &str, value: &str) {
+        let value = normalize(value);
+        match key.to_lowercase().as_str() {
+            "material" | "texture" | "detailmaterial" | "ropematerial" | "overlaymaterial" => self.add_material(&value),
+            "detailvbsp" => {
+                self.add_file(&value);
+            }
+            _ if value.ends_with(".vmt") => {
+                // Sprites are set as models, relative to `materials/`
+                self.add_material(value.trim_end_matches(".vmt"));
+            }
+            _ if value.ends_with(".mdl") => self.add_model(&value),
+            _ if value.ends_with(".pcf") => {
+                self.add_file(&value);
+            }
+            _ if SOUND_EXTENSIONS.iter().any(|extension| value.ends_with(extension)) => {
+                let sound = value.trim_start_matches(SOUND_PREFIXES);
+                self.add_file(&format!("sound/{sound}"));
+            }
+            _ => {}
+        }
+    }
+
+    fn add_skybox(&mut self, skyname: &str) {
+        let skyname = normalize(skyname);
+        for variant in ["", "_hdr"] {
+            for side in ["bk", "dn", "ft", "lf", "rt", "up"] {
+                self.add_material(&format!("skybox/{skyname}{variant}{side}"));
+            }
+        }
+    }
+
+    /// Adds a material by its name relative to `materials/`, and the textures and materials it uses.
+    fn add_material(&mut self, name: &str) {
+        let name = normalize(name);
+        let relative = format!("materials/{}.vmt", name.trim_end_matches(".vmt"));
+        if self.found.contains_key(&relative) || !self.add_file(&relative) {
+            return;
+        }
+        let Ok(vmt) = fs::read_to_string(&self.found[&relative]) else {
+            return;
+        };
+
+        for (key, value) in key_values(&vmt) {
+            let key = key.to_lowercase();
+            let value = normalize(value);
+            if TEXTURE_PARAMETERS.contains(&key.as_str()) && value != "env_cubemap" {
+                self.add_file(&format!("materials/{}.vtf", value.trim_end_matches(".vtf")));
+            } else if key == "include" {
+                // Patch materials, the path includes `materials/`
+                self.add_material(value.trim_start_matches("materials/"));
+            }
+        }
+    }
+
+    fn add_model(&mut self, relative: &str) {
+        if !self.add_file(relative) {
+            return;
+        }
+        let stem = relative.trim_end_matches(".mdl");
+        for extension in MODEL_EXTENSIONS {
+            self.add_file(&format!("{stem}.{extension}"));
+        }
+    }
+
+    /// Adds a particle manifest and the custom particle files it lists.
+    fn add_particle_manifest(&mut self, relative: &str) {
+        if !self.add_file(relative) {
+            return;
+        }
+        let Ok(manifest) = fs::read_to_string(&self.found[relative]) else {
+            return;
+        };
+        for (key, value) in key_values(&manifest) {
+            if key.eq_ignore_ascii_case("file") {
+                // `!` preloads the file, it is not part of the path
+                self.add_file(normalize(value).trim_start_matches('!'));
+            }
+        }
+    }
+
+    /// Adds the custom file at `relative`, returns whether one exists.
+    fn add_file(&mut self, relative: &str) -> bool {
+        if self.found.contains_key(relative) {
+            return true;
+        }
+        match self.content.find_custom(relative) {
+            Some(path) => {
+                self.found.insert(relative.to_string(), path);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn normalize(value: &str) -> String {
+    value.trim().replace('\\', "/").to_lowercase()
+}
+
+/// Extracts `"key" "value"` and `key value` pairs of KeyValues text (VMT, manifests), ignoring nesting.
+fn key_values(text: &str) -> impl Iterator<Item = (&str, &str)> {
+    text.lines().filter_map(|line| {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let mut tokens = tokens(line);
+        let key = tokens.next()?;
+        let value = tokens.next()?;
+        Some((key, value))
+    })
+}
+
+/// Splits a line into quoted or whitespace separated tokens.
+fn tokens(mut line: &str) -> impl Iterator<Item = &str> {
+    std::iter::from_fn(move || {
+        line = line.trim_start();
+        if line.is_empty() || line.starts_with(['{', '}']) {
+            return None;
+        }
+        let (token, rest) = match line.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                (&quoted[..end], quoted.get(end + 1..).unwrap_or_default())
+            }
+            None => line.split_at(line.find(char::is_whitespace).unwrap_or(line.len())),
+        };
+        line = rest;
+        Some(token)
+    })
+}
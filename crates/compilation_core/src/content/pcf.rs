@@ -0,0 +1,76 @@
+//! Names of the particle systems defined in .pcf files (binary DMX).
+
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+/// Element type of the particle systems, other elements are their operators and children.
+const PARTICLE_SYSTEM_TYPE: &str = "DmeParticleSystemDefinition";
+
+/// Returns the names of the particle systems defined in a .pcf file.
+pub fn particle_system_names(pcf: &Path) -> io::Result<Vec<String>> {
+    let data = fs::read(pcf)?;
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+    // `<!-- dmx encoding binary 2 format pcf 1 -->\n` then a null byte
+    let header_end = data.iter().position(|byte| *byte == 0).ok_or_else(|| invalid("missing DMX header"))?;
+    let header = String::from_utf8_lossy(&data[..header_end]);
+    let version: u32 = header.split_whitespace()
+        .skip_while(|word| *word != "binary")
+        .nth(1)
+        .and_then(|version| version.parse().ok())
+        .ok_or_else(|| invalid("not a binary DMX file"))?;
+    if !(1..=5).contains(&version) {
+        return Err(invalid(&format!("unsupported DMX version {version}")));
+    }
+    let mut reader = &data[header_end + 1..];
+
+    // Version 2 adds a string dictionary for element types, version 4 uses it for names too
+    let dictionary = if version >= 2 {
+        let count = read_u32(&mut reader)?;
+        (0..count).map(|_| read_string(&mut reader)).collect::<io::Result<Vec<_>>>()?
+    } else {
+        Vec::new()
+    };
+    let read_dictionary_string = |reader: &mut &[u8], in_dictionary: bool| -> io::Result<String> {
+        if !in_dictionary {
+            return read_string(reader);
+        }
+        let index = if version >= 5 { read_u32(reader)? as usize } else { read_u16(reader)? as usize };
+        dictionary.get(index).cloned().ok_or_else(|| invalid("string index out of range"))
+    };
+
+    let element_count = read_u32(&mut reader)?;
+    let mut names = Vec::new();
+    for _ in 0..element_count {
+        let element_type = read_dictionary_string(&mut reader, version >= 2)?;
+        let name = read_dictionary_string(&mut reader, version >= 4)?;
+        let mut guid = [0; 16];
+        reader.read_exact(&mut guid)?;
+        if element_type == PARTICLE_SYSTEM_TYPE {
+            names.push(name);
+        }
+    }
+    Ok(names)
+}
+
+fn read_u16(reader: &mut &[u8]) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(reader: &mut &[u8]) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_string(reader: &mut &[u8]) -> io::Result<String> {
+    let end = reader.iter().position(|byte| *byte == 0).ok_or(io::ErrorKind::UnexpectedEof)?;
+    let text = String::from_utf8_lossy(&reader[..end]).into_owned();
+    *reader = &reader[end + 1..];
+    Ok(text)
+}
@@ -0,0 +1,95 @@
+//! Folders and VPKs a game mounts, read from the `SearchPaths` of its `gameinfo.txt`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use vmf_parser::parse_keyvalues;
+
+/// What a game mounts, in search order.
+#[derive(Debug, Default)]
+pub struct SearchPaths {
+    /// Folders with loose files.
+    pub folders: Vec<PathBuf>,
+    /// `_dir.vpk` files of the mounted archives.
+    pub vpks: Vec<PathBuf>,
+}
+
+impl SearchPaths {
+    /// Reads the search paths of the game in `game_dir`.
+    ///
+    /// Without a readable `gameinfo.txt`, the game folder and its own VPKs are used.
+    pub fn read(game_dir: &Path) -> Self {
+        let mut paths = Self::default();
+        let entries = read_gameinfo_entries(game_dir);
+        if entries.is_empty() {
+            paths.folders.push(game_dir.to_path_buf());
+            paths.vpks = dir_vpks(game_dir);
+            return paths;
+        }
+
+        // Relative entries start from the folder holding every game (`.../Half-Life 2`)
+        let base_dir = game_dir.parent().unwrap_or(game_dir);
+        for entry in entries {
+            let entry = entry
+                .replace("|gameinfo_path|", &format!("{}/", game_dir.display()))
+                .replace("|all_source_engine_paths|", &format!("{}/", base_dir.display()));
+            let path = base_dir.join(entry.trim_end_matches(['/', '\\', '.']));
+
+            if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("vpk")) {
+                // `pak01.vpk` refers to `pak01_dir.vpk` and its numbered archives
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                let dir_vpk = if stem.ends_with("_dir") { path.clone() } else { path.with_file_name(format!("{stem}_dir.vpk")) };
+                push_unique(&mut paths.vpks, dir_vpk);
+            } else if path.file_name().is_some_and(|name| name == "*") {
+                let parent = path.parent().unwrap_or(base_dir);
+                let mut folders: Vec<PathBuf> = fs::read_dir(parent).into_iter().flatten()
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir())
+                    .collect();
+                folders.sort();
+                folders.into_iter().for_each(|folder| push_unique(&mut paths.folders, folder));
+            } else {
+                push_unique(&mut paths.folders, path);
+            }
+        }
+        paths.folders.retain(|folder| folder.is_dir());
+        paths.vpks.retain(|vpk| vpk.is_file());
+        paths
+    }
+}
+
+fn push_unique(paths: &mut Vec<PathBuf>, path: PathBuf) {
+    if !paths.contains(&path) {
+        paths.push(path);
+    }
+}
+
+/// Values of `GameInfo/FileSystem/SearchPaths`, in file order.
+fn read_gameinfo_entries(game_dir: &Path) -> Vec<String> {
+    let Ok(text) = fs::read_to_string(game_dir.join("gameinfo.txt")) else {
+        return Vec::new();
+    };
+    let Ok(root) = parse_keyvalues(&text) else {
+        return Vec::new();
+    };
+
+    root.children_named("GameInfo")
+        .flat_map(|gameinfo| gameinfo.children_named("FileSystem"))
+        .flat_map(|filesystem| filesystem.children_named("SearchPaths"))
+        .flat_map(|search_paths| &search_paths.properties)
+        .map(|property| property.value.replace('\\', "/"))
+        .collect()
+}
+
+fn dir_vpks(game_dir: &Path) -> Vec<PathBuf> {
+    let mut vpks: Vec<PathBuf> = fs::read_dir(game_dir).into_iter().flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().to_lowercase().ends_with("_dir.vpk")))
+        .collect();
+    vpks.sort();
+    vpks
+}
@@ -0,0 +1,70 @@
+//! Listing of the files stored in VPK archives, from their `_dir.vpk` directory file.
+
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+const VPK_SIGNATURE: u32 = 0x55AA_1234;
+/// CRC, preload size, archive index, offset, length and terminator of a directory entry.
+const ENTRY_SIZE: usize = 18;
+
+/// Adds the paths of the files of a `_dir.vpk` to `files`, lowercase and using `/`.
+pub fn list_files(dir_vpk: &Path, files: &mut HashSet<String>) -> io::Result<()> {
+    let data = fs::read(dir_vpk)?;
+    let mut reader = data.as_slice();
+
+    let signature = read_u32(&mut reader)?;
+    let version = read_u32(&mut reader)?;
+    if signature != VPK_SIGNATURE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a VPK directory"));
+    }
+    let _tree_size = read_u32(&mut reader)?;
+    if version == 2 {
+        // Sizes of the data, MD5 and signature sections, all after the tree
+        let mut rest = [0; 16];
+        reader.read_exact(&mut rest)?;
+    }
+
+    loop {
+        let extension = read_string(&mut reader)?;
+        if extension.is_empty() {
+            return Ok(());
+        }
+        loop {
+            let dir = read_string(&mut reader)?;
+            if dir.is_empty() {
+                break;
+            }
+            loop {
+                let name = read_string(&mut reader)?;
+                if name.is_empty() {
+                    break;
+                }
+                let mut entry = [0; ENTRY_SIZE];
+                reader.read_exact(&mut entry)?;
+                let preload = u16::from_le_bytes([entry[4], entry[5]]) as usize;
+                reader = reader.get(preload..).ok_or(io::ErrorKind::UnexpectedEof)?;
+
+                // A single space stands for the root folder
+                let path = if dir == " " { format!("{name}.{extension}") } else { format!("{dir}/{name}.{extension}") };
+                files.insert(path.replace('\\', "/").to_lowercase());
+            }
+        }
+    }
+}
+
+fn read_u32(reader: &mut &[u8]) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_string(reader: &mut &[u8]) -> io::Result<String> {
+    let end = reader.iter().position(|byte| *byte == 0).ok_or(io::ErrorKind::UnexpectedEof)?;
+    let text = String::from_utf8_lossy(&reader[..end]).into_owned();
+    *reader = &reader[end + 1..];
+    Ok(text)
+}
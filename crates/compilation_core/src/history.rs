@@ -0,0 +1,212 @@
+//! Compile history: one record per compiled map, appended as a JSON line to a file kept across runs,
+//! to find out afterwards how long each step took and with which parameters.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use vmflow_config_types::{preset::Preset, GameConfiguration};
+
+use crate::{MapReport, MapStatus, StepOutcome};
+
+/// Maps compiled at the same time append to the same file.
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// One step of a recorded compile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepRecord {
+    pub name: String,
+    /// Command line parts of the activated parameters, placeholders not resolved.
+    pub parameters: Vec<String>,
+    pub duration: Duration,
+    pub outcome: StepOutcome,
+    pub exit_code: Option<i32>,
+    pub warnings: usize,
+    pub errors: usize,
+}
+
+/// How one map of a batch compiled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompileRecord {
+    /// Start of the batch in milliseconds since the Unix epoch, shared by the maps compiled together.
+    pub batch_id: u64,
+    pub map_name: String,
+    pub map_path: PathBuf,
+    pub preset: String,
+    pub game: String,
+    /// Seconds since the Unix epoch.
+    pub started_at: u64,
+    /// Seconds since the Unix epoch.
+    pub finished_at: u64,
+    pub status: MapStatus,
+    pub steps: Vec<StepRecord>,
+    /// SHA-256 of the compiled .bsp, when the map succeeded.
+    pub bsp_hash: Option<String>,
+    /// Full log of the compile, see [`crate::logs`].
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+}
+
+impl CompileRecord {
+    /// Builds the record of a map that just finished, with the parameters `preset` ran its steps with.
+    pub fn new(batch_id: u64, report: &MapReport, preset: &Preset, game: &GameConfiguration, bsp_hash: Option<String>) -> Self {
+        // Each step of the preset has a report, after the pre-flight one
+        let mut apps = preset.apps.iter();
+        let steps = report.steps.iter()
+            .map(|step| {
+                let parameters = if step.name == crate::map_pipeline::PREFLIGHT_STEP {
+                    Vec::new()
+                } else {
+                    apps.next()
+                        .map(|app| app.parameters.iter().filter_map(|param| param.get_command_parts()).flatten().collect())
+                        .unwrap_or_default()
+                };
+                StepRecord {
+                    name: step.name.clone(),
+                    parameters,
+                    duration: step.duration,
+                    outcome: step.outcome,
+                    exit_code: step.exit_code,
+                    warnings: step.warnings,
+                    errors: step.errors,
+                }
+            })
+            .collect();
+
+        let finished_at = unix_now().as_secs();
+        Self {
+            batch_id,
+            map_name: report.map_name.clone(),
+            map_path: report.path.clone(),
+            preset: preset.name.clone(),
+            game: game.name.clone(),
+            started_at: finished_at.saturating_sub(report.duration.as_secs()),
+            finished_at,
+            status: report.status.clone(),
+            steps,
+            bsp_hash,
+            log_file: report.log_file.clone(),
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.steps.iter().map(|step| step.duration).sum()
+    }
+
+    /// Finds a step by name, ignoring case.
+    pub fn step(&self, name: &str) -> Option<&StepRecord> {
+        self.steps.iter().find(|step| step.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.steps.iter().map(|step| step.warnings).sum()
+    }
+
+    pub fn errors(&self) -> usize {
+        self.steps.iter().map(|step| step.errors).sum()
+    }
+}
+
+/// Which records to show, every filter is optional.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    /// Part of the map name, ignoring case.
+    pub map: Option<String>,
+    /// Name of a step the record must have run, ignoring case.
+    pub step: Option<String>,
+    /// Only the most recent records.
+    pub limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    pub fn matches(&self, record: &CompileRecord) -> bool {
+        let map_matches = self.map.as_deref()
+            .is_none_or(|map| record.map_name.to_lowercase().contains(&map.to_lowercase()));
+        let step_matches = self.step.as_deref()
+            .is_none_or(|step| record.step(step).is_some_and(|step| step.outcome != StepOutcome::Skipped));
+        map_matches && step_matches
+    }
+
+    /// Keeps the matching records, the oldest first.
+    pub fn apply(&self, records: Vec<CompileRecord>) -> Vec<CompileRecord> {
+        let mut records: Vec<_> = records.into_iter().filter(|record| self.matches(record)).collect();
+        if let Some(limit) = self.limit {
+            records.drain(..records.len().saturating_sub(limit));
+        }
+        records
+    }
+}
+
+/// Appends a record to the history file, creating it if needed.
+pub fn append(path: &Path, record: &CompileRecord) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let line = serde_json::to_string(record)?;
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{line}")
+}
+
+/// Reads every record of the history file, the oldest first.
+///
+/// A missing file is an empty history, lines that cannot be read are skipped.
+pub fn load(path: &Path) -> io::Result<Vec<CompileRecord>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut records = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(record) = serde_json::from_str(&line?) {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+/// SHA-256 of a file, in lowercase hex.
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Current time since the Unix epoch, used for batch ids and record times.
+pub fn unix_now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// Formats seconds since the Unix epoch as `YYYY-MM-DD HH:MM:SS` in UTC.
+pub fn format_timestamp(seconds: u64) -> String {
+    let days = (seconds / 86400) as i64;
+    let time = seconds % 86400;
+
+    // Days to civil date, from Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}", time / 3600, time % 3600 / 60, time % 60)
+}
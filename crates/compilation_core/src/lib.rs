@@ -1,23 +1,48 @@
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_lock::Semaphore;
 use async_std::task;
+use vmflow_config_types::validation::IssueSeverity;
+
+pub mod budget;
+pub mod content;
+pub mod history;
+pub mod logs;
 
 mod map_pipeline;
 pub use map_pipeline::process_map_async;
 
+mod progress;
+pub use progress::{BatchProgress, Progress};
+
+mod report;
+pub use report::{BatchOutcome, BatchReport, MapReport, MapStatus, StepOutcome, StepReport};
+
 mod types;
-pub use types::{CoreEvent, JobEventHandler, send_or_print_event};
+pub use types::{BackendError, CoreEvent, JobEventHandler, send_or_print_event};
+pub use compiler_logs_process::{format_point, Diagnostic, DiagnosticKind, LeakInfo, LeakedEntity, Pointfile, Severity};
+pub use bsp_parser::{BspSummary, LimitUsage};
 
-use crate::types::BackendError;
 
 #[derive(Default, Clone)]
 pub struct CompilationSessionSettings {
     pub preset: vmflow_config_types::preset::Preset,
     pub game_config: vmflow_config_types::GameConfiguration,
+    /// File the limit usage of compiled maps is recorded in, see [`budget`]. `None` records nothing.
+    pub budget_history: Option<std::path::PathBuf>,
+    /// File every compiled map is recorded in, see [`history`]. `None` records nothing.
+    pub compile_history: Option<std::path::PathBuf>,
+    /// Folder the full log of every map is written to, see [`logs`]. `None` writes no logs.
+    pub log_dir: Option<std::path::PathBuf>,
+    /// Batches whose logs are kept in `log_dir`, 0 keeps them all.
+    pub keep_log_batches: usize,
+    /// File the input fingerprint of each successful compile is recorded in. `None` compiles every map.
+    pub fingerprints: Option<std::path::PathBuf>,
+    /// Compiles maps whose inputs did not change too, recording their fingerprint anyway.
+    pub force_rebuild: bool,
 }
 
 pub struct CompilationSession {
@@ -48,6 +73,12 @@ impl CompilationSession {
         let settings = CompilationSessionSettings {
             preset,
             game_config,
+            budget_history: None,
+            compile_history: None,
+            log_dir: None,
+            keep_log_batches: 0,
+            fingerprints: None,
+            force_rebuild: false,
         };
         Self {
             settings: Arc::new(settings),
@@ -57,6 +88,38 @@ impl CompilationSession {
         }
     }
 
+    /// Records the limit usage of compiled maps in `path`, to warn when it jumps between compiles.
+    pub fn with_budget_history(mut self, path: std::path::PathBuf) -> Self {
+        Arc::make_mut(&mut self.settings).budget_history = Some(path);
+        self
+    }
+
+    /// Records how every map of the batches compiled in `path`, see [`history`].
+    pub fn with_compile_history(mut self, path: std::path::PathBuf) -> Self {
+        Arc::make_mut(&mut self.settings).compile_history = Some(path);
+        self
+    }
+
+    /// Writes the full log of every map to `dir`, keeping the logs of the last `keep_batches` batches.
+    pub fn with_log_files(mut self, dir: std::path::PathBuf, keep_batches: usize) -> Self {
+        let settings = Arc::make_mut(&mut self.settings);
+        settings.log_dir = Some(dir);
+        settings.keep_log_batches = keep_batches;
+        self
+    }
+
+    /// Skips the maps whose inputs did not change since their last successful compile, fingerprints are kept in `path`.
+    pub fn with_fingerprints(mut self, path: std::path::PathBuf) -> Self {
+        Arc::make_mut(&mut self.settings).fingerprints = Some(path);
+        self
+    }
+
+    /// Compiles every map, even the ones [`Self::with_fingerprints`] would skip.
+    pub fn with_force_rebuild(mut self, force_rebuild: bool) -> Self {
+        Arc::make_mut(&mut self.settings).force_rebuild = force_rebuild;
+        self
+    }
+
     /// Cancels the current batch processing job.
     pub fn cancel_batch(&self) {
         self.cancel_flag.store(true, Ordering::Relaxed);
@@ -64,11 +127,11 @@ impl CompilationSession {
     }
 
     /// Starts a batch processing job, processing maps concurrently using async tasks.
-    /// Returns a JoinHandle for the spawned thread, providing a way to await completion and retrieve results.
+    /// Returns a JoinHandle for the spawned thread, providing a way to await completion and retrieve the report.
     pub fn start_batch(
         &self,
         maps_to_process: Vec<vmflow_config_types::VmfMap>,
-    ) -> thread::JoinHandle<Result<Vec<Result<(), BackendError>>, BackendError>> {
+    ) -> thread::JoinHandle<BatchReport> {
         let settings_for_thread = Arc::clone(&self.settings);
         let cancel_flag_for_thread = Arc::clone(&self.cancel_flag);
         let event_handler_for_thread = self.event_handler.as_ref().map(Arc::clone);
@@ -85,7 +148,7 @@ impl CompilationSession {
         })
     }
 
-    pub async fn start_batch_async(&self, maps_to_process: Vec<vmflow_config_types::VmfMap>) {
+    pub async fn start_batch_async(&self, maps_to_process: Vec<vmflow_config_types::VmfMap>) -> BatchReport {
         let settings_for_thread = Arc::clone(&self.settings);
         let cancel_flag_for_thread = Arc::clone(&self.cancel_flag);
         let event_handler_for_thread = self.event_handler.as_ref().map(Arc::clone);
@@ -97,12 +160,63 @@ impl CompilationSession {
             event_handler_for_thread,
             self.max_threads,
         )
-        .await;
+        .await
     }
 
     // submit_map?
 }
 
+/// Ends a batch that cannot start, every activated map fails with `error`.
+fn refuse_batch(
+    maps_to_process: Vec<vmflow_config_types::VmfMap>,
+    error: BackendError,
+    started: Instant,
+    event_handler: &Option<Arc<dyn JobEventHandler>>,
+) -> BatchReport {
+    let maps = maps_to_process.iter()
+        .map(|map_info| {
+            let status = if map_info.activated {
+                MapStatus::Failed { step: "Preset validation".to_string(), exit_code: None, error: error.to_string() }
+            } else {
+                MapStatus::Skipped
+            };
+            MapReport::not_started(map_info, status)
+        })
+        .collect();
+
+    let report = BatchReport::new(maps, started.elapsed(), false);
+    send_or_print_event(event_handler, CoreEvent::BatchCompleted(report.clone()));
+    report
+}
+
+/// Opens the log file of a map, returns the handler writing to it and its path.
+///
+/// Without a log file the map compiles anyway, with the handler of the session.
+fn open_map_log(
+    log_dir: &std::path::Path,
+    batch_id: u64,
+    file_name: &str,
+    map_info: &vmflow_config_types::VmfMap,
+    settings: &CompilationSessionSettings,
+    event_handler: &Option<Arc<dyn JobEventHandler>>,
+) -> (Option<Arc<dyn JobEventHandler>>, Option<std::path::PathBuf>) {
+    match logs::MapLogWriter::create(log_dir, batch_id, file_name, map_info, settings, event_handler.clone()) {
+        Ok(writer) => {
+            let path = writer.path();
+            (Some(Arc::new(writer)), Some(path))
+        }
+        Err(e) => {
+            send_or_print_event(event_handler, CoreEvent::StepWarn(
+                map_info.order_idx,
+                map_info.name.clone(),
+                "Compile log".to_string(),
+                format!("Cannot create the log file in {}: {e}", log_dir.display()),
+            ));
+            (event_handler.as_ref().map(Arc::clone), None)
+        }
+    }
+}
+
 /// Core asynchronous logic for a batch of maps
 async fn run_batch_concurrently(
     maps_to_process: Vec<vmflow_config_types::VmfMap>,
@@ -110,55 +224,89 @@ async fn run_batch_concurrently(
     cancel_flag: Arc<AtomicBool>,
     event_handler: Option<Arc<dyn JobEventHandler>>,
     max_concurrent_maps: usize,
-) -> Result<Vec<Result<(), BackendError>>, BackendError> {
+) -> BatchReport {
     send_or_print_event(&event_handler, CoreEvent::BatchStarted);
+    let started = Instant::now();
+    let batch_id = history::unix_now().as_millis() as u64;
+
+    let issues = settings.preset.validate(Some(&settings.game_config));
+    let error_count = issues.iter().filter(|issue| issue.severity == IssueSeverity::Error).count();
+    if !issues.is_empty() {
+        send_or_print_event(&event_handler, CoreEvent::ValidationIssues(issues));
+    }
+    if error_count > 0 {
+        return refuse_batch(maps_to_process, BackendError::InvalidPreset(error_count), started, &event_handler);
+    }
 
+    let log_names = settings.log_dir.as_deref().map(|dir| {
+        // Logs of old batches are only worth a retry at the next batch
+        let _ = logs::prune(dir, settings.keep_log_batches);
+        logs::map_log_names(&maps_to_process)
+    });
+
+    let map_count = maps_to_process.iter().filter(|map| map.activated).count();
+    let step_count = settings.preset.apps.iter().filter(|step| step.activated).count();
+    let progress = Arc::new(BatchProgress::new(step_count, map_count));
     let semaphore = Arc::new(Semaphore::new(max_concurrent_maps.max(1)));
+
+    // Reports of maps that never start are known right away, the others are filled in when their task ends
+    let mut map_reports: Vec<Option<MapReport>> = Vec::new();
     let mut task_handles = Vec::new();
 
     for map_info in maps_to_process {
-        if cancel_flag.load(Ordering::Relaxed) { break }
-        if !map_info.activated { continue }
+        if !map_info.activated {
+            map_reports.push(Some(MapReport::not_started(&map_info, MapStatus::Skipped)));
+            continue;
+        }
+        if cancel_flag.load(Ordering::Relaxed) {
+            map_reports.push(Some(MapReport::not_started(&map_info, MapStatus::Cancelled)));
+            continue;
+        }
 
         let map_settings = Arc::clone(&settings);
         let map_cancel_flag = Arc::clone(&cancel_flag);
-        let map_event_handler = event_handler.as_ref().map(Arc::clone);
+        let (map_event_handler, log_file) = match (&settings.log_dir, &log_names) {
+            (Some(dir), Some(names)) => open_map_log(dir, batch_id, &names[&map_info.order_idx], &map_info, &settings, &event_handler),
+            _ => (event_handler.as_ref().map(Arc::clone), None),
+        };
         let permit_semaphore = Arc::clone(&semaphore);
+        let map_progress = Arc::clone(&progress);
 
         let handle = task::spawn(async move {
             // Asynchronously wait for permission from the semaphore
             let _permit = permit_semaphore.acquire().await; // Just .acquire() for RAII guard in async
 
-            process_map_async(map_info, map_settings, map_cancel_flag, map_event_handler).await
+            if map_cancel_flag.load(Ordering::Relaxed) {
+                return MapReport::not_started(&map_info, MapStatus::Cancelled);
+            }
+            let map_id = map_info.order_idx;
+            let mut report = process_map_async(map_info.clone(), Arc::clone(&map_settings), map_cancel_flag, map_event_handler.clone(), Arc::clone(&map_progress)).await;
+            report.log_file = log_file;
+            map_pipeline::record_history(batch_id, &map_info, &map_settings, &report, &map_event_handler);
+            if report.status == MapStatus::UpToDate {
+                map_progress.skip_map(map_id);
+            } else {
+                map_progress.finish_map(map_id);
+            }
+            report
         });
-        task_handles.push(handle);
+        task_handles.push((map_reports.len(), handle));
+        map_reports.push(None);
     }
 
     // Wait for all spawned asynchronous tasks to complete
-    let results_from_join = futures::future::join_all(task_handles).await;
+    let (slots, handles): (Vec<usize>, Vec<_>) = task_handles.into_iter().unzip();
+    let results_from_join = futures::future::join_all(handles).await;
+    for (slot, report) in slots.into_iter().zip(results_from_join) {
+        map_reports[slot] = Some(report);
+    }
 
-    let final_map_results: Vec<Result<(), BackendError>> = results_from_join
-        .into_iter()
-        .map(|join_result| {
-            println!("== Process status: {:?}", join_result);
-            join_result // todo, add process result here
-        })
-        .collect();
+    let cancelled = cancel_flag.load(Ordering::Relaxed);
+    let report = BatchReport::new(map_reports.into_iter().flatten().collect(), started.elapsed(), cancelled);
 
-    // Batch completion logic
-    if cancel_flag.load(Ordering::Relaxed) {
+    if cancelled {
         send_or_print_event(&event_handler, CoreEvent::BatchCancelled);
-        return Err(BackendError::Cancelled);
-    }
-    // TODO!
-    // if final_map_results.iter().any(|r| r.is_err()) {
-    //     let err_msg = "One or more maps failed to compile.".to_string();
-    //     send_or_print_event(&event_handler, CoreEvent::Log(Err(err_msg.clone())));
-    //     send_or_print_event(&event_handler, CoreEvent::BatchCompleted(Error(())));
-    //     return Err(err_msg);
-    // }
-
-    send_or_print_event(&event_handler, CoreEvent::BatchCompleted(Ok(())));
-    println!("PROCESS FINISHED!");
-    Ok(final_map_results)
+    }
+    send_or_print_event(&event_handler, CoreEvent::BatchCompleted(report.clone()));
+    report
 }
@@ -0,0 +1,213 @@
+//! Compile logs: everything the steps of a map print, with timestamps, written to one file per map like
+//! Hammer's `<map>.log`. Each batch gets its own folder and an index links batches to their files.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use vmflow_config_types::VmfMap;
+
+use crate::{history, send_or_print_event, CompilationSessionSettings, CoreEvent, JobEventHandler, MapStatus};
+
+/// File of the log folder listing the log file of every map of every batch.
+pub const INDEX_FILE: &str = "index.jsonl";
+
+/// The index is appended to by every map and rewritten when old batches are deleted.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Line of the index: where the log of a map of a batch is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogIndexEntry {
+    /// Id of the batch, as in [`history::CompileRecord::batch_id`].
+    pub batch_id: u64,
+    pub map_name: String,
+    pub map_path: PathBuf,
+    /// Relative to the log folder.
+    pub log_file: PathBuf,
+    pub status: MapStatus,
+}
+
+/// Folder of the logs of a batch.
+pub fn batch_dir(log_dir: &Path, batch_id: u64) -> PathBuf {
+    log_dir.join(batch_id.to_string())
+}
+
+/// Log file name of each map of a batch, by map position: `<map>.log`, with the position added when names collide.
+pub fn map_log_names(maps: &[VmfMap]) -> HashMap<usize, String> {
+    let stem = |map: &VmfMap| map.path.file_stem().map_or_else(|| map.name.clone(), |stem| stem.to_string_lossy().to_string());
+    let mut seen = BTreeSet::new();
+    maps.iter()
+        .filter(|map| map.activated)
+        .map(|map| {
+            let stem = stem(map);
+            let name = if seen.insert(stem.to_lowercase()) {
+                format!("{stem}.log")
+            } else {
+                format!("{stem}_{}.log", map.order_idx)
+            };
+            (map.order_idx, name)
+        })
+        .collect()
+}
+
+/// Reads the index of a log folder, the oldest batch first. Lines that cannot be read are skipped.
+pub fn load_index(log_dir: &Path) -> io::Result<Vec<LogIndexEntry>> {
+    let file = match File::open(log_dir.join(INDEX_FILE)) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+fn append_index(log_dir: &Path, entry: &LogIndexEntry) -> io::Result<()> {
+    let line = serde_json::to_string(entry)?;
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut file = OpenOptions::new().create(true).append(true).open(log_dir.join(INDEX_FILE))?;
+    writeln!(file, "{line}")
+}
+
+/// Deletes the folders of the oldest batches so that a new batch makes `keep`, and their index lines.
+///
+/// Only batches listed in the index are deleted, other folders of the log folder are not VMFlow's.
+/// `keep` 0 deletes nothing.
+pub fn prune(log_dir: &Path, keep: usize) -> io::Result<()> {
+    if keep == 0 {
+        return Ok(());
+    }
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let entries = load_index(log_dir)?;
+    let batches: BTreeSet<u64> = entries.iter().map(|entry| entry.batch_id).collect();
+    let excess = (batches.len() + 1).saturating_sub(keep);
+    let pruned: BTreeSet<u64> = batches.into_iter().take(excess).collect();
+    if pruned.is_empty() {
+        return Ok(());
+    }
+
+    for &batch_id in &pruned {
+        match fs::remove_dir_all(batch_dir(log_dir, batch_id)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    let mut kept = String::new();
+    for entry in entries.iter().filter(|entry| !pruned.contains(&entry.batch_id)) {
+        kept.push_str(&serde_json::to_string(entry)?);
+        kept.push('\n');
+    }
+    fs::write(log_dir.join(INDEX_FILE), kept)
+}
+
+/// Writes the events of one map to its log file, then passes them on to the handler of the session.
+pub struct MapLogWriter {
+    file: Mutex<BufWriter<File>>,
+    log_dir: PathBuf,
+    batch_id: u64,
+    /// Relative to the log folder.
+    log_file: PathBuf,
+    inner: Option<Arc<dyn JobEventHandler>>,
+}
+
+impl MapLogWriter {
+    /// Creates the log file of a map in the folder of its batch, starting with what is compiled.
+    pub fn create(
+        log_dir: &Path,
+        batch_id: u64,
+        file_name: &str,
+        map_info: &VmfMap,
+        settings: &CompilationSessionSettings,
+        inner: Option<Arc<dyn JobEventHandler>>,
+    ) -> io::Result<Self> {
+        let dir = batch_dir(log_dir, batch_id);
+        fs::create_dir_all(&dir)?;
+        let mut file = BufWriter::new(File::create(dir.join(file_name))?);
+        writeln!(file, "Map: {}", map_info.path.display())?;
+        writeln!(file, "Preset: {}", settings.preset.name)?;
+        writeln!(file, "Game: {}", settings.game_config.name)?;
+        writeln!(file, "Started: {} UTC", history::format_timestamp(history::unix_now().as_secs()))?;
+        writeln!(file)?;
+        file.flush()?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+            log_dir: log_dir.to_path_buf(),
+            batch_id,
+            log_file: PathBuf::from(batch_id.to_string()).join(file_name),
+            inner,
+        })
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.log_dir.join(&self.log_file)
+    }
+
+    /// Writes a timestamped line, flushing it at the end of steps so an interrupted compile keeps its log.
+    fn write_line(&self, step: &str, text: &str, flush: bool) {
+        let mut file = self.file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // A failing disk must not fail the compile, the log is lost either way
+        let _ = writeln!(file, "{} [{step}] {text}", format_time_of_day(history::unix_now()));
+        if flush {
+            let _ = file.flush();
+        }
+    }
+}
+
+impl JobEventHandler for MapLogWriter {
+    fn handle_event(&self, event: CoreEvent) {
+        match &event {
+            CoreEvent::StepStarted(_, _, step) => self.write_line(step, "started", false),
+            CoreEvent::StepLog(_, _, step, line) => self.write_line(step, line, false),
+            CoreEvent::StepWarn(_, _, step, line) => self.write_line(step, &format!("warning: {line}"), false),
+            CoreEvent::StepErr(_, _, step, line) => self.write_line(step, &format!("(stderr) {line}"), false),
+            CoreEvent::StepFinished(_, _, step) => self.write_line(step, "finished", true),
+            CoreEvent::StepFailed(_, _, step, error) => self.write_line(step, &format!("failed: {error}"), true),
+            CoreEvent::StepSkipped(_, _, step, reason) => self.write_line(step, &format!("skipped: {reason}"), true),
+            CoreEvent::BspSummary(_, _, summary) => self.write_line("BSP summary", &summary.to_string(), false),
+            CoreEvent::LimitAlert(_, _, alert) => self.write_line("BSP summary", &alert.to_string(), false),
+            CoreEvent::MapFinished(_, map_name, report) => {
+                let status = match &report.status {
+                    MapStatus::Failed { step, error, .. } => format!("failed at {step}: {error}"),
+                    status => format!("{status:?}"),
+                };
+                self.write_line(map_name, &format!("{status} in {:.1}s", report.duration.as_secs_f64()), true);
+
+                let entry = LogIndexEntry {
+                    batch_id: self.batch_id,
+                    map_name: map_name.clone(),
+                    map_path: report.path.clone(),
+                    log_file: self.log_file.clone(),
+                    status: report.status.clone(),
+                };
+                if let Err(e) = append_index(&self.log_dir, &entry) {
+                    self.write_line(map_name, &format!("cannot update the log index: {e}"), true);
+                }
+            }
+            _ => {}
+        }
+        send_or_print_event(&self.inner, event);
+    }
+}
+
+/// `HH:MM:SS.mmm` in UTC.
+fn format_time_of_day(since_epoch: Duration) -> String {
+    let seconds = since_epoch.as_secs() % 86400;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+        since_epoch.subsec_millis()
+    )
+}
@@ -1,10 +1,26 @@
-use std::{io::Error, sync::mpsc::Sender};
-use crate::types::BackendError;
+use std::{io::Error, path::{Path, PathBuf}, sync::{atomic::AtomicBool, Arc}};
+use async_std::fs;
+use compiler_logs_process::Diagnostic;
+use vmflow_config_types::{selected_compiler::SelectedCompiler, GameConfiguration, VmfMap};
 
-pub async fn process(step_name: &str) -> Result<(), BackendError> {
-    match step_name {
-        "COPY" => {eprintln!("Built-in func not implemented: COPY.")},
-        "SHUTDOWN" => {system_shutdown();},
+use crate::{send_or_print_event, types::BackendError, BatchProgress, CompilationSessionSettings, CoreEvent, JobEventHandler};
+
+#[allow(clippy::too_many_arguments)]
+pub async fn process(
+    compiler_step: &SelectedCompiler,
+    map_info: &VmfMap,
+    settings: &Arc<CompilationSessionSettings>,
+    step_idx: usize,
+    progress: &Arc<BatchProgress>,
+    cancel_flag: &Arc<AtomicBool>,
+    event_handler: &Option<Arc<dyn JobEventHandler>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<(), BackendError> {
+    match compiler_step.name() {
+        "COPY" => copy_bsp(compiler_step, map_info, &settings.game_config, event_handler).await?,
+        "PACK" => super::pack::pack_content(compiler_step, map_info, settings, step_idx, progress, cancel_flag, event_handler, diagnostics).await?,
+        "PARTICLES" => super::particles::write_particle_manifest(compiler_step, map_info, settings, step_idx, progress, cancel_flag, event_handler, diagnostics).await?,
+        "SHUTDOWN" => system_shutdown().map_err(BackendError::BuiltinFailed)?,
         _ => {
             return Err(BackendError::BuiltinFailed("Process Not Found".to_string())); // todo?
         }
@@ -12,6 +28,97 @@ pub async fn process(step_name: &str) -> Result<(), BackendError> {
     Ok(())
 }
 
+/// Returns the path of the .bsp that VBSP produces next to the map source.
+pub fn compiled_bsp_path(map_info: &VmfMap) -> PathBuf {
+    map_info.path.with_extension("bsp")
+}
+
+/// Returns the folder the COPY step puts compiled maps into.
+///
+/// Uses `output_dir` of the game configuration, falling back to `<game_dir>/maps`.
+pub fn maps_output_dir(game_config: &GameConfiguration) -> PathBuf {
+    if game_config.output_dir.is_empty() {
+        Path::new(&game_config.game_dir).join("maps")
+    } else {
+        PathBuf::from(&game_config.output_dir)
+    }
+}
+
+/// Returns the path the COPY step copies the compiled .bsp to.
+pub fn bsp_copy_destination(map_info: &VmfMap, game_config: &GameConfiguration) -> PathBuf {
+    let file_name = compiled_bsp_path(map_info)
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    maps_output_dir(game_config).join(file_name)
+}
+
+/// Copies the compiled .bsp from the map source folder into the game's maps folder.
+///
+/// Supported step arguments:
+/// * `-backup` - keeps the previous .bsp as `<map>.bsp.bak` before overwriting it.
+/// * `-nooverwrite` - fails instead of replacing an existing .bsp.
+async fn copy_bsp(
+    compiler_step: &SelectedCompiler,
+    map_info: &VmfMap,
+    game_config: &GameConfiguration,
+    event_handler: &Option<Arc<dyn JobEventHandler>>,
+) -> Result<(), BackendError> {
+    let args = compiler_step.get_command_params();
+    let backup = args.iter().any(|arg| arg == "-backup");
+    let no_overwrite = args.iter().any(|arg| arg == "-nooverwrite");
+
+    let log = |message: String| send_or_print_event(event_handler, CoreEvent::StepLog(
+        map_info.order_idx,
+        map_info.name.clone(),
+        compiler_step.name().to_string(),
+        message,
+    ));
+
+    let source = compiled_bsp_path(map_info);
+    if !fs::metadata(&source).await.is_ok_and(|meta| meta.is_file()) {
+        return Err(BackendError::BuiltinFailed(format!(
+            "Compiled map not found: {}", source.display()
+        )));
+    }
+
+    let target_dir = maps_output_dir(game_config);
+    if target_dir.as_os_str().is_empty() || !fs::metadata(&target_dir).await.is_ok_and(|meta| meta.is_dir()) {
+        return Err(BackendError::BuiltinFailed(format!(
+            "Maps folder does not exist: '{}'. Check the game and output dirs of '{}'",
+            target_dir.display(), game_config.name
+        )));
+    }
+
+    let target = bsp_copy_destination(map_info, game_config);
+    if fs::metadata(&target).await.is_ok() {
+        if no_overwrite {
+            return Err(BackendError::BuiltinFailed(format!(
+                "{} already exists and -nooverwrite is set", target.display()
+            )));
+        }
+
+        if backup {
+            let mut backup_name = target.clone().into_os_string();
+            backup_name.push(".bak");
+            let backup_path = PathBuf::from(backup_name);
+
+            fs::copy(&target, &backup_path).await.map_err(|e| BackendError::BuiltinFailed(format!(
+                "Failed to back up {} to {}: {e}", target.display(), backup_path.display()
+            )))?;
+            log(format!("Backed up previous map to {}", backup_path.display()));
+        }
+    }
+
+    log(format!("Copying {} -> {}", source.display(), target.display()));
+    let copied = fs::copy(&source, &target).await.map_err(|e| BackendError::BuiltinFailed(format!(
+        "Failed to copy {} to {}: {e}", source.display(), target.display()
+    )))?;
+    log(format!("Copied {copied} bytes"));
+
+    Ok(())
+}
+
 fn system_shutdown() -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
@@ -37,9 +144,128 @@ fn execute_command(program: &str, args: &[&str]) -> Result<(), Error> {
     if status.success() {
         Ok(())
     } else {
-        Err(Error::new(
-            std::io::ErrorKind::Other,
+        Err(Error::other(
             format!("Command '{} {:?}' failed with status: {}", program, args, status),
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use vmflow_config_types::parameter_override::ParameterOverride;
+
+    use super::*;
+
+    /// Folder with `src/test.vmf` and a `game` folder, removed by the next run of the same test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vmflow_copy_test_{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("game")).unwrap();
+        dir
+    }
+
+    fn map(dir: &Path) -> VmfMap {
+        VmfMap { name: "test".to_string(), path: dir.join("src/test.vmf"), activated: true, order_idx: 0 }
+    }
+
+    fn game(dir: &Path) -> GameConfiguration {
+        GameConfiguration {
+            name: "HL2".to_string(),
+            game_dir: dir.join("game").to_string_lossy().into_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// A COPY step with the flags of `arguments` activated.
+    fn copy_step(arguments: &[&str]) -> SelectedCompiler {
+        let mut step = SelectedCompiler::from_id("copy");
+        for argument in arguments {
+            let parameter = step.config().parameters.iter().find(|parameter| parameter.argument == *argument).unwrap();
+            step.parameters.push(ParameterOverride::new("copy", &parameter.id));
+        }
+        step
+    }
+
+    fn run(step: &SelectedCompiler, map: &VmfMap, game: &GameConfiguration) -> Result<(), BackendError> {
+        async_std::task::block_on(copy_bsp(step, map, game, &None))
+    }
+
+    #[test]
+    fn resolves_paths() {
+        let map = VmfMap { path: PathBuf::from("/maps/src/test.vmf"), ..Default::default() };
+        let mut game = GameConfiguration { game_dir: "/games/hl2".to_string(), ..Default::default() };
+        assert_eq!(compiled_bsp_path(&map), Path::new("/maps/src/test.bsp"));
+        assert_eq!(maps_output_dir(&game), Path::new("/games/hl2/maps"));
+        assert_eq!(bsp_copy_destination(&map, &game), Path::new("/games/hl2/maps/test.bsp"));
+
+        game.output_dir = "/out".to_string();
+        assert_eq!(maps_output_dir(&game), Path::new("/out"));
+        assert_eq!(bsp_copy_destination(&map, &game), Path::new("/out/test.bsp"));
+    }
+
+    #[test]
+    fn copies_the_bsp() {
+        let dir = test_dir("plain");
+        fs::create_dir_all(dir.join("game/maps")).unwrap();
+        fs::write(dir.join("src/test.bsp"), "new").unwrap();
+        fs::write(dir.join("game/maps/test.bsp"), "old").unwrap();
+
+        run(&copy_step(&[]), &map(&dir), &game(&dir)).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("game/maps/test.bsp")).unwrap(), "new");
+        assert!(!dir.join("game/maps/test.bsp.bak").exists());
+        assert_eq!(fs::read_to_string(dir.join("src/test.bsp")).unwrap(), "new");
+    }
+
+    #[test]
+    fn backs_up_the_previous_bsp() {
+        let dir = test_dir("backup");
+        fs::create_dir_all(dir.join("game/maps")).unwrap();
+        fs::write(dir.join("src/test.bsp"), "new").unwrap();
+        fs::write(dir.join("game/maps/test.bsp"), "old").unwrap();
+
+        run(&copy_step(&["-backup"]), &map(&dir), &game(&dir)).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("game/maps/test.bsp")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(dir.join("game/maps/test.bsp.bak")).unwrap(), "old");
+    }
+
+    #[test]
+    fn leaves_an_existing_bsp_with_nooverwrite() {
+        let dir = test_dir("nooverwrite");
+        fs::create_dir_all(dir.join("game/maps")).unwrap();
+        fs::write(dir.join("src/test.bsp"), "new").unwrap();
+        fs::write(dir.join("game/maps/test.bsp"), "old").unwrap();
+
+        let result = run(&copy_step(&["-nooverwrite", "-backup"]), &map(&dir), &game(&dir));
+        assert!(matches!(result, Err(BackendError::BuiltinFailed(message)) if message.contains("-nooverwrite")));
+        assert_eq!(fs::read_to_string(dir.join("game/maps/test.bsp")).unwrap(), "old");
+        assert!(!dir.join("game/maps/test.bsp.bak").exists());
+
+        // Nothing to overwrite, the map is copied
+        fs::remove_file(dir.join("game/maps/test.bsp")).unwrap();
+        run(&copy_step(&["-nooverwrite"]), &map(&dir), &game(&dir)).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("game/maps/test.bsp")).unwrap(), "new");
+    }
+
+    #[test]
+    fn fails_without_a_compiled_bsp() {
+        let dir = test_dir("no_source");
+        fs::create_dir_all(dir.join("game/maps")).unwrap();
+
+        let result = run(&copy_step(&[]), &map(&dir), &game(&dir));
+        assert!(matches!(result, Err(BackendError::BuiltinFailed(message)) if message.starts_with("Compiled map not found")));
+        assert!(!dir.join("game/maps/test.bsp").exists());
+    }
+
+    #[test]
+    fn fails_without_a_maps_folder() {
+        let dir = test_dir("no_maps_dir");
+        fs::write(dir.join("src/test.bsp"), "new").unwrap();
+
+        let result = run(&copy_step(&[]), &map(&dir), &game(&dir));
+        assert!(matches!(result, Err(BackendError::BuiltinFailed(message)) if message.starts_with("Maps folder does not exist")));
+        assert!(!dir.join("game/maps").exists());
+    }
+}
@@ -1,21 +1,28 @@
 use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
 use async_process::{Command, Stdio};
-use async_std::{io::{BufReadExt, BufReader}, stream::StreamExt, task::{self, JoinHandle}};
-use crate::{send_or_print_event, types::BackendError, CoreEvent, JobEventHandler};
+use compiler_logs_process::{Diagnostic, LogParser, ProgressParser, ProgressUpdate};
+use async_std::{io::ReadExt, task::{self, JoinHandle}};
+use crate::{send_or_print_event, types::BackendError, BatchProgress, CoreEvent, JobEventHandler};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn execute_process(
     map_id: usize,
     map_name: String,
     step_name: String,
+    step_idx: usize,
+    progress: Arc<BatchProgress>,
     //
     executable: String,
     arguments: Vec<String>,
     work_dir: String,
+    envs: Vec<(String, String)>,
     cancel_flag: Arc<AtomicBool>,
-    event_handler: Option<Arc<dyn JobEventHandler>>
+    event_handler: Option<Arc<dyn JobEventHandler>>,
+    diagnostics: &mut Vec<Diagnostic>,
 ) -> Result<(), BackendError> {
     let mut command = Command::new(&executable);
     command.args(arguments);
+    command.envs(envs);
     if !work_dir.is_empty() {
         command.current_dir(work_dir);
     }
@@ -41,6 +48,8 @@ pub async fn execute_process(
         map_id,
         map_name.clone(),
         step_name.clone(),
+        step_idx,
+        Arc::clone(&progress),
         Arc::clone(&cancel_flag),
         event_handler.clone(),
         false
@@ -51,6 +60,8 @@ pub async fn execute_process(
         map_id,
         map_name.clone(),
         step_name.clone(),
+        step_idx,
+        Arc::clone(&progress),
         Arc::clone(&cancel_flag),
         event_handler.clone(),
         true
@@ -61,7 +72,7 @@ pub async fn execute_process(
     loop {
         if cancel_flag.load(Ordering::Relaxed) {
             if let Err(e) = child.kill() { // Пытаемся убить процесс
-                println!("ERROR: Failed to kill process on cancellation: {}", e)
+                eprintln!("ERROR: Failed to kill process on cancellation: {}", e)
             }
             status_result = Err(BackendError::Cancelled);
             break;
@@ -82,8 +93,8 @@ pub async fn execute_process(
     }
 
     // Wait for the stdout/stderr reading tasks to complete.
-    stdout_task.await;
-    stderr_task.await;
+    diagnostics.extend(stdout_task.await);
+    diagnostics.extend(stderr_task.await);
 
     // Returns the result based on the status
     match status_result {
@@ -94,37 +105,145 @@ pub async fn execute_process(
 }
 
 
-/// Helper function to spawn an asynchronous task for reading lines from a stream.
+/// Helper function to spawn an asynchronous task for reading a stream.
+///
+/// The stream is read in raw chunks rather than lines, since progress sequences
+/// (`0...1...2`) are printed without line breaks.
+/// The task returns every diagnostic parsed from the stream.
+#[allow(clippy::too_many_arguments)]
 fn spawn_stream_processing_task<S>(
-    stream_source: S,
+    mut stream_source: S,
     map_id: usize,
     map_name: String,
     step_name: String,
+    step_idx: usize,
+    progress: Arc<BatchProgress>,
     cancel_flag: Arc<AtomicBool>,
     event_handler: Option<Arc<dyn JobEventHandler>>,
     is_err: bool,
-) -> JoinHandle<()>
+) -> JoinHandle<Vec<Diagnostic>>
 where
     S: async_std::io::Read + Unpin + Send + 'static,
 {
     task::spawn(async move {
-        let mut lines = BufReader::new(stream_source).lines();
+        let mut parser = LogParser::new(&step_name);
+        let mut progress_parser = ProgressParser::new(&step_name);
+        let mut diagnostics = Vec::new();
+
+        let mut handle_line = |raw_line: String| {
+            let diagnostic = parser.parse_line(&raw_line);
+            let event = if is_err {
+                CoreEvent::StepErr(map_id, map_name.clone(), step_name.clone(), raw_line)
+            }
+            else {
+                CoreEvent::StepLog(map_id, map_name.clone(), step_name.clone(), raw_line)
+            };
+            send_or_print_event(&event_handler, event);
 
-        while let Some(line_result) = lines.next().await {
+            if let Some(diagnostic) = diagnostic {
+                diagnostics.push(diagnostic.clone());
+                send_or_print_event(&event_handler, CoreEvent::StepDiagnostic(
+                    map_id, map_name.clone(), step_name.clone(), diagnostic
+                ));
+            }
+        };
+
+        let mut buffer = [0u8; 4096];
+        let mut pending_line = Vec::new();
+        let mut undecoded = Vec::new();
+        loop {
             if cancel_flag.load(Ordering::Relaxed) { break }
 
-            match line_result {
-                Ok(raw_line) => {
-                    let event = if is_err {
-                        CoreEvent::StepErr(map_id, map_name.clone(), step_name.clone(), raw_line)
+            let read = match stream_source.read(&mut buffer).await {
+                Ok(0) | Err(_) => break,
+                Ok(read) => read,
+            };
+            let chunk = &buffer[..read];
+
+            undecoded.extend_from_slice(chunk);
+            for update in progress_parser.feed(&decode_utf8(&mut undecoded)) {
+                let event = match update {
+                    ProgressUpdate::Phase { phase, fraction, step_fraction } => CoreEvent::StepProgress(
+                        map_id,
+                        map_name.clone(),
+                        step_name.clone(),
+                        progress.update(map_id, step_idx, phase, fraction, step_fraction),
+                    ),
+                    ProgressUpdate::Count(name, value) => CoreEvent::StepStatistic(
+                        map_id, map_name.clone(), step_name.clone(), name, value
+                    ),
+                };
+                send_or_print_event(&event_handler, event);
+            }
+
+            pending_line.extend_from_slice(chunk);
+            while let Some(end) = pending_line.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending_line.drain(..=end).collect();
+                handle_line(String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string());
+            }
+        }
+        if !pending_line.is_empty() {
+            handle_line(String::from_utf8_lossy(&pending_line).trim_end_matches('\r').to_string());
+        }
+
+        diagnostics
+    })
+}
+
+/// Decodes `bytes` like [`String::from_utf8_lossy`], but leaves a character cut at the end of the
+/// buffer in it, to be completed by the next read.
+fn decode_utf8(bytes: &mut Vec<u8>) -> String {
+    let mut text = String::with_capacity(bytes.len());
+    let mut rest = bytes.as_slice();
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                text.push_str(valid);
+                rest = &[];
+                break;
+            }
+            Err(e) => {
+                let (valid, after) = rest.split_at(e.valid_up_to());
+                text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                match e.error_len() {
+                    Some(invalid) => {
+                        text.push(char::REPLACEMENT_CHARACTER);
+                        rest = &after[invalid..];
+                    }
+                    None => {
+                        rest = after;
+                        break;
                     }
-                    else {
-                        CoreEvent::StepLog(map_id, map_name.clone(), step_name.clone(), raw_line)
-                    };
-                    send_or_print_event(&event_handler, event);
                 }
-                Err(e) => {}
             }
         }
-    })
+    }
+    let kept = rest.len();
+    bytes.drain(..bytes.len() - kept);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_utf8;
+
+    #[test]
+    fn keeps_characters_split_across_reads() {
+        let text = "Lumière: 0...1...2";
+        let split = text.find('è').unwrap() + 1;
+        let mut bytes = text.as_bytes()[..split].to_vec();
+        assert_eq!(decode_utf8(&mut bytes), "Lumi");
+        assert_eq!(bytes, &text.as_bytes()[split - 1..split]);
+
+        bytes.extend_from_slice(&text.as_bytes()[split..]);
+        assert_eq!(decode_utf8(&mut bytes), "ère: 0...1...2");
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn replaces_invalid_bytes() {
+        let mut bytes = b"a\xffb\xe2\x82".to_vec();
+        assert_eq!(decode_utf8(&mut bytes), "a\u{FFFD}b");
+        assert_eq!(bytes, b"\xe2\x82");
+    }
 }
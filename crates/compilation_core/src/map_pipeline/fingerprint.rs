@@ -0,0 +1,223 @@
+//! Fingerprints of the inputs of a map compile, to skip maps that did not change since their last
+//! successful compile.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use sha2::{Digest, Sha256};
+use vmf_parser::Vmf;
+use vmflow_config_types::VmfMap;
+
+use super::build_command;
+use crate::CompilationSessionSettings;
+
+/// Maps compiled at the same time record into the same file.
+static FINGERPRINTS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Fingerprint of the last successful compile of each map, by map source path.
+type Fingerprints = BTreeMap<PathBuf, String>;
+
+/// Hashes what a compile of the map depends on: the .vmf and its instances, the command line of every
+/// activated step and the compiler executables.
+pub fn map_fingerprint(map_info: &VmfMap, settings: &CompilationSessionSettings) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    hash_file(&mut hasher, "vmf", &map_info.path)?;
+
+    let map_dir = map_info.path.parent().unwrap_or(Path::new(""));
+    let mut instances = BTreeSet::new();
+    collect_instances(&map_info.path, map_dir, &mut instances)?;
+    for instance in &instances {
+        match instance {
+            Ok(path) => hash_file(&mut hasher, "instance", path)?,
+            // An instance appearing later must change the fingerprint too
+            Err(missing) => hash_part(&mut hasher, "missing instance", missing.as_bytes()),
+        }
+    }
+
+    for step in settings.preset.apps.iter().filter(|step| step.activated) {
+        hash_part(&mut hasher, "step", step.compiler_id.as_bytes());
+        if step.config().is_builtin {
+            let parameters: Vec<String> = step.parameters.iter().filter_map(|param| param.get_command_parts()).flatten().collect();
+            hash_part(&mut hasher, "parameters", parameters.join("\0").as_bytes());
+            continue;
+        }
+
+        let command = build_command(map_info, settings, step).map_err(|e| io::Error::other(e.to_string()))?;
+        hash_part(&mut hasher, "executable", command.executable.as_bytes());
+        hash_part(&mut hasher, "arguments", command.arguments.join("\0").as_bytes());
+        hash_part(&mut hasher, "work dir", command.work_dir.as_bytes());
+        if let Some(compiler) = settings.game_config.compiler_path(&step.compiler_id) {
+            hash_file(&mut hasher, "compiler", Path::new(compiler))?;
+        }
+    }
+
+    Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Adds the `func_instance` files of a .vmf to `found`, and theirs in turn.
+///
+/// Like VBSP, instance paths are relative to the .vmf using them, then to the folder of the compiled map.
+/// Instances that cannot be found are added as `Err(name)`.
+fn collect_instances(vmf_path: &Path, map_dir: &Path, found: &mut BTreeSet<Result<PathBuf, String>>) -> io::Result<()> {
+    let vmf = Vmf::load(vmf_path).map_err(|e| io::Error::other(format!("{}: {e}", vmf_path.display())))?;
+    let vmf_dir = vmf_path.parent().unwrap_or(Path::new(""));
+
+    for file in vmf.entities_by_class("func_instance").filter_map(|entity| entity.get("file")) {
+        let file = file.trim().replace('\\', "/");
+        if file.is_empty() {
+            continue;
+        }
+        let Some(path) = [vmf_dir.join(&file), map_dir.join(&file)].into_iter().find(|path| path.is_file()) else {
+            found.insert(Err(file));
+            continue;
+        };
+        let path = fs::canonicalize(&path).unwrap_or(path);
+        if found.insert(Ok(path.clone())) {
+            collect_instances(&path, map_dir, found)?;
+        }
+    }
+    Ok(())
+}
+
+fn hash_part(hasher: &mut Sha256, label: &str, bytes: &[u8]) {
+    hasher.update(label.as_bytes());
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+fn hash_file(hasher: &mut Sha256, label: &str, path: &Path) -> io::Result<()> {
+    let bytes = fs::read(path).map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+    hash_part(hasher, label, &bytes);
+    Ok(())
+}
+
+/// The map is named by its canonical path, the GUI and the CLI may name it differently.
+fn map_key(map_path: &Path) -> PathBuf {
+    fs::canonicalize(map_path).unwrap_or_else(|_| map_path.to_path_buf())
+}
+
+fn load(path: &Path) -> Fingerprints {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Whether the map was last compiled successfully with these inputs.
+pub fn matches_last_success(fingerprints_path: &Path, map_path: &Path, fingerprint: &str) -> bool {
+    let _guard = FINGERPRINTS_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    load(fingerprints_path).get(&map_key(map_path)).is_some_and(|last| last == fingerprint)
+}
+
+/// Records the fingerprint of a successful compile of the map.
+pub fn record_success(fingerprints_path: &Path, map_path: &Path, fingerprint: String) -> io::Result<()> {
+    let _guard = FINGERPRINTS_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut fingerprints = load(fingerprints_path);
+    fingerprints.insert(map_key(map_path), fingerprint);
+    if let Some(dir) = fingerprints_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(fingerprints_path, serde_json::to_string_pretty(&fingerprints)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use vmflow_config_types::{parameter_override::ParameterOverride, selected_compiler::SelectedCompiler, GameConfiguration};
+
+    use super::*;
+
+    /// Folder with a fake VBSP, removed by the next run of the same test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vmflow_fingerprint_test_{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        fs::write(dir.join("bin/vbsp"), "vbsp").unwrap();
+        dir
+    }
+
+    fn vmf(instances: &[&str]) -> String {
+        let mut vmf = "world\n{\n\t\"id\" \"1\"\n\t\"classname\" \"worldspawn\"\n}\n".to_string();
+        for (idx, file) in instances.iter().enumerate() {
+            vmf += &format!("entity\n{{\n\t\"id\" \"{}\"\n\t\"classname\" \"func_instance\"\n\t\"file\" \"{file}\"\n}}\n", idx + 2);
+        }
+        vmf
+    }
+
+    fn map(dir: &Path) -> VmfMap {
+        VmfMap { name: "test".to_string(), path: dir.join("test.vmf"), activated: true, order_idx: 0 }
+    }
+
+    fn settings(dir: &Path) -> CompilationSessionSettings {
+        let mut settings = CompilationSessionSettings {
+            game_config: GameConfiguration {
+                game_dir: dir.join("game").to_string_lossy().into_owned(),
+                bin_dir: dir.join("bin").to_string_lossy().into_owned(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        settings.game_config.compiler_paths.insert("vbsp".to_string(), dir.join("bin/vbsp").to_string_lossy().into_owned());
+        settings.preset.apps.push(SelectedCompiler::from_id("vbsp"));
+        settings
+    }
+
+    #[test]
+    fn changes_with_the_map_and_its_instances() {
+        let dir = test_dir("instances");
+        fs::create_dir_all(dir.join("instances")).unwrap();
+        fs::write(dir.join("test.vmf"), vmf(&["instances/door.vmf"])).unwrap();
+        fs::write(dir.join("instances/door.vmf"), vmf(&["window.vmf"])).unwrap();
+        fs::write(dir.join("instances/window.vmf"), vmf(&[])).unwrap();
+        let (map, settings) = (map(&dir), settings(&dir));
+
+        let first = map_fingerprint(&map, &settings).unwrap();
+        assert_eq!(map_fingerprint(&map, &settings).unwrap(), first);
+
+        // An instance of an instance, relative to the instance using it
+        fs::write(dir.join("instances/window.vmf"), vmf(&[]) + "// edited\n").unwrap();
+        let edited_instance = map_fingerprint(&map, &settings).unwrap();
+        assert_ne!(edited_instance, first);
+
+        fs::write(dir.join("test.vmf"), vmf(&["instances/door.vmf"]) + "// edited\n").unwrap();
+        assert_ne!(map_fingerprint(&map, &settings).unwrap(), edited_instance);
+    }
+
+    #[test]
+    fn changes_when_a_missing_instance_appears() {
+        let dir = test_dir("missing_instance");
+        fs::write(dir.join("test.vmf"), vmf(&["instances/door.vmf"])).unwrap();
+        let (map, settings) = (map(&dir), settings(&dir));
+
+        let missing = map_fingerprint(&map, &settings).unwrap();
+        fs::create_dir_all(dir.join("instances")).unwrap();
+        fs::write(dir.join("instances/door.vmf"), vmf(&[])).unwrap();
+        assert_ne!(map_fingerprint(&map, &settings).unwrap(), missing);
+    }
+
+    #[test]
+    fn changes_with_the_steps_and_their_arguments() {
+        let dir = test_dir("arguments");
+        fs::write(dir.join("test.vmf"), vmf(&[])).unwrap();
+        let map = map(&dir);
+        let mut settings = settings(&dir);
+        let first = map_fingerprint(&map, &settings).unwrap();
+
+        let flag = settings.preset.apps[0].config().parameters.iter()
+            .find(|parameter| parameter.argument == "-notjunc")
+            .unwrap();
+        settings.preset.apps[0].parameters.push(ParameterOverride::new("vbsp", &flag.id));
+        let with_flag = map_fingerprint(&map, &settings).unwrap();
+        assert_ne!(with_flag, first);
+
+        // Deactivated parameters are not passed to the compiler
+        settings.preset.apps[0].parameters[0].activated = false;
+        assert_eq!(map_fingerprint(&map, &settings).unwrap(), first);
+
+        fs::write(dir.join("bin/vbsp"), "vbsp 2").unwrap();
+        assert_ne!(map_fingerprint(&map, &settings).unwrap(), first);
+    }
+}
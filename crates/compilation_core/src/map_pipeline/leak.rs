@@ -0,0 +1,42 @@
+//! Detection of map leaks reported by VBSP.
+
+use std::sync::Arc;
+
+use compiler_logs_process::{Diagnostic, DiagnosticKind, LeakInfo, LeakedEntity, Pointfile};
+use vmflow_config_types::VmfMap;
+
+use crate::{send_or_print_event, CoreEvent, JobEventHandler};
+
+/// Builds the leak report of a map if VBSP reported a leak in `diagnostics`.
+///
+/// The leak path is read from the `<map>.lin` pointfile VBSP writes next to the map source.
+pub fn detect_leak(
+    map_info: &VmfMap,
+    step_name: &str,
+    diagnostics: &[Diagnostic],
+    event_handler: &Option<Arc<dyn JobEventHandler>>,
+) -> Option<LeakInfo> {
+    if !diagnostics.iter().any(|d| d.kind == DiagnosticKind::Leak) {
+        return None;
+    }
+
+    let entity = diagnostics
+        .iter()
+        .find_map(|d| LeakedEntity::from_log_line(&d.message));
+
+    let path = map_info.path.with_extension("lin");
+    let (pointfile_path, pointfile) = match Pointfile::load(&path) {
+        Ok(pointfile) => (Some(path), pointfile),
+        Err(e) => {
+            send_or_print_event(event_handler, CoreEvent::StepWarn(
+                map_info.order_idx,
+                map_info.name.clone(),
+                step_name.to_string(),
+                format!("Leak detected, but the pointfile {} could not be loaded: {e}", path.display()),
+            ));
+            (None, Pointfile::default())
+        }
+    };
+
+    Some(LeakInfo { pointfile_path, pointfile, entity })
+}
@@ -1,60 +1,449 @@
-use std::{fmt::Arguments, sync::{
+use std::{fmt::Arguments, time::Instant, sync::{
     atomic::{AtomicBool, Ordering}, Arc
 }};
 
-use vmflow_config_types::selected_compiler::SelectedCompiler;
+use bsp_parser::{Bsp, BspSummary};
+use compiler_data_model::ParameterType;
+use compiler_logs_process::{Diagnostic, Severity, Tool};
+use vmflow_config_types::{preset::PreflightPolicy, selected_compiler::{SelectedCompiler, StepPolicy}};
 
-use crate::{send_or_print_event, types::BackendError, CompilationSessionSettings, CoreEvent, JobEventHandler};
+use crate::{budget, history, send_or_print_event, types::BackendError, BatchProgress, CompilationSessionSettings, CoreEvent, JobEventHandler, MapReport, MapStatus, StepOutcome, StepReport};
 
 mod builtin_commands;
 mod execute_handler;
+mod fingerprint;
+mod leak;
+mod pack;
+mod particles;
+mod placeholders;
+mod preflight;
+mod wine;
 pub use execute_handler::execute_process;
+use leak::detect_leak;
+use placeholders::{PathStyle, PlaceholderContext};
+pub(crate) use preflight::PREFLIGHT_STEP;
+use wine::WinePathTranslator;
 
-/// todo
+/// Name the post-compile summary is reported under.
+const BSP_SUMMARY_STEP: &str = "BSP summary";
+/// Name problems with the input fingerprints are reported under.
+const FINGERPRINT_STEP: &str = "Fingerprint";
+/// Name problems with the compile history are reported under.
+const HISTORY_STEP: &str = "Compile history";
+
+/// Runs every step of the preset on a map and reports how it went.
+///
+/// `MapFinished` is sent with the same report, whether the map succeeded or not.
 pub async fn process_map_async(
     map_info: vmflow_config_types::VmfMap,
     settings: Arc<CompilationSessionSettings>,
     cancel_flag: Arc<AtomicBool>,
     event_fn: Option<Arc<dyn JobEventHandler>>,
-) -> Result<(), BackendError> {
+    progress: Arc<BatchProgress>,
+) -> MapReport {
     send_or_print_event(&event_fn, CoreEvent::MapStarted(map_info.order_idx, map_info.name.clone()));
 
+    let fingerprint = map_fingerprint(&map_info, &settings, &event_fn);
+    if let Some(fingerprint) = &fingerprint
+        && is_up_to_date(&map_info, &settings, fingerprint)
+    {
+        let report = MapReport::not_started(&map_info, MapStatus::UpToDate);
+        send_or_print_event(&event_fn, CoreEvent::MapFinished(map_info.order_idx, map_info.name.clone(), report.clone()));
+        return report;
+    }
+
+    let started = Instant::now();
+    let mut steps = Vec::new();
+    let result = process_steps(&map_info, &settings, &cancel_flag, &event_fn, &progress, &mut steps).await;
+    let bsp = match result {
+        Ok(()) => summarize_bsp(&map_info, &settings, &event_fn),
+        Err(_) => None,
+    };
+    if result.is_ok()
+        && activated_steps_succeeded(&settings, &steps)
+        && let Some(fingerprint) = fingerprint
+    {
+        record_fingerprint(&map_info, &settings, fingerprint, &event_fn);
+    }
+
+    let mut report = MapReport::finished(&map_info, steps, started.elapsed(), result);
+    report.bsp = bsp;
+    send_or_print_event(&event_fn, CoreEvent::MapFinished(map_info.order_idx, map_info.name.clone(), report.clone()));
+    report
+}
+
+/// Fingerprints the inputs of a map when the session skips unchanged maps.
+///
+/// A map that cannot be fingerprinted is compiled, with a warning.
+fn map_fingerprint(
+    map_info: &vmflow_config_types::VmfMap,
+    settings: &CompilationSessionSettings,
+    event_fn: &Option<Arc<dyn JobEventHandler>>,
+) -> Option<String> {
+    settings.fingerprints.as_ref()?;
+    match fingerprint::map_fingerprint(map_info, settings) {
+        Ok(fingerprint) => Some(fingerprint),
+        Err(e) => {
+            send_or_print_event(event_fn, CoreEvent::StepWarn(
+                map_info.order_idx,
+                map_info.name.clone(),
+                FINGERPRINT_STEP.to_string(),
+                format!("Cannot fingerprint the map, compiling it: {e}"),
+            ));
+            None
+        }
+    }
+}
+
+/// Whether the last successful compile of the map had the same inputs and its .bsp is still there.
+fn is_up_to_date(map_info: &vmflow_config_types::VmfMap, settings: &CompilationSessionSettings, fingerprint: &str) -> bool {
+    let Some(path) = &settings.fingerprints else {
+        return false;
+    };
+    !settings.force_rebuild
+        && find_compiled_bsp(map_info, settings).is_some()
+        && fingerprint::matches_last_success(path, &map_info.path, fingerprint)
+}
+
+/// Whether every activated step of the preset ran and succeeded, the only compiles whose fingerprint is recorded.
+///
+/// Failures ignored by a step policy and steps skipped by their policy leave the map to be compiled again.
+fn activated_steps_succeeded(settings: &CompilationSessionSettings, steps: &[StepReport]) -> bool {
+    let app_steps: Vec<&StepReport> = steps.iter().filter(|step| step.name != PREFLIGHT_STEP).collect();
+    app_steps.len() == settings.preset.apps.len()
+        && settings.preset.apps.iter()
+            .zip(app_steps)
+            .all(|(app, step)| !app.activated || step.outcome == StepOutcome::Succeeded)
+}
+
+fn record_fingerprint(
+    map_info: &vmflow_config_types::VmfMap,
+    settings: &CompilationSessionSettings,
+    fingerprint: String,
+    event_fn: &Option<Arc<dyn JobEventHandler>>,
+) {
+    let Some(path) = &settings.fingerprints else {
+        return;
+    };
+    if let Err(e) = fingerprint::record_success(path, &map_info.path, fingerprint) {
+        send_or_print_event(event_fn, CoreEvent::StepWarn(
+            map_info.order_idx,
+            map_info.name.clone(),
+            FINGERPRINT_STEP.to_string(),
+            format!("Cannot record the fingerprint in {}: {e}", path.display()),
+        ));
+    }
+}
+
+/// Runs the steps of a map in order, following the policy of each step.
+///
+/// Returns the first failure that was not ignored by its step policy.
+async fn process_steps(
+    map_info: &vmflow_config_types::VmfMap,
+    settings: &Arc<CompilationSessionSettings>,
+    cancel_flag: &Arc<AtomicBool>,
+    event_fn: &Option<Arc<dyn JobEventHandler>>,
+    progress: &Arc<BatchProgress>,
+    steps: &mut Vec<StepReport>,
+) -> Result<(), BackendError> {
+    if settings.preset.preflight != PreflightPolicy::Off {
+        run_preflight(map_info, settings, event_fn, steps)?;
+    }
+
+    // Checked once, the steps themselves rewrite the .bsp
+    let map_changed = map_changed_since_compile(map_info);
+    let mut map_error: Option<BackendError> = None;
+    let mut previous_succeeded = true;
+    // Position among the activated steps, the deactivated ones are left out of the progress
+    let mut progress_idx = 0;
+
     for compiler_step in &settings.preset.apps {
-        if cancel_flag.load(Ordering::Relaxed) { return Ok(()) }
+        if cancel_flag.load(Ordering::Relaxed) { return Err(BackendError::Cancelled) }
 
         let step_name = compiler_step.name().to_string();
-        send_or_print_event(&event_fn, CoreEvent::StepStarted(map_info.order_idx, map_info.name.clone(), step_name.clone()));
-
-        // Processing built-in command
-        if compiler_step.config().is_builtin {
-            builtin_commands::process(step_name.as_str()).await?;
+        if let Some(reason) = skip_reason(compiler_step, map_error.is_some(), previous_succeeded, map_changed) {
+            send_or_print_event(event_fn, CoreEvent::StepSkipped(map_info.order_idx, map_info.name.clone(), step_name.clone(), reason.to_string()));
+            if compiler_step.activated {
+                send_or_print_event(event_fn, CoreEvent::StepProgress(
+                    map_info.order_idx,
+                    map_info.name.clone(),
+                    step_name.clone(),
+                    progress.update(map_info.order_idx, progress_idx, String::new(), 1.0, 1.0),
+                ));
+                progress_idx += 1;
+            }
+            steps.push(StepReport::skipped(step_name));
+            // A skipped step did not succeed, for the steps running only after a success
+            previous_succeeded = false;
+            continue;
         }
-        // Processing compiler stuff
-        else {
-            spawn_process(
-                &map_info,
-                &settings,
-                compiler_step,
-                &cancel_flag,
-                event_fn.clone(), // todo правильно ли это? будет ли он клонировать arc?
-            ).await?;
+
+        let step_started = Instant::now();
+        send_or_print_event(event_fn, CoreEvent::StepStarted(map_info.order_idx, map_info.name.clone(), step_name.clone()));
+
+        let mut diagnostics = Vec::new();
+        let result = process_step(map_info, settings, compiler_step, progress_idx, progress, cancel_flag, event_fn, &mut diagnostics).await;
+        let exit_code = match &result {
+            Ok(()) if !compiler_step.config().is_builtin => Some(0),
+            Ok(()) => None,
+            Err(error) => error.exit_code(),
+        };
+
+        let outcome = match result {
+            Ok(()) => StepOutcome::Succeeded,
+            Err(BackendError::Cancelled) => return Err(BackendError::Cancelled),
+            Err(error) => {
+                send_or_print_event(event_fn, CoreEvent::StepFailed(map_info.order_idx, map_info.name.clone(), step_name.clone(), error.to_string()));
+                if compiler_step.policy == StepPolicy::ContinueOnFailure {
+                    send_or_print_event(event_fn, CoreEvent::StepWarn(
+                        map_info.order_idx,
+                        map_info.name.clone(),
+                        step_name.clone(),
+                        "Continuing, the step is allowed to fail".to_string(),
+                    ));
+                    StepOutcome::FailureIgnored
+                } else {
+                    map_error.get_or_insert(error);
+                    StepOutcome::Failed
+                }
+            }
+        };
+        previous_succeeded = outcome == StepOutcome::Succeeded;
+        steps.push(StepReport::new(step_name.clone(), step_started.elapsed(), outcome, &diagnostics).with_exit_code(exit_code));
+
+        if previous_succeeded {
+            send_or_print_event(event_fn, CoreEvent::StepFinished(map_info.order_idx, map_info.name.clone(), step_name.clone()));
         }
+        send_or_print_event(event_fn, CoreEvent::StepProgress(
+            map_info.order_idx,
+            map_info.name.clone(),
+            step_name,
+            progress.update(map_info.order_idx, progress_idx, String::new(), 1.0, 1.0),
+        ));
+        progress_idx += 1;
+    }
+
+    map_error.map_or(Ok(()), Err)
+}
 
-        send_or_print_event(&event_fn, CoreEvent::StepFinished(map_info.order_idx, map_info.name.clone(), compiler_step.name().to_string()));
+/// Checks the map source before the first step and reports what it found.
+///
+/// Fails only with [`PreflightPolicy::Block`] and at least one error.
+fn run_preflight(
+    map_info: &vmflow_config_types::VmfMap,
+    settings: &Arc<CompilationSessionSettings>,
+    event_fn: &Option<Arc<dyn JobEventHandler>>,
+    steps: &mut Vec<StepReport>,
+) -> Result<(), BackendError> {
+    let started = Instant::now();
+    send_or_print_event(event_fn, CoreEvent::StepStarted(map_info.order_idx, map_info.name.clone(), PREFLIGHT_STEP.to_string()));
+
+    let diagnostics = preflight::check_map(map_info, &settings.preset, &settings.game_config);
+    for diagnostic in &diagnostics {
+        send_or_print_event(event_fn, CoreEvent::StepLog(
+            map_info.order_idx,
+            map_info.name.clone(),
+            PREFLIGHT_STEP.to_string(),
+            diagnostic.message.clone(),
+        ));
+        send_or_print_event(event_fn, CoreEvent::StepDiagnostic(
+            map_info.order_idx,
+            map_info.name.clone(),
+            PREFLIGHT_STEP.to_string(),
+            diagnostic.clone(),
+        ));
     }
 
-    send_or_print_event(&event_fn, CoreEvent::MapFinished(map_info.order_idx, map_info.name.clone(), Ok(())));
+    let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+    if errors > 0 && settings.preset.preflight == PreflightPolicy::Block {
+        let error = BackendError::PreflightFailed(errors);
+        send_or_print_event(event_fn, CoreEvent::StepFailed(map_info.order_idx, map_info.name.clone(), PREFLIGHT_STEP.to_string(), error.to_string()));
+        steps.push(StepReport::new(PREFLIGHT_STEP.to_string(), started.elapsed(), StepOutcome::Failed, &diagnostics));
+        return Err(error);
+    }
+    steps.push(StepReport::new(PREFLIGHT_STEP.to_string(), started.elapsed(), StepOutcome::Succeeded, &diagnostics));
+    send_or_print_event(event_fn, CoreEvent::StepFinished(map_info.order_idx, map_info.name.clone(), PREFLIGHT_STEP.to_string()));
     Ok(())
 }
 
-async fn spawn_process( // todo rename
+/// Reads the compiled .bsp of a map that succeeded and sends what it holds.
+///
+/// The .bsp next to the source is read, or the copy in the maps folder if the source one is gone.
+fn summarize_bsp(
     map_info: &vmflow_config_types::VmfMap,
     settings: &Arc<CompilationSessionSettings>,
-    compiler: &SelectedCompiler,
+    event_fn: &Option<Arc<dyn JobEventHandler>>,
+) -> Option<Box<BspSummary>> {
+    let path = find_compiled_bsp(map_info, settings)?;
+
+    match Bsp::load(&path).and_then(|bsp| bsp.summary()) {
+        Ok(summary) => {
+            let summary = Box::new(summary);
+            send_or_print_event(event_fn, CoreEvent::BspSummary(map_info.order_idx, map_info.name.clone(), summary.clone()));
+            check_limit_budget(map_info, settings, &summary, event_fn);
+            Some(summary)
+        }
+        Err(e) => {
+            send_or_print_event(event_fn, CoreEvent::StepWarn(
+                map_info.order_idx,
+                map_info.name.clone(),
+                BSP_SUMMARY_STEP.to_string(),
+                format!("Cannot read {}: {e}", path.display()),
+            ));
+            None
+        }
+    }
+}
+
+/// The .bsp next to the source, or the copy in the maps folder if the source one is gone.
+fn find_compiled_bsp(map_info: &vmflow_config_types::VmfMap, settings: &CompilationSessionSettings) -> Option<std::path::PathBuf> {
+    [
+        builtin_commands::compiled_bsp_path(map_info),
+        builtin_commands::bsp_copy_destination(map_info, &settings.game_config),
+    ].into_iter().find(|path| path.is_file())
+}
+
+/// Appends how a map compiled to the compile history of the session, if it has one.
+pub(crate) fn record_history(
+    batch_id: u64,
+    map_info: &vmflow_config_types::VmfMap,
+    settings: &CompilationSessionSettings,
+    report: &MapReport,
+    event_fn: &Option<Arc<dyn JobEventHandler>>,
+) {
+    let Some(path) = &settings.compile_history else {
+        return;
+    };
+    // Nothing ran, the previous record still describes the map
+    if report.status == MapStatus::UpToDate {
+        return;
+    }
+    let bsp_hash = (report.status == MapStatus::Succeeded)
+        .then(|| find_compiled_bsp(map_info, settings))
+        .flatten()
+        .and_then(|bsp| history::hash_file(&bsp).ok());
+    let record = history::CompileRecord::new(batch_id, report, &settings.preset, &settings.game_config, bsp_hash);
+
+    if let Err(e) = history::append(path, &record) {
+        send_or_print_event(event_fn, CoreEvent::StepWarn(
+            map_info.order_idx,
+            map_info.name.clone(),
+            HISTORY_STEP.to_string(),
+            format!("Cannot record the compile in {}: {e}", path.display()),
+        ));
+    }
+}
+
+/// Records the limit usage of a compiled map and sends the limits crossing the budget of the preset.
+fn check_limit_budget(
+    map_info: &vmflow_config_types::VmfMap,
+    settings: &Arc<CompilationSessionSettings>,
+    summary: &BspSummary,
+    event_fn: &Option<Arc<dyn JobEventHandler>>,
+) {
+    let history = settings.budget_history.as_deref();
+    let alerts = match budget::record_compile(history, &map_info.path, summary, &settings.preset.budget) {
+        Ok(alerts) => alerts,
+        Err(e) => {
+            send_or_print_event(event_fn, CoreEvent::StepWarn(
+                map_info.order_idx,
+                map_info.name.clone(),
+                BSP_SUMMARY_STEP.to_string(),
+                format!("Cannot record the limit usage in {}: {e}", history.map_or(String::new(), |path| path.display().to_string())),
+            ));
+            // The thresholds do not need the history
+            budget::check_budget(&summary.limits, None, &settings.preset.budget)
+        }
+    };
+    for alert in alerts {
+        send_or_print_event(event_fn, CoreEvent::LimitAlert(map_info.order_idx, map_info.name.clone(), alert));
+    }
+}
+
+/// Returns why a step must not run, if it must not.
+fn skip_reason(step: &SelectedCompiler, map_failed: bool, previous_succeeded: bool, map_changed: bool) -> Option<&'static str> {
+    if !step.activated {
+        return Some("the step is deactivated");
+    }
+    match step.policy {
+        StepPolicy::AlwaysRun => None,
+        _ if map_failed => Some("a previous step failed"),
+        StepPolicy::RunIfPreviousSucceeded if !previous_succeeded => Some("the previous step did not succeed"),
+        StepPolicy::RunIfMapChanged if !map_changed => Some("the map did not change since the last compile"),
+        _ => None,
+    }
+}
+
+/// Returns whether the map source is newer than its compiled .bsp, or was never compiled.
+fn map_changed_since_compile(map_info: &vmflow_config_types::VmfMap) -> bool {
+    let modified = |path: &std::path::Path| std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    match (modified(&map_info.path), modified(&builtin_commands::compiled_bsp_path(map_info))) {
+        (Some(source), Some(compiled)) => source > compiled,
+        _ => true,
+    }
+}
+
+/// Runs a single step, built-in or external compiler.
+#[allow(clippy::too_many_arguments)]
+async fn process_step(
+    map_info: &vmflow_config_types::VmfMap,
+    settings: &Arc<CompilationSessionSettings>,
+    compiler_step: &SelectedCompiler,
+    step_idx: usize,
+    progress: &Arc<BatchProgress>,
     cancel_flag: &Arc<AtomicBool>,
-    event_handler: Option<Arc<dyn JobEventHandler>>,
+    event_fn: &Option<Arc<dyn JobEventHandler>>,
+    diagnostics: &mut Vec<Diagnostic>,
 ) -> Result<(), BackendError> {
-    let mut executable = settings.game_config.custom_apps_paths[compiler.compiler_idx].clone();
+    // Processing built-in command
+    if compiler_step.config().is_builtin {
+        return builtin_commands::process(compiler_step, map_info, settings, step_idx, progress, cancel_flag, event_fn, diagnostics).await;
+    }
+
+    // Processing compiler stuff
+    let result = spawn_process(
+        map_info,
+        settings,
+        compiler_step,
+        step_idx,
+        progress,
+        cancel_flag,
+        event_fn.clone(), // todo правильно ли это? будет ли он клонировать arc?
+        diagnostics,
+    ).await;
+
+    // A leaked map is not worth running VVIS/VRAD on, even if VBSP exited successfully
+    let step_name = compiler_step.name();
+    let leak = (Tool::from_step_name(step_name) == Tool::Vbsp)
+        .then(|| detect_leak(map_info, step_name, diagnostics, event_fn))
+        .flatten();
+    match leak {
+        Some(leak) => {
+            let pointfile_path = leak.pointfile_path.clone();
+            send_or_print_event(event_fn, CoreEvent::LeakDetected(map_info.order_idx, map_info.name.clone(), leak));
+            Err(BackendError::MapLeaked(pointfile_path))
+        }
+        None => result,
+    }
+}
+
+/// Command line of an external compiler step, placeholders resolved.
+struct StepCommand {
+    executable: String,
+    arguments: Vec<String>,
+    work_dir: String,
+    envs: Vec<(String, String)>,
+}
+
+/// Resolves the executable, the arguments and the working dir of an external compiler step.
+fn build_command(
+    map_info: &vmflow_config_types::VmfMap,
+    settings: &CompilationSessionSettings,
+    compiler: &SelectedCompiler,
+) -> Result<StepCommand, BackendError> {
+    let mut executable = settings.game_config.compiler_path(&compiler.compiler_id)
+        .unwrap_or_default()
+        .to_string();
     if executable.is_empty() {
         return Err(BackendError::CommandNotFound(format!(
             "Path for {} not installed",
@@ -62,68 +451,185 @@ async fn spawn_process( // todo rename
         )));
     }
 
-    let mut work_dir = compiler.config().working_dir.clone().unwrap_or("$binFolder".to_string());
-    resolve_placeholders(&mut work_dir, map_info, settings);
+    let wine = (cfg!(unix) && executable.ends_with(".exe"))
+        .then(|| WinePathTranslator::new(&settings.game_config.wine));
+    let dir_placeholders = PlaceholderContext {
+        map_info,
+        game_config: &settings.game_config,
+        path_style: PathStyle::Native,
+    };
+    let arg_placeholders = PlaceholderContext {
+        path_style: wine.as_ref().map_or(PathStyle::Native, PathStyle::Wine),
+        ..dir_placeholders
+    };
 
-    let mut command_args = compiler.get_command_params(); // todo process placeholders!
-    command_args.iter_mut().for_each(|arg| resolve_placeholders(arg, map_info, settings));
+    let mut unresolved = Vec::new();
+    let work_dir = compiler.config().working_dir.as_deref().unwrap_or("$binFolder");
+    let work_dir = dir_placeholders.resolve_into(work_dir, &mut unresolved);
 
-    #[cfg(unix)]
-    if executable.ends_with(".exe") {
+    let mut command_args = Vec::new();
+    for param in &compiler.parameters {
+        let Some(mut parts) = param.get_command_parts() else { continue };
+        let path_value_idx = (matches!(param.value_type(), ParameterType::Path) && param.value_or_default().is_some())
+            .then(|| parts.len() - 1);
+
+        for (idx, part) in parts.iter_mut().enumerate() {
+            *part = if Some(idx) == path_value_idx {
+                // Path values are written for this machine and translated as a whole
+                let native = dir_placeholders.resolve_into(part, &mut unresolved);
+                arg_placeholders.path(&native).unwrap_or_else(|reason| {
+                    unresolved.push(format!("{} ({reason})", param.name()));
+                    native
+                })
+            } else {
+                arg_placeholders.resolve_into(part, &mut unresolved)
+            };
+        }
+        command_args.append(&mut parts);
+    }
+    let base_args = compiler.config().base_arguments.as_deref().unwrap_or_default();
+    for arg in base_args.split_whitespace() {
+        command_args.push(arg_placeholders.resolve_into(arg, &mut unresolved));
+    }
+
+    // The working dir stays a host path, but the compiler must still be able to see it
+    if let Some(wine) = &wine && !work_dir.is_empty() {
+        wine.translate(std::path::Path::new(&work_dir))?;
+    }
+
+    if !unresolved.is_empty() {
+        // Each variable once, in the order the arguments use them
+        let mut seen = std::collections::HashSet::new();
+        unresolved.retain(|variable| seen.insert(variable.clone()));
+        return Err(BackendError::UnresolvedPlaceholders(compiler.name().to_string(), unresolved));
+    }
+
+    let mut envs = Vec::new();
+    if let Some(wine) = &wine {
         command_args.insert(0, executable);
-        executable = "wine".to_string();
+        executable = wine.binary().to_string();
+        envs = wine.envs();
     }
 
+    Ok(StepCommand { executable, arguments: command_args, work_dir, envs })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn spawn_process( // todo rename
+    map_info: &vmflow_config_types::VmfMap,
+    settings: &Arc<CompilationSessionSettings>,
+    compiler: &SelectedCompiler,
+    step_idx: usize,
+    progress: &Arc<BatchProgress>,
+    cancel_flag: &Arc<AtomicBool>,
+    event_handler: Option<Arc<dyn JobEventHandler>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<(), BackendError> {
+    let StepCommand { executable, arguments, work_dir, envs } = build_command(map_info, settings, compiler)?;
+
     // bruh bruh bruh bruh todo
     send_or_print_event(&event_handler, CoreEvent::StepLog(
         map_info.order_idx,
         map_info.name.clone(),
         compiler.name().to_string(),
-        format!("Executing: {executable} {command_args:?}")
+        format!("Executing: {executable} {arguments:?}")
     ));
 
     execute_process(
         map_info.order_idx,
         map_info.name.clone(),
         compiler.name().to_string(), // todo
+        step_idx,
+        Arc::clone(progress),
         executable,
-        command_args,
+        arguments,
         work_dir,
+        envs,
         Arc::clone(cancel_flag),
-        event_handler
+        event_handler,
+        diagnostics,
     ).await?;
 
     Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
 
-pub fn resolve_placeholders(
-    arg: &mut String,
-    map_info: &vmflow_config_types::VmfMap,
-    settings: &Arc<CompilationSessionSettings>
-) {
-    // #[cfg(not(unix))] // TODO PLACEHOLDER!
+    use vmflow_config_types::{parameter_override::ParameterOverride, GameConfiguration, VmfMap};
+
+    use super::*;
+
+    fn settings() -> CompilationSessionSettings {
+        let mut game_config = GameConfiguration {
+            game_dir: "/games/hl2".to_string(),
+            bin_dir: "/games/bin".to_string(),
+            ..Default::default()
+        };
+        game_config.compiler_paths.insert("game".to_string(), "/games/hl2_linux".to_string());
+        CompilationSessionSettings { game_config, ..Default::default() }
+    }
+
+    fn map() -> VmfMap {
+        VmfMap { name: "test".to_string(), path: PathBuf::from("/maps/test.vmf"), activated: true, order_idx: 0 }
+    }
+
+    #[test]
+    fn reports_each_unresolved_variable_once() {
+        let argument = compilers_service::get_compiler("game").unwrap()
+            .parameters.iter()
+            .find(|parameter| parameter.name == "Command Line Argument")
+            .unwrap();
+        let mut step = SelectedCompiler::from_id("game");
+        for value in ["$nope", "$missing", "$nope/$map"] {
+            step.parameters.push(ParameterOverride {
+                value: Some(value.to_string()),
+                ..ParameterOverride::new("game", &argument.id)
+            });
+        }
+
+        match build_command(&map(), &settings(), &step) {
+            Err(BackendError::UnresolvedPlaceholders(name, variables)) => {
+                assert_eq!(name, "GAME");
+                assert_eq!(variables, ["$nope", "$missing"]);
+            }
+            Err(e) => panic!("expected unresolved placeholders, got {e}"),
+            Ok(_) => panic!("expected unresolved placeholders, the command was built"),
+        }
+    }
+
+    #[test]
+    fn skips_only_unchanged_maps_with_their_bsp() {
+        let dir = std::env::temp_dir().join("vmflow_up_to_date_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let map = VmfMap { path: dir.join("test.vmf"), ..map() };
+        let mut settings = CompilationSessionSettings {
+            fingerprints: Some(dir.join("fingerprints.json")),
+            ..settings()
+        };
+        settings.game_config.game_dir = dir.join("game").to_string_lossy().into_owned();
 
-    #[cfg(unix)]
-    match arg.as_str() { // todo what is there is no disk Z?
-        "$gameDir" => *arg = "Z:".to_string() + &settings.game_config.game_dir,
-        // "$gameName" => *arg = "Z:".to_string() + &settings.game_config.game_dir,
-        // "$gameExe" => *arg = "Z:".to_string() + &settings.game_config.game_dir,
+        fingerprint::record_success(&dir.join("fingerprints.json"), &map.path, "abc".to_string()).unwrap();
+        std::fs::write(dir.join("test.bsp"), "bsp").unwrap();
+        assert!(is_up_to_date(&map, &settings, "abc"));
+        assert!(!is_up_to_date(&map, &settings, "changed"));
 
-        // "$mapDir" => *arg = "Z:".to_string() + &map_info.path.to_string_lossy(),
-        "$mapFile" => *arg = "Z:".to_string() + &map_info.path.to_string_lossy(),
-        // "$mapName" => *arg = "Z:".to_string() + &map_info.path.to_string_lossy(),
+        settings.force_rebuild = true;
+        assert!(!is_up_to_date(&map, &settings, "abc"));
+        settings.force_rebuild = false;
 
-        "$binFolder" => *arg = settings.game_config.bin_dir.clone(),
-        "$outputDir" => *arg = settings.game_config.output_dir.clone(),
+        // The copy in the maps folder counts too
+        std::fs::create_dir_all(dir.join("game/maps")).unwrap();
+        std::fs::rename(dir.join("test.bsp"), dir.join("game/maps/test.bsp")).unwrap();
+        assert!(is_up_to_date(&map, &settings, "abc"));
 
-        // FUCKING TODO, because fucking hard-code
-        "$vbsp" => *arg = settings.game_config.custom_apps_paths[0].clone(),
-        "$vvis" => *arg = settings.game_config.custom_apps_paths[1].clone(),
-        "$vrad" => *arg = settings.game_config.custom_apps_paths[2].clone(),
-        "$bspZip" => *arg = settings.game_config.custom_apps_paths[3].clone(),
-        // "vbspInfo" => *arg = settings.game_config.custom_apps_paths[4].clone(),
+        std::fs::remove_file(dir.join("game/maps/test.bsp")).unwrap();
+        assert!(!is_up_to_date(&map, &settings, "abc"));
 
-        _ => {}
+        std::fs::write(dir.join("test.bsp"), "bsp").unwrap();
+        settings.fingerprints = None;
+        assert!(!is_up_to_date(&map, &settings, "abc"));
     }
 }
@@ -0,0 +1,169 @@
+//! The PACK built-in: embeds the custom content of a map into its .bsp with BSPZIP.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use async_std::fs;
+use compiler_logs_process::Diagnostic;
+use vmflow_config_types::{selected_compiler::SelectedCompiler, VmfMap};
+
+use crate::{
+    content::{collect_referenced_content, GameContent},
+    send_or_print_event, BackendError, BatchProgress, CompilationSessionSettings, CoreEvent, JobEventHandler,
+};
+
+use super::{builtin_commands::compiled_bsp_path, execute_process, wine::WinePathTranslator};
+
+/// Returns the path of the BSPZIP file list written for a map, next to its source.
+pub fn addlist_path(map_info: &VmfMap) -> PathBuf {
+    let stem = map_info.path.file_stem().unwrap_or_default().to_string_lossy();
+    map_info.path.with_file_name(format!("{stem}_addlist.txt"))
+}
+
+/// Finds the custom content of the map, writes it to a BSPZIP file list and packs it into the .bsp.
+///
+/// Supported step arguments:
+/// * `-listonly` - writes the file list without running BSPZIP.
+/// * `-exclude <paths>` - skips files under these `;` separated paths (`sound/music;maps/`).
+#[allow(clippy::too_many_arguments)]
+pub async fn pack_content(
+    compiler_step: &SelectedCompiler,
+    map_info: &VmfMap,
+    settings: &Arc<CompilationSessionSettings>,
+    step_idx: usize,
+    progress: &Arc<BatchProgress>,
+    cancel_flag: &Arc<AtomicBool>,
+    event_handler: &Option<Arc<dyn JobEventHandler>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<(), BackendError> {
+    let game_config = &settings.game_config;
+    let args = compiler_step.get_command_params();
+    let list_only = args.iter().any(|arg| arg == "-listonly");
+    let excluded: Vec<String> = args.iter()
+        .skip_while(|arg| *arg != "-exclude")
+        .nth(1)
+        .map(|paths| paths.split(';').map(|path| path.trim().replace('\\', "/").to_lowercase()).filter(|path| !path.is_empty()).collect())
+        .unwrap_or_default();
+
+    let log = |message: String| send_or_print_event(event_handler, CoreEvent::StepLog(
+        map_info.order_idx,
+        map_info.name.clone(),
+        compiler_step.name().to_string(),
+        message,
+    ));
+
+    let game_content = GameContent::load(Path::new(&game_config.game_dir));
+    for error in &game_content.errors {
+        send_or_print_event(event_handler, CoreEvent::StepWarn(
+            map_info.order_idx,
+            map_info.name.clone(),
+            compiler_step.name().to_string(),
+            format!("Stock files of {error} are unknown and may be packed"),
+        ));
+    }
+    let files: Vec<(String, PathBuf)> = collect_referenced_content(&map_info.path, &game_content)
+        .map_err(|e| BackendError::BuiltinFailed(format!("Cannot scan {}: {e}", map_info.path.display())))?
+        .into_iter()
+        .filter(|(relative, _)| !excluded.iter().any(|prefix| relative.starts_with(prefix.as_str())))
+        .collect();
+
+    if files.is_empty() {
+        log("No custom content to pack".to_string());
+        return Ok(());
+    }
+
+    pack_files(&files, list_only, compiler_step, map_info, settings, step_idx, progress, cancel_flag, event_handler, diagnostics).await
+}
+
+/// Writes `files` to the BSPZIP file list of the map and, unless `list_only`, packs them into its .bsp.
+///
+/// `files` are `(path inside the .bsp, file on disk)` pairs.
+#[allow(clippy::too_many_arguments)]
+pub async fn pack_files(
+    files: &[(String, PathBuf)],
+    list_only: bool,
+    compiler_step: &SelectedCompiler,
+    map_info: &VmfMap,
+    settings: &Arc<CompilationSessionSettings>,
+    step_idx: usize,
+    progress: &Arc<BatchProgress>,
+    cancel_flag: &Arc<AtomicBool>,
+    event_handler: &Option<Arc<dyn JobEventHandler>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<(), BackendError> {
+    let game_config = &settings.game_config;
+    let log = |message: String| send_or_print_event(event_handler, CoreEvent::StepLog(
+        map_info.order_idx,
+        map_info.name.clone(),
+        compiler_step.name().to_string(),
+        message,
+    ));
+
+    let executable = game_config.compiler_path("bspzip").unwrap_or_default().to_string();
+    let wine = (cfg!(unix) && executable.ends_with(".exe"))
+        .then(|| WinePathTranslator::new(&game_config.wine));
+    // BSPZIP runs in the bin folder and reads the list itself, so paths must be absolute and the way it sees them
+    let external = |path: &Path| {
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        match &wine {
+            Some(wine) => wine.translate(&path),
+            None => Ok(path.display().to_string()),
+        }
+    };
+
+    let mut addlist = String::new();
+    for (relative, path) in files {
+        log(format!("Packing {relative}"));
+        addlist.push_str(&format!("{relative}\n{}\n", external(path)?));
+    }
+    let addlist_path = addlist_path(map_info);
+    fs::write(&addlist_path, addlist).await.map_err(|e| BackendError::BuiltinFailed(format!(
+        "Failed to write {}: {e}", addlist_path.display()
+    )))?;
+    log(format!("Wrote {} files to {}", files.len(), addlist_path.display()));
+
+    if list_only {
+        return Ok(());
+    }
+    if executable.is_empty() {
+        return Err(BackendError::CommandNotFound("Path for BSPZIP not installed".to_string()));
+    }
+    let bsp = compiled_bsp_path(map_info);
+    if !fs::metadata(&bsp).await.is_ok_and(|meta| meta.is_file()) {
+        return Err(BackendError::BuiltinFailed(format!("Compiled map not found: {}", bsp.display())));
+    }
+
+    let bsp_arg = external(&bsp)?;
+    let mut command_args = vec![
+        "-addlist".to_string(),
+        bsp_arg.clone(),
+        external(&addlist_path)?,
+        bsp_arg,
+        "-game".to_string(),
+        external(Path::new(&game_config.game_dir))?,
+    ];
+    let (executable, envs) = match &wine {
+        Some(wine) => {
+            command_args.insert(0, executable);
+            (wine.binary().to_string(), wine.envs())
+        }
+        None => (executable, Vec::new()),
+    };
+
+    execute_process(
+        map_info.order_idx,
+        map_info.name.clone(),
+        compiler_step.name().to_string(),
+        step_idx,
+        Arc::clone(progress),
+        executable,
+        command_args,
+        game_config.bin_dir.clone(),
+        envs,
+        Arc::clone(cancel_flag),
+        event_handler.clone(),
+        diagnostics,
+    ).await
+}
@@ -0,0 +1,114 @@
+//! The PARTICLES built-in: writes the particle manifest of a map from the effects it uses.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use async_std::fs;
+use compiler_logs_process::Diagnostic;
+use vmflow_config_types::{selected_compiler::SelectedCompiler, VmfMap};
+
+use crate::{
+    content::{collect_particle_effects, particle_system_names, GameContent},
+    send_or_print_event, BackendError, BatchProgress, CompilationSessionSettings, CoreEvent, JobEventHandler,
+};
+
+use super::{builtin_commands::maps_output_dir, pack::pack_files};
+
+/// Finds the custom .pcf files defining the `info_particle_system` effects of the map and lists them
+/// in `maps/<map>_particles.txt`, next to where COPY puts the .bsp.
+///
+/// Supported step arguments:
+/// * `-pack` - also packs the manifest and the .pcf files into the .bsp with BSPZIP.
+/// * `-nopreload` - lists the files without `!`, so the engine loads them on first use.
+#[allow(clippy::too_many_arguments)]
+pub async fn write_particle_manifest(
+    compiler_step: &SelectedCompiler,
+    map_info: &VmfMap,
+    settings: &Arc<CompilationSessionSettings>,
+    step_idx: usize,
+    progress: &Arc<BatchProgress>,
+    cancel_flag: &Arc<AtomicBool>,
+    event_handler: &Option<Arc<dyn JobEventHandler>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<(), BackendError> {
+    let game_config = &settings.game_config;
+    let args = compiler_step.get_command_params();
+    let pack = args.iter().any(|arg| arg == "-pack");
+    let preload = !args.iter().any(|arg| arg == "-nopreload");
+
+    let log = |message: String| send_or_print_event(event_handler, CoreEvent::StepLog(
+        map_info.order_idx,
+        map_info.name.clone(),
+        compiler_step.name().to_string(),
+        message,
+    ));
+    let warn = |message: String| send_or_print_event(event_handler, CoreEvent::StepWarn(
+        map_info.order_idx,
+        map_info.name.clone(),
+        compiler_step.name().to_string(),
+        message,
+    ));
+
+    let effects = collect_particle_effects(&map_info.path)
+        .map_err(|e| BackendError::BuiltinFailed(format!("Cannot scan {}: {e}", map_info.path.display())))?;
+    if effects.is_empty() {
+        log("No particle systems in the map".to_string());
+        return Ok(());
+    }
+
+    // Effect name -> the first custom .pcf defining it
+    let game_content = GameContent::load(Path::new(&game_config.game_dir));
+    let mut definitions: BTreeMap<String, (String, PathBuf)> = BTreeMap::new();
+    for (relative, path) in game_content.custom_files("particles", "pcf") {
+        match particle_system_names(&path) {
+            Ok(names) => for name in names {
+                definitions.entry(name.to_lowercase()).or_insert_with(|| (relative.clone(), path.clone()));
+            },
+            Err(e) => warn(format!("Cannot read {relative}: {e}")),
+        }
+    }
+
+    let mut pcf_files: BTreeSet<(String, PathBuf)> = BTreeSet::new();
+    for effect in &effects {
+        match definitions.get(effect) {
+            Some(pcf) => {
+                log(format!("{effect} is defined in {}", pcf.0));
+                pcf_files.insert(pcf.clone());
+            }
+            None => log(format!("{effect} is not in a custom .pcf, expecting the game to load it")),
+        }
+    }
+    if pcf_files.is_empty() {
+        log("Every particle system is stock, no manifest needed".to_string());
+        return Ok(());
+    }
+
+    let map_name = map_info.path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let manifest_relative = format!("maps/{map_name}_particles.txt");
+    let maps_dir = maps_output_dir(game_config);
+    let manifest_path = maps_dir.join(format!("{map_name}_particles.txt"));
+    let prefix = if preload { "!" } else { "" };
+    let mut manifest = String::from("particles_manifest\n{\n");
+    for (relative, _) in &pcf_files {
+        manifest.push_str(&format!("\t\"file\"\t\"{prefix}{relative}\"\n"));
+    }
+    manifest.push_str("}\n");
+
+    let write_error = |e: std::io::Error| BackendError::BuiltinFailed(format!(
+        "Failed to write {}: {e}", manifest_path.display()
+    ));
+    fs::create_dir_all(&maps_dir).await.map_err(write_error)?;
+    fs::write(&manifest_path, manifest).await.map_err(write_error)?;
+    log(format!("Wrote {} .pcf files to {}", pcf_files.len(), manifest_path.display()));
+
+    if !pack {
+        return Ok(());
+    }
+    let files: Vec<(String, PathBuf)> = std::iter::once((manifest_relative, manifest_path.clone()))
+        .chain(pcf_files)
+        .collect();
+    pack_files(&files, false, compiler_step, map_info, settings, step_idx, progress, cancel_flag, event_handler, diagnostics).await
+}
@@ -0,0 +1,235 @@
+//! Substitution of `$variables` in compiler arguments and working directories.
+//!
+//! Supported variables:
+//! * `$gameDir`, `$gameName`, `$gameExe` - game folder, its folder name and the GAME executable.
+//! * `$mapFile`, `$mapDir`, `$mapName`, `$map` - map source, its folder and its name without extension.
+//! * `$binFolder`, `$outputDir`, `$mapCopyLocation` - bin folder, maps folder and the copied .bsp.
+//! * `$<compiler>` - path of any loaded compiler, e.g. `$vbsp`, `$vrad`, `$bspZip`, `$vbspInfo`.
+//!
+//! Variables are replaced anywhere inside an argument (`-path=$mapDir/foo`), `$$` produces a literal `$`.
+
+use std::path::Path;
+
+use vmflow_config_types::{GameConfiguration, VmfMap};
+
+use super::{
+    builtin_commands::{bsp_copy_destination, maps_output_dir},
+    wine::WinePathTranslator,
+};
+
+/// How path-valued variables are written into the resolved string.
+#[derive(Clone, Copy)]
+pub enum PathStyle<'a> {
+    /// Paths as seen by this machine (used for working directories).
+    Native,
+    /// Paths as seen by a Windows compiler running under Wine.
+    Wine(&'a WinePathTranslator),
+}
+
+/// Values the placeholders of a single step are resolved against.
+pub struct PlaceholderContext<'a> {
+    pub map_info: &'a VmfMap,
+    pub game_config: &'a GameConfiguration,
+    pub path_style: PathStyle<'a>,
+}
+
+impl PlaceholderContext<'_> {
+    /// Replaces every `$variable` in `input`.
+    ///
+    /// Returns the list of variables that could not be resolved if there are any.
+    pub fn resolve(&self, input: &str) -> Result<String, Vec<String>> {
+        let mut output = String::with_capacity(input.len());
+        let mut unresolved = Vec::new();
+        let mut rest = input;
+
+        while let Some(pos) = rest.find('$') {
+            output.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+
+            if let Some(stripped) = rest.strip_prefix('$') {
+                output.push('$');
+                rest = stripped;
+                continue;
+            }
+
+            let name_len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            if name_len == 0 {
+                output.push('$');
+                continue;
+            }
+
+            let name = &rest[..name_len];
+            match self.lookup(name) {
+                Ok(value) => output.push_str(&value),
+                Err(reason) if reason.is_empty() => unresolved.push(format!("${name}")),
+                Err(reason) => unresolved.push(format!("${name} ({reason})")),
+            }
+            rest = &rest[name_len..];
+        }
+        output.push_str(rest);
+
+        if unresolved.is_empty() {
+            Ok(output)
+        } else {
+            Err(unresolved)
+        }
+    }
+
+    /// Like [`Self::resolve`], but appends unresolved variables to `unresolved` and returns
+    /// an empty string instead of failing, so every problem of a step can be reported at once.
+    pub fn resolve_into(&self, input: &str, unresolved: &mut Vec<String>) -> String {
+        self.resolve(input).unwrap_or_else(|mut vars| {
+            unresolved.append(&mut vars);
+            String::new()
+        })
+    }
+
+    /// Resolves a single variable name (without `$`).
+    ///
+    /// An empty error means the variable is unknown.
+    fn lookup(&self, name: &str) -> Result<String, String> {
+        let game = self.game_config;
+        let map = self.map_info;
+
+        let value = match name {
+            "gameDir" => self.path(&game.game_dir)?,
+            "gameName" => Path::new(&game.game_dir)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "gameExe" => return self.compiler_path("game")
+                .unwrap_or_else(|| Err("GAME path is not set".to_string())),
+
+            "mapFile" => self.path(&map.path)?,
+            "mapDir" => self.path(map.path.parent().unwrap_or(Path::new("")))?,
+            "mapName" | "map" => map.path
+                .file_stem()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+
+            "binFolder" => self.path(&game.bin_dir)?,
+            "outputDir" => self.path(maps_output_dir(game))?,
+            "mapCopyLocation" => self.path(bsp_copy_destination(map, game))?,
+
+            _ => {
+                let compiler = compilers_service::iter_configs()
+                    .find(|c| !c.is_builtin && (normalize(&c.name) == normalize(name) || c.id == name))
+                    .ok_or_else(String::new)?;
+                return self.compiler_path(&compiler.id)
+                    .unwrap_or_else(|| Err(format!("{} path is not set", compiler.name)));
+            }
+        };
+        Ok(value)
+    }
+
+    /// Returns the configured executable of a compiler.
+    fn compiler_path(&self, compiler_id: &str) -> Option<Result<String, String>> {
+        self.game_config.compiler_path(compiler_id).map(|path| self.path(path))
+    }
+
+    /// Formats a path according to the path style of the context.
+    pub fn path(&self, path: impl AsRef<Path>) -> Result<String, String> {
+        let path = path.as_ref();
+        match self.path_style {
+            PathStyle::Native => Ok(path.to_string_lossy().into_owned()),
+            PathStyle::Wine(translator) => translator
+                .translate(path)
+                .map_err(|_| format!("{} is not visible inside the Wine prefix", path.display())),
+        }
+    }
+}
+
+/// Name form used to match `$variables` against compiler names: `VBSP Info` == `$vbspInfo`.
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn map() -> VmfMap {
+        VmfMap {
+            name: "test".to_string(),
+            path: PathBuf::from("/maps/src/test.vmf"),
+            activated: true,
+            order_idx: 0,
+        }
+    }
+
+    fn game() -> GameConfiguration {
+        let mut game = GameConfiguration {
+            name: "HL2".to_string(),
+            game_dir: "/games/hl2".to_string(),
+            bin_dir: "/games/bin".to_string(),
+            ..Default::default()
+        };
+        game.compiler_paths.insert("vbsp".to_string(), "/games/bin/vbsp.exe".to_string());
+        game.compiler_paths.insert("vbsp_info".to_string(), "/games/bin/vbspinfo.exe".to_string());
+        game.compiler_paths.insert("vrad".to_string(), String::new());
+        game.compiler_paths.insert("game".to_string(), "/games/hl2.exe".to_string());
+        game
+    }
+
+    fn resolve(game: &GameConfiguration, input: &str) -> Result<String, Vec<String>> {
+        let map = map();
+        let context = PlaceholderContext { map_info: &map, game_config: game, path_style: PathStyle::Native };
+        context.resolve(input)
+    }
+
+    #[test]
+    fn resolves_map_and_game_variables() {
+        let game = game();
+        assert_eq!(resolve(&game, "-game $gameDir").unwrap(), "-game /games/hl2");
+        assert_eq!(resolve(&game, "$gameName/$mapName.bsp").unwrap(), "hl2/test.bsp");
+        assert_eq!(resolve(&game, "-path=$mapDir/foo $map").unwrap(), "-path=/maps/src/foo test");
+        assert_eq!(resolve(&game, "$mapFile").unwrap(), "/maps/src/test.vmf");
+        assert_eq!(resolve(&game, "$binFolder").unwrap(), "/games/bin");
+        assert_eq!(resolve(&game, "$gameExe -game $gameDir").unwrap(), "/games/hl2.exe -game /games/hl2");
+        assert_eq!(resolve(&game, "$mapCopyLocation").unwrap(), "/games/hl2/maps/test.bsp");
+
+        let game = GameConfiguration { output_dir: "/out".to_string(), ..game };
+        assert_eq!(resolve(&game, "$outputDir").unwrap(), "/out");
+    }
+
+    #[test]
+    fn resolves_compilers_by_name_or_id() {
+        let game = game();
+        assert_eq!(resolve(&game, "$vbsp").unwrap(), "/games/bin/vbsp.exe");
+        assert_eq!(resolve(&game, "$vbspInfo").unwrap(), "/games/bin/vbspinfo.exe");
+        assert_eq!(resolve(&game, "$vbsp_info").unwrap(), "/games/bin/vbspinfo.exe");
+    }
+
+    #[test]
+    fn escapes_and_lone_dollars_are_kept() {
+        let game = game();
+        assert_eq!(resolve(&game, "$$mapName costs 5$").unwrap(), "$mapName costs 5$");
+        assert_eq!(resolve(&game, "a $ b").unwrap(), "a $ b");
+        assert_eq!(resolve(&game, "no variables").unwrap(), "no variables");
+    }
+
+    #[test]
+    fn reports_every_unresolved_variable() {
+        let mut game = game();
+        game.compiler_paths.remove("game");
+        assert_eq!(
+            resolve(&game, "$unknown $vrad $gameExe $mapName").unwrap_err(),
+            ["$unknown", "$vrad (VRAD path is not set)", "$gameExe (GAME path is not set)"]
+        );
+
+        let map = map();
+        let context = PlaceholderContext { map_info: &map, game_config: &game, path_style: PathStyle::Native };
+        let mut unresolved = vec!["$first".to_string()];
+        assert_eq!(context.resolve_into("$mapName", &mut unresolved), "test");
+        assert_eq!(context.resolve_into("$nope/$mapName", &mut unresolved), "");
+        assert_eq!(unresolved, ["$first", "$nope"]);
+    }
+}
@@ -0,0 +1,271 @@
+//! Checks of the map source for common mistakes, run before the first step.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use compiler_logs_process::{Diagnostic, DiagnosticKind, Severity};
+use vmf_parser::{ClassKind, EntityClasses, Solid, Vec3, Vmf};
+use vmflow_config_types::{preset::Preset, GameConfiguration, VmfMap};
+
+/// Step name the pre-flight diagnostics are reported under.
+pub const PREFLIGHT_STEP: &str = "Pre-flight checks";
+
+const MAX_MAP_BRUSHES: usize = 8192;
+const MAX_MAP_BRUSHSIDES: usize = 65536;
+/// Share of an engine limit from which a warning is reported.
+const LIMIT_WARNING_RATIO: f64 = 0.8;
+/// Distance an entity may stick out of the world brushes, in units.
+const BOUNDS_TOLERANCE: f64 = 1.0;
+
+/// Inspects the map source and returns what looks wrong with it.
+pub fn check_map(map_info: &VmfMap, preset: &Preset, game: &GameConfiguration) -> Vec<Diagnostic> {
+    let vmf = match Vmf::load(&map_info.path) {
+        Ok(vmf) => vmf,
+        Err(e) => return vec![diagnostic(Severity::Error, format!("The map cannot be read: {e}"))],
+    };
+
+    let mut diagnostics = Vec::new();
+    check_spawns(&vmf, &mut diagnostics);
+    check_world_bounds(&vmf, &mut diagnostics);
+    check_solids(&vmf, &mut diagnostics);
+    check_limits(&vmf, &mut diagnostics);
+    check_sky_light(&vmf, preset, &mut diagnostics);
+    check_targetnames(&vmf, &mut diagnostics);
+    match load_entity_classes(game, &mut diagnostics) {
+        Some(classes) => check_entity_classes(&vmf, &classes, &mut diagnostics),
+        None => diagnostics.push(diagnostic(Severity::Info, "No FGD found for the game, entity classes are not checked".to_string())),
+    }
+    diagnostics
+}
+
+fn diagnostic(severity: Severity, message: String) -> Diagnostic {
+    Diagnostic {
+        severity,
+        kind: DiagnosticKind::Preflight,
+        message,
+        step: PREFLIGHT_STEP.to_string(),
+        brush_id: None,
+        entity_id: None,
+        side_id: None,
+    }
+}
+
+fn check_spawns(vmf: &Vmf, diagnostics: &mut Vec<Diagnostic>) {
+    let has_spawn = vmf.entities.iter().any(|entity| entity.classname.to_ascii_lowercase().starts_with("info_player_"));
+    if !has_spawn {
+        diagnostics.push(diagnostic(Severity::Warning, "The map has no info_player_* spawn point".to_string()));
+    }
+}
+
+/// Bounding box of the world brushes, `None` without any.
+fn world_bounds(vmf: &Vmf) -> Option<(Vec3, Vec3)> {
+    let mut points = vmf.world.solids.iter().flat_map(|solid| &solid.sides).flat_map(|side| side.plane);
+    let first = points.next()?;
+    Some(points.fold((first, first), |(mut min, mut max), point| {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(point[axis]);
+            max[axis] = max[axis].max(point[axis]);
+        }
+        (min, max)
+    }))
+}
+
+fn check_world_bounds(vmf: &Vmf, diagnostics: &mut Vec<Diagnostic>) {
+    let Some((min, max)) = world_bounds(vmf) else {
+        diagnostics.push(diagnostic(Severity::Error, "The world has no brushes".to_string()));
+        return;
+    };
+
+    for entity in &vmf.entities {
+        let Some(origin) = entity.origin else { continue };
+        let outside = (0..3).any(|axis| origin[axis] < min[axis] - BOUNDS_TOLERANCE || origin[axis] > max[axis] + BOUNDS_TOLERANCE);
+        if outside {
+            diagnostics.push(Diagnostic {
+                entity_id: Some(entity.id),
+                ..diagnostic(Severity::Warning, format!(
+                    "{} (id {}) at ({}) is outside the world brushes",
+                    entity.classname, entity.id, format_vec3(origin)
+                ))
+            });
+        }
+    }
+}
+
+fn check_solids(vmf: &Vmf, diagnostics: &mut Vec<Diagnostic>) {
+    for solid in vmf.all_solids() {
+        let error = |message: String, side_id: Option<u32>| Diagnostic {
+            brush_id: Some(solid.id),
+            side_id,
+            ..diagnostic(Severity::Error, message)
+        };
+
+        if solid.sides.len() < 4 {
+            diagnostics.push(error(format!("Brush {} has only {} sides", solid.id, solid.sides.len()), None));
+            continue;
+        }
+        let planes = solid_planes(solid);
+        for (side, plane) in solid.sides.iter().zip(&planes) {
+            if plane.is_none() {
+                diagnostics.push(error(format!("Side {} of brush {} has a degenerate plane", side.id, solid.id), Some(side.id)));
+            }
+        }
+        for (idx, (side, plane)) in solid.sides.iter().zip(&planes).enumerate() {
+            let Some(plane) = plane else { continue };
+            let duplicate = solid.sides.iter().zip(&planes).skip(idx + 1)
+                .find(|(_, other)| other.is_some_and(|other| same_plane(*plane, other)));
+            if let Some((other, _)) = duplicate {
+                diagnostics.push(error(
+                    format!("Sides {} and {} of brush {} lie on the same plane", side.id, other.id, solid.id),
+                    Some(other.id),
+                ));
+            }
+        }
+    }
+}
+
+/// Unit normal and distance of each side, `None` for sides whose points are aligned.
+fn solid_planes(solid: &Solid) -> Vec<Option<(Vec3, f64)>> {
+    solid.sides.iter()
+        .map(|side| {
+            let [a, b, c] = side.plane;
+            let (u, v) = (sub(b, a), sub(c, a));
+            let normal = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+            let length = dot(normal, normal).sqrt();
+            (length > 1e-6).then(|| {
+                let normal = normal.map(|n| n / length);
+                (normal, dot(normal, a))
+            })
+        })
+        .collect()
+}
+
+fn same_plane((normal, dist): (Vec3, f64), (other_normal, other_dist): (Vec3, f64)) -> bool {
+    dot(normal, other_normal) > 1.0 - 1e-6 && (dist - other_dist).abs() < 0.01
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: Vec3, b: Vec3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn format_vec3(point: Vec3) -> String {
+    format!("{} {} {}", point[0], point[1], point[2])
+}
+
+fn check_limits(vmf: &Vmf, diagnostics: &mut Vec<Diagnostic>) {
+    let stats = vmf.stats();
+    for (limit, count, max) in [
+        ("MAX_MAP_BRUSHES", stats.solids, MAX_MAP_BRUSHES),
+        ("MAX_MAP_BRUSHSIDES", stats.sides, MAX_MAP_BRUSHSIDES),
+    ] {
+        let severity = if count > max {
+            Severity::Error
+        } else if count as f64 > max as f64 * LIMIT_WARNING_RATIO {
+            Severity::Warning
+        } else {
+            continue;
+        };
+        diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::MapLimit(limit.to_string()),
+            ..diagnostic(severity, format!("{count} of {max} allowed by {limit}"))
+        });
+    }
+}
+
+/// Sky faces are lit by `light_environment`, VRAD leaves them dark without one.
+fn check_sky_light(vmf: &Vmf, preset: &Preset, diagnostics: &mut Vec<Diagnostic>) {
+    let runs_vrad = preset.apps.iter().any(|step| step.activated && step.compiler_id == "vrad");
+    let has_sky = vmf.all_solids()
+        .flat_map(|solid| &solid.sides)
+        .any(|side| side.material.to_ascii_uppercase().starts_with("TOOLS/TOOLSSKYBOX"));
+    if runs_vrad && has_sky && vmf.entities_by_class("light_environment").next().is_none() {
+        diagnostics.push(diagnostic(Severity::Warning, "The map has sky brushes but no light_environment, VRAD will not light it from the sky".to_string()));
+    }
+}
+
+/// Entities sharing a name are usually meant to be driven together: names shared by different classes are warned about,
+/// names shared within a class are only noted in case the duplicate is a copy-paste leftover.
+fn check_targetnames(vmf: &Vmf, diagnostics: &mut Vec<Diagnostic>) {
+    let mut by_name: BTreeMap<String, Vec<&vmf_parser::Entity>> = BTreeMap::new();
+    for entity in &vmf.entities {
+        if let Some(name) = entity.targetname().filter(|name| !name.is_empty()) {
+            by_name.entry(name.to_ascii_lowercase()).or_default().push(entity);
+        }
+    }
+
+    for (name, entities) in by_name {
+        let mut classes: Vec<String> = entities.iter().map(|entity| entity.classname.to_ascii_lowercase()).collect();
+        classes.sort();
+        classes.dedup();
+        if classes.len() > 1 {
+            diagnostics.push(Diagnostic {
+                entity_id: Some(entities[0].id),
+                ..diagnostic(Severity::Warning, format!(
+                    "Targetname '{name}' is shared by {} entities of different classes ({})",
+                    entities.len(),
+                    classes.join(", ")
+                ))
+            });
+        } else if entities.len() > 1 {
+            diagnostics.push(Diagnostic {
+                entity_id: Some(entities[0].id),
+                ..diagnostic(Severity::Info, format!(
+                    "Targetname '{name}' is shared by {} {} entities",
+                    entities.len(),
+                    classes[0]
+                ))
+            });
+        }
+    }
+}
+
+fn check_entity_classes(vmf: &Vmf, classes: &EntityClasses, diagnostics: &mut Vec<Diagnostic>) {
+    let mut unknown: BTreeMap<String, (u32, usize)> = BTreeMap::new(); // (first entity id, count)
+    for entity in &vmf.entities {
+        match classes.get(&entity.classname) {
+            Some(ClassKind::Solid) if !entity.is_brush_entity() => {
+                diagnostics.push(Diagnostic {
+                    entity_id: Some(entity.id),
+                    ..diagnostic(Severity::Error, format!("{} (id {}) is a brush entity without brushes", entity.classname, entity.id))
+                });
+            }
+            Some(ClassKind::Point | ClassKind::Solid) => {}
+            Some(ClassKind::Base) | None => {
+                unknown.entry(entity.classname.to_ascii_lowercase()).or_insert((entity.id, 0)).1 += 1;
+            }
+        }
+    }
+
+    for (classname, (entity_id, count)) in unknown {
+        diagnostics.push(Diagnostic {
+            entity_id: Some(entity_id),
+            ..diagnostic(Severity::Warning, format!("Entity class '{classname}' is not declared by the game FGD ({count} entities)"))
+        });
+    }
+}
+
+/// Loads the FGD of the game, or every FGD of its bin folder when none is set.
+fn load_entity_classes(game: &GameConfiguration, diagnostics: &mut Vec<Diagnostic>) -> Option<EntityClasses> {
+    let paths = if game.fgd.is_empty() {
+        let mut paths: Vec<_> = fs::read_dir(&game.bin_dir).ok()?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("fgd")))
+            .collect();
+        paths.sort();
+        paths
+    } else {
+        vec![Path::new(&game.fgd).to_path_buf()]
+    };
+
+    let mut classes = EntityClasses::default();
+    for path in paths {
+        match EntityClasses::load(&path) {
+            Ok(loaded) => classes.merge(loaded),
+            Err(e) => diagnostics.push(diagnostic(Severity::Warning, format!("The FGD {} cannot be read: {e}", path.display()))),
+        }
+    }
+    (!classes.is_empty()).then_some(classes)
+}
@@ -0,0 +1,232 @@
+//! Translation of host paths into the Windows paths seen by compilers running under Wine.
+
+use std::{
+    env, fs,
+    path::{Component, Path, PathBuf},
+    process::Command,
+};
+
+use vmflow_config_types::WineConfiguration;
+
+use crate::types::BackendError;
+
+/// Translates host paths to Windows paths using the drive mapping of a Wine prefix.
+///
+/// The mapping is read from the `dosdevices` symlinks of the prefix (`c:` -> `../drive_c`,
+/// `z:` -> `/`, ...). Paths outside of every mapped drive are handed to `winepath` as a last resort.
+pub struct WinePathTranslator {
+    prefix: PathBuf,
+    binary: String,
+    env_vars: Vec<(String, String)>,
+    /// (host root, drive letter), sorted from the deepest root to the shallowest.
+    drives: Vec<(PathBuf, char)>,
+}
+
+impl WinePathTranslator {
+    /// Reads the drive mapping of the prefix configured in `config`.
+    pub fn new(config: &WineConfiguration) -> Self {
+        let prefix = if config.prefix.is_empty() {
+            default_prefix()
+        } else {
+            PathBuf::from(&config.prefix)
+        };
+        let drives = read_drive_mapping(&prefix);
+
+        Self {
+            prefix,
+            binary: config.binary().to_string(),
+            env_vars: config.env_vars.clone(),
+            drives,
+        }
+    }
+
+    /// Wine binary compilers are launched with.
+    pub fn binary(&self) -> &str {
+        &self.binary
+    }
+
+    /// Environment variables compilers are launched with, `WINEPREFIX` included.
+    pub fn envs(&self) -> Vec<(String, String)> {
+        let mut envs = vec![("WINEPREFIX".to_string(), self.prefix.to_string_lossy().into_owned())];
+        envs.extend(self.env_vars.iter().cloned());
+        envs
+    }
+
+    /// Expresses a host path as a Windows path inside the prefix.
+    pub fn translate(&self, host_path: &Path) -> Result<String, BackendError> {
+        let host_path = normalize_host_path(host_path);
+
+        let mapped = self.drives.iter().find_map(|(root, drive)| {
+            let rest = host_path.strip_prefix(root).ok()?;
+            let rest: Vec<_> = rest.components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            Some(format!("{}:\\{}", drive.to_ascii_uppercase(), rest.join("\\")))
+        });
+
+        match mapped {
+            Some(path) => Ok(path),
+            None => self.winepath(&host_path),
+        }
+    }
+
+    /// Asks `winepath` how the prefix sees a host path.
+    fn winepath(&self, host_path: &Path) -> Result<String, BackendError> {
+        let output = Command::new(&self.binary)
+            .arg("winepath")
+            .arg("-w")
+            .arg(host_path)
+            .envs(self.envs())
+            .output()
+            .map_err(|_| BackendError::WinePathUnmapped(host_path.to_path_buf()))?;
+
+        let translated = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        // winepath answers with a `\\?\unix\...` path when no drive covers the host path
+        if !output.status.success() || translated.is_empty() || translated.starts_with("\\\\?\\") {
+            return Err(BackendError::WinePathUnmapped(host_path.to_path_buf()));
+        }
+        Ok(translated)
+    }
+}
+
+/// Returns `$WINEPREFIX`, or `~/.wine` when it is not set.
+fn default_prefix() -> PathBuf {
+    if let Some(prefix) = env::var_os("WINEPREFIX").filter(|p| !p.is_empty()) {
+        return PathBuf::from(prefix);
+    }
+    env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(".wine")
+}
+
+/// Reads the `dosdevices` drive symlinks of a prefix.
+fn read_drive_mapping(prefix: &Path) -> Vec<(PathBuf, char)> {
+    let dosdevices = prefix.join("dosdevices");
+    let Ok(entries) = fs::read_dir(&dosdevices) else {
+        return Vec::new();
+    };
+
+    let mut drives: Vec<(PathBuf, char)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            // Drive links are named `c:`, `z:`, ...; `c::` entries are raw devices.
+            let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+            let mut chars = name.chars();
+            let (Some(drive), Some(':'), None) = (chars.next(), chars.next(), chars.next()) else {
+                return None;
+            };
+            if !drive.is_ascii_alphabetic() {
+                return None;
+            }
+
+            let target = fs::read_link(entry.path()).ok()?;
+            let target = dosdevices.join(target);
+            let root = fs::canonicalize(&target).unwrap_or(target);
+            Some((root, drive))
+        })
+        .collect();
+
+    drives.sort_by_key(|(root, _)| std::cmp::Reverse(root.components().count()));
+    drives
+}
+
+/// Makes a path absolute and resolves symlinks of the part of it that already exists.
+fn normalize_host_path(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir().unwrap_or_default().join(path)
+    };
+
+    // Outputs like `$mapCopyLocation` may not exist yet; canonicalize the closest existing ancestor.
+    let mut existing = absolute.as_path();
+    let mut missing = Vec::new();
+    loop {
+        if let Ok(canonical) = fs::canonicalize(existing) {
+            return missing.iter().rev().fold(canonical, |acc, part| acc.join(part));
+        }
+        match (existing.parent(), existing.components().next_back()) {
+            (Some(parent), Some(Component::Normal(part))) => {
+                missing.push(part.to_os_string());
+                existing = parent;
+            }
+            _ => return absolute,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::symlink;
+
+    use super::*;
+
+    /// Prefix with `c:` on its `drive_c`, `d:` on a folder next to it and the raw `c::` device.
+    fn prefix(name: &str, with_z: bool) -> (PathBuf, WineConfiguration) {
+        let dir = env::temp_dir().join(format!("vmflow_wine_test_{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("prefix/dosdevices")).unwrap();
+        fs::create_dir_all(dir.join("prefix/drive_c/games")).unwrap();
+        fs::create_dir_all(dir.join("data/maps")).unwrap();
+        let dir = fs::canonicalize(&dir).unwrap();
+
+        let dosdevices = dir.join("prefix/dosdevices");
+        symlink("../drive_c", dosdevices.join("c:")).unwrap();
+        symlink(dir.join("data"), dosdevices.join("D:")).unwrap();
+        symlink("/dev/sda1", dosdevices.join("c::")).unwrap();
+        if with_z {
+            symlink("/", dosdevices.join("z:")).unwrap();
+        }
+
+        let config = WineConfiguration {
+            prefix: dir.join("prefix").to_string_lossy().into_owned(),
+            binary: dir.join("no_wine").to_string_lossy().into_owned(),
+            env_vars: vec![("WINEDEBUG".to_string(), "-all".to_string())],
+        };
+        (dir, config)
+    }
+
+    #[test]
+    fn maps_paths_to_the_deepest_drive() {
+        let (dir, config) = prefix("mapping", true);
+        let translator = WinePathTranslator::new(&config);
+
+        let drives: Vec<char> = translator.drives.iter().map(|(_, drive)| *drive).collect();
+        assert_eq!(drives.len(), 3);
+        assert_eq!(drives[2], 'z');
+
+        let translate = |path: PathBuf| translator.translate(&path).unwrap();
+        assert_eq!(translate(dir.join("prefix/drive_c/games")), "C:\\games");
+        assert_eq!(translate(dir.join("data/maps/test.vmf")), "D:\\maps\\test.vmf");
+        assert_eq!(translate(dir.join("data/maps/../maps/new/test.bsp")), "D:\\maps\\new\\test.bsp");
+        assert_eq!(translate(dir.join("data")), "D:\\");
+
+        let outside = format!("Z:{}", dir.join("other/file.txt").to_string_lossy().replace('/', "\\"));
+        assert_eq!(translate(dir.join("other/file.txt")), outside);
+    }
+
+    #[test]
+    fn unmapped_paths_need_winepath() {
+        let (dir, config) = prefix("unmapped", false);
+        let translator = WinePathTranslator::new(&config);
+
+        assert_eq!(translator.translate(&dir.join("prefix/drive_c")).unwrap(), "C:\\");
+        assert!(matches!(
+            translator.translate(&dir.join("other")),
+            Err(BackendError::WinePathUnmapped(path)) if path == dir.join("other")
+        ));
+    }
+
+    #[test]
+    fn passes_prefix_and_variables_to_wine() {
+        let (dir, config) = prefix("envs", false);
+        let translator = WinePathTranslator::new(&config);
+
+        assert_eq!(translator.binary(), config.binary);
+        assert_eq!(translator.envs(), [
+            ("WINEPREFIX".to_string(), dir.join("prefix").to_string_lossy().into_owned()),
+            ("WINEDEBUG".to_string(), "-all".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,70 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+
+/// Progress of a running step, as reported in [`crate::CoreEvent::StepProgress`].
+///
+/// All fractions are in `0.0..=1.0`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    /// Phase of the step the progress comes from (`BuildFacelights`), empty when the step finished.
+    pub phase: String,
+    pub phase_fraction: f32,
+    pub step_fraction: f32,
+    pub map_fraction: f32,
+    pub batch_fraction: f32,
+}
+
+/// Tracks how far every map of a batch is, to turn step progress into map and batch fractions.
+pub struct BatchProgress {
+    steps_per_map: usize,
+    map_count: usize,
+    /// (map id -> map fraction), `None` for maps left out of the batch
+    maps: Mutex<HashMap<usize, Option<f32>>>,
+}
+
+impl BatchProgress {
+    /// `steps_per_map` counts the activated steps only, the deactivated ones take no time.
+    pub fn new(steps_per_map: usize, map_count: usize) -> Self {
+        Self {
+            steps_per_map: steps_per_map.max(1),
+            map_count: map_count.max(1),
+            maps: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records the progress of the activated step `step_idx` of a map.
+    pub fn update(&self, map_id: usize, step_idx: usize, phase: String, phase_fraction: f32, step_fraction: f32) -> Progress {
+        let step_fraction = step_fraction.clamp(0.0, 1.0);
+        let map_fraction = ((step_idx as f32 + step_fraction) / self.steps_per_map as f32).min(1.0);
+        let batch_fraction = self.set_map_fraction(map_id, Some(map_fraction));
+
+        Progress {
+            phase,
+            phase_fraction,
+            step_fraction,
+            map_fraction,
+            batch_fraction,
+        }
+    }
+
+    /// Marks a map as done, whatever its result, returns the batch fraction.
+    pub fn finish_map(&self, map_id: usize) -> f32 {
+        self.set_map_fraction(map_id, Some(1.0))
+    }
+
+    /// Leaves a map with nothing to compile out of the batch, so it does not count as work done.
+    pub fn skip_map(&self, map_id: usize) -> f32 {
+        self.set_map_fraction(map_id, None)
+    }
+
+    fn set_map_fraction(&self, map_id: usize, fraction: Option<f32>) -> f32 {
+        let mut maps = self.maps.lock().unwrap_or_else(|e| e.into_inner());
+        maps.insert(map_id, fraction);
+        let skipped = maps.values().filter(|fraction| fraction.is_none()).count();
+        match self.map_count.saturating_sub(skipped) {
+            0 => 1.0,
+            map_count => maps.values().flatten().sum::<f32>() / map_count as f32,
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::{path::PathBuf, time::Duration};
+
+use bsp_parser::BspSummary;
+use compiler_logs_process::Diagnostic;
+use serde::{Deserialize, Serialize};
+
+use crate::types::BackendError;
+
+/// How a step of a map ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StepOutcome {
+    Succeeded,
+    Failed,
+    /// Failed, but its policy let the map go on.
+    FailureIgnored,
+    /// Not run because of its policy or because it is deactivated.
+    Skipped,
+}
+
+/// Result of one step of a map.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepReport {
+    pub name: String,
+    pub duration: Duration,
+    pub outcome: StepOutcome,
+    pub warnings: usize,
+    pub errors: usize,
+    /// Exit code of the compiler, `None` for built-in steps and compilers that did not exit.
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+}
+
+impl StepReport {
+    pub fn new(name: String, duration: Duration, outcome: StepOutcome, diagnostics: &[Diagnostic]) -> Self {
+        Self {
+            name,
+            duration,
+            outcome,
+            warnings: diagnostics.iter().filter(|d| d.is_warning()).count(),
+            errors: diagnostics.iter().filter(|d| d.is_error()).count(),
+            exit_code: None,
+        }
+    }
+
+    pub fn with_exit_code(mut self, exit_code: Option<i32>) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    pub fn skipped(name: String) -> Self {
+        Self::new(name, Duration::ZERO, StepOutcome::Skipped, &[])
+    }
+}
+
+/// How the compilation of a map ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MapStatus {
+    Succeeded,
+    Failed {
+        /// Step the map failed at.
+        step: String,
+        /// Exit code of the compiler, if it ran and exited with one.
+        exit_code: Option<i32>,
+        error: String,
+    },
+    Cancelled,
+    /// The map is deactivated in the map list.
+    Skipped,
+    /// Not compiled, its inputs did not change since its last successful compile and its .bsp exists.
+    UpToDate,
+}
+
+/// Result of one map of a batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapReport {
+    pub map_id: usize,
+    pub map_name: String,
+    pub path: PathBuf,
+    pub status: MapStatus,
+    pub steps: Vec<StepReport>,
+    pub duration: Duration,
+    /// What the compiled .bsp holds, when the map succeeded and the .bsp could be read.
+    #[serde(default)]
+    pub bsp: Option<Box<BspSummary>>,
+    /// Full log of the map, when the session writes logs, see [`crate::logs`].
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+}
+
+impl MapReport {
+    /// Builds the report of a map that finished running its steps with `result`.
+    pub fn finished(map_info: &vmflow_config_types::VmfMap, steps: Vec<StepReport>, duration: Duration, result: Result<(), BackendError>) -> Self {
+        let status = match result {
+            Ok(()) => MapStatus::Succeeded,
+            Err(BackendError::Cancelled) => MapStatus::Cancelled,
+            Err(error) => MapStatus::Failed {
+                step: steps.iter()
+                    .find(|step| step.outcome == StepOutcome::Failed)
+                    .map(|step| step.name.clone())
+                    .unwrap_or_default(),
+                exit_code: error.exit_code(),
+                error: error.to_string(),
+            },
+        };
+
+        Self {
+            map_id: map_info.order_idx,
+            map_name: map_info.name.clone(),
+            path: map_info.path.clone(),
+            status,
+            steps,
+            duration,
+            bsp: None,
+            log_file: None,
+        }
+    }
+
+    /// Builds the report of a map that never started.
+    pub fn not_started(map_info: &vmflow_config_types::VmfMap, status: MapStatus) -> Self {
+        Self {
+            map_id: map_info.order_idx,
+            map_name: map_info.name.clone(),
+            path: map_info.path.clone(),
+            status,
+            steps: Vec::new(),
+            duration: Duration::ZERO,
+            bsp: None,
+            log_file: None,
+        }
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.steps.iter().map(|step| step.warnings).sum()
+    }
+
+    pub fn errors(&self) -> usize {
+        self.steps.iter().map(|step| step.errors).sum()
+    }
+}
+
+/// Overall result of a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BatchOutcome {
+    /// Every started map compiled.
+    Succeeded,
+    /// Some maps compiled, some failed.
+    PartiallyFailed,
+    /// No map compiled.
+    Failed,
+    Cancelled,
+}
+
+/// Result of a whole batch, returned by [`crate::CompilationSession::start_batch`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub outcome: BatchOutcome,
+    pub maps: Vec<MapReport>,
+    pub duration: Duration,
+}
+
+impl BatchReport {
+    pub fn new(maps: Vec<MapReport>, duration: Duration, cancelled: bool) -> Self {
+        let count = |status: fn(&MapStatus) -> bool| maps.iter().filter(|map| status(&map.status)).count();
+        // An up to date map is one that compiled before
+        let succeeded = count(|status| matches!(status, MapStatus::Succeeded | MapStatus::UpToDate));
+        let failed = count(|status| matches!(status, MapStatus::Failed { .. }));
+
+        let outcome = if cancelled {
+            BatchOutcome::Cancelled
+        } else if failed == 0 {
+            BatchOutcome::Succeeded
+        } else if succeeded == 0 {
+            BatchOutcome::Failed
+        } else {
+            BatchOutcome::PartiallyFailed
+        };
+
+        Self { outcome, maps, duration }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.outcome == BatchOutcome::Succeeded
+    }
+
+    /// Maps that failed, in batch order.
+    pub fn failed_maps(&self) -> impl Iterator<Item = &MapReport> {
+        self.maps.iter().filter(|map| matches!(map.status, MapStatus::Failed { .. }))
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.maps.iter().map(MapReport::warnings).sum()
+    }
+
+    pub fn errors(&self) -> usize {
+        self.maps.iter().map(MapReport::errors).sum()
+    }
+}
@@ -1,4 +1,14 @@
-use std::{io, sync::Arc};
+use std::{fmt, io, path::PathBuf, sync::Arc};
+
+use bsp_parser::BspSummary;
+
+use crate::budget::LimitAlert;
+use compiler_logs_process::{Diagnostic, LeakInfo};
+use serde::{Deserialize, Serialize};
+
+use vmflow_config_types::validation::ValidationIssue;
+
+use crate::{BatchReport, MapReport, Progress};
 
 /// Represents errors that can occur during backend processing.
 #[derive(Debug)]
@@ -10,28 +20,73 @@ pub enum BackendError {
     Cancelled,
     StepFailed(String, std::process::ExitStatus), // (name, status)
     // InvalidConfiguration,
-    BuiltinFailed(String), // (name)
+    BuiltinFailed(String), // (message)
+    InvalidPreset(usize), // (error count)
+    PreflightFailed(usize), // (error count)
+    UnresolvedPlaceholders(String, Vec<String>), // (name, variables)
+    WinePathUnmapped(PathBuf), // (host path)
+    MapLeaked(Option<PathBuf>), // (pointfile)
     Unknown,
 }
 
-type ProcessResult = Result<(), BackendError>;
+impl BackendError {
+    /// Exit code of the compiler if the error comes from a compiler exiting unsuccessfully.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            Self::StepFailed(_, status) => status.code(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IoError(e) => write!(f, "I/O error: {e}"),
+            Self::ProcessSpawnError(e) => write!(f, "Failed to start the process: {e}"),
+            Self::ProcessWaitError(e) => write!(f, "Failed to wait for the process: {e}"),
+            Self::CommandNotFound(command) => write!(f, "Command not found: {command}"),
+            Self::Cancelled => write!(f, "Cancelled"),
+            Self::StepFailed(name, status) => write!(f, "{name} failed with {status}"),
+            Self::BuiltinFailed(message) => write!(f, "{message}"),
+            Self::InvalidPreset(count) => write!(f, "The preset has {count} error(s)"),
+            Self::PreflightFailed(count) => write!(f, "Pre-flight checks found {count} error(s) in the map"),
+            Self::UnresolvedPlaceholders(name, variables) => {
+                write!(f, "{name}: unresolved placeholders {}", variables.join(", "))
+            }
+            Self::WinePathUnmapped(path) => write!(f, "{} is not visible inside the Wine prefix", path.display()),
+            Self::MapLeaked(Some(pointfile)) => write!(f, "Map leaked, see {}", pointfile.display()),
+            Self::MapLeaked(None) => write!(f, "Map leaked"),
+            Self::Unknown => write!(f, "Unknown error"),
+        }
+    }
+}
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 /// Represents events that occur during core processing.
 pub enum CoreEvent {
     // BIG TODO!!!
     BatchStarted,
+    ValidationIssues(Vec<ValidationIssue>),     // (issues of the preset), a batch with errors stops after it
 
     MapStarted(usize, String),                  // (thread_id, map_name)
     StepStarted(usize, String, String),         // (thread_id, map_name, process_name)
     StepLog(usize, String, String, String),     // (thread_id, map_name, process_name)
     StepWarn(usize, String, String, String),    // (thread_id, map_name, process_name)
     StepErr(usize, String, String, String),     // (thread_id, map_name, process_name)
+    StepDiagnostic(usize, String, String, Diagnostic), // (thread_id, map_name, process_name, diagnostic)
+    StepProgress(usize, String, String, Progress), // (thread_id, map_name, process_name, progress)
+    StepStatistic(usize, String, String, String, u64), // (thread_id, map_name, process_name, name, value)
+    LeakDetected(usize, String, LeakInfo),      // (thread_id, map_name, leak)
+    BspSummary(usize, String, Box<BspSummary>), // (thread_id, map_name, summary), sent before MapFinished when the map succeeded
+    LimitAlert(usize, String, LimitAlert),      // (thread_id, map_name, alert), sent after BspSummary
 
     StepFinished(usize, String, String),        // (thread_id, map_name, process_name)
-    MapFinished(usize, String, ProcessResult),  // (thread_id, map_name, result)
+    StepFailed(usize, String, String, String),  // (thread_id, map_name, process_name, error), also when the step policy ignores the failure
+    StepSkipped(usize, String, String, String), // (thread_id, map_name, process_name, reason)
+    MapFinished(usize, String, MapReport),      // (thread_id, map_name, report)
 
-    BatchCompleted(ProcessResult),              // (result)
+    BatchCompleted(BatchReport),                // (report), sent last, after BatchCancelled too
     BatchCancelled,
     CancellationRequested,
 }
@@ -64,12 +119,12 @@ pub fn send_or_print_event(event_handler: &Option<Arc<dyn JobEventHandler>>, eve
                 "[EVENT] StepFinished(thread_id: {}, map_name: {}, process_name: {})",
                 thread_id, map_name, process_name
             ),
-            CoreEvent::MapFinished(thread_id, map_name, result) => println!(
-                "[EVENT] MapFinished(thread_id: {}, map_name: {}, result: {:?})",
-                thread_id, map_name, result
+            CoreEvent::MapFinished(thread_id, map_name, report) => println!(
+                "[EVENT] MapFinished(thread_id: {}, map_name: {}, status: {:?})",
+                thread_id, map_name, report.status
             ),
-            CoreEvent::BatchCompleted(result) => {
-                println!("[EVENT] BatchCompleted(result: {:?})", result)
+            CoreEvent::BatchCompleted(report) => {
+                println!("[EVENT] BatchCompleted(outcome: {:?})", report.outcome)
             },
             _ => println!("[EVENT] {:?}", event),
         },
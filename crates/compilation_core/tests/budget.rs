@@ -0,0 +1,50 @@
+use std::{fs, path::PathBuf};
+
+use compilation_core::{
+    budget::{record_compile, AlertKind, BudgetHistory},
+    BspSummary, LimitUsage,
+};
+use vmflow_config_types::preset::LimitBudget;
+
+/// Empty folder for one test, removed by the next run of the same test.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vmflow_budget_test_{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn summary(used: u64) -> BspSummary {
+    BspSummary {
+        limits: vec![LimitUsage { name: "MAX_MAP_PLANES".to_string(), used, max: 1000 }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn records_usage_across_compiles() {
+    let dir = test_dir("record");
+    let history = dir.join("budget.json");
+    let map = dir.join("test.vmf");
+
+    assert!(record_compile(Some(&history), &map, &summary(100), &LimitBudget::default()).unwrap().is_empty());
+    let alerts = record_compile(Some(&history), &map, &summary(850), &LimitBudget::default()).unwrap();
+    let kinds: Vec<_> = alerts.iter().map(|alert| &alert.kind).collect();
+    assert_eq!(kinds, [&AlertKind::NearLimit, &AlertKind::Jump(100)]);
+
+    assert_eq!(BudgetHistory::load(&history).unwrap().entries(&map).len(), 2);
+}
+
+#[test]
+fn keeps_an_unreadable_history() {
+    let dir = test_dir("unreadable");
+    let history = dir.join("budget.json");
+    let map = dir.join("test.vmf");
+    assert_eq!(BudgetHistory::load(&history).unwrap().maps.len(), 0);
+
+    // A partial write of the file
+    fs::write(&history, "{\"maps\": {\"/maps/other.vmf\": [").unwrap();
+    assert!(BudgetHistory::load(&history).is_err());
+    assert!(record_compile(Some(&history), &map, &summary(100), &LimitBudget::default()).is_err());
+    assert_eq!(fs::read_to_string(&history).unwrap(), "{\"maps\": {\"/maps/other.vmf\": [");
+}
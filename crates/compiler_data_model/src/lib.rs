@@ -2,6 +2,9 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct CompilerConfig {
+    /// Stable identity saved in presets and game configs. Defaults to the name, lowercased.
+    #[serde(default)]
+    pub id: String,
     pub name: String,
     pub is_builtin: bool,
     pub description: String,
@@ -11,17 +14,133 @@ pub struct CompilerConfig {
     pub custom_path: Option<String>,
 }
 
+impl CompilerConfig {
+    /// Derives the ids the config file left out.
+    pub fn fill_missing_ids(&mut self) {
+        if self.id.is_empty() {
+            self.id = slug(&self.name);
+        }
+        for parameter in &mut self.parameters {
+            if parameter.id.is_empty() {
+                let argument = slug(&parameter.argument);
+                parameter.id = if argument.is_empty() { slug(&parameter.name) } else { argument };
+            }
+        }
+    }
+
+    pub fn find_parameter(&self, id: &str) -> Option<&Parameter> {
+        self.parameters.iter().find(|parameter| parameter.id == id)
+    }
+
+    /// Checks what the TOML schema cannot: relations reference other parameters of this compiler,
+    /// choices and booleans have legal defaults.
+    pub fn check_parameters(&self) -> Result<(), String> {
+        for parameter in &self.parameters {
+            match parameter.value_type {
+                ParameterType::Choice if parameter.choices.is_empty() => {
+                    return Err(format!("Choice parameter '{}' has no `choices`", parameter.name));
+                }
+                ParameterType::Choice => {
+                    if let Some(default) = &parameter.default_value
+                        && parameter.find_choice(default).is_none()
+                    {
+                        return Err(format!("Default value '{default}' of '{}' is not one of its choices", parameter.name));
+                    }
+                }
+                ParameterType::Bool => {
+                    if let Some(default) = &parameter.default_value
+                        && parse_bool(default).is_none()
+                    {
+                        return Err(format!("Default value '{default}' of '{}' is not true or false", parameter.name));
+                    }
+                }
+                _ if !parameter.choices.is_empty() => {
+                    return Err(format!("Parameter '{}' has `choices` but is not of type choice", parameter.name));
+                }
+                _ => {}
+            }
+
+            for (relation, ids) in [("requires", &parameter.requires), ("conflicts_with", &parameter.conflicts_with)] {
+                for id in ids {
+                    if *id == parameter.id {
+                        return Err(format!("Parameter '{}' {relation} itself", parameter.name));
+                    }
+                    if self.find_parameter(id).is_none() {
+                        return Err(format!("Parameter '{}' {relation} unknown parameter '{id}'", parameter.name));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lowercase id made of the alphanumeric runs of `text`: `-StaticPropLighting` -> `staticproplighting`.
+fn slug(text: &str) -> String {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Parameter {
+    /// Stable identity within its compiler. Defaults to the argument (or the name), lowercased.
+    #[serde(default)]
+    pub id: String,
     pub name: String,
     pub argument: String,
     pub value_type: ParameterType,
     pub default_value: Option<String>,
     pub description: String,
+    /// Ids of parameters that must be set along with this one.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Ids of parameters that cannot be set along with this one. Declaring it on one side is enough.
+    #[serde(default)]
+    pub conflicts_with: Vec<String>,
+    /// Legal values of a `choice` parameter.
+    #[serde(default)]
+    pub choices: Vec<ParameterChoice>,
     pub constraints: Option<ParameterConstraints>
 }
 
+/// A legal value of a `choice` parameter.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ParameterChoice {
+    pub value: String,
+    /// Shown instead of the value when set.
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl ParameterChoice {
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.value)
+    }
+}
+
+/// Parses the value of a `bool` parameter: `true`/`false`, `1`/`0`, `yes`/`no`.
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" => Some(true),
+        "false" | "0" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+impl Parameter {
+    pub fn find_choice(&self, value: &str) -> Option<&ParameterChoice> {
+        self.choices.iter().find(|choice| choice.value == value)
+    }
+
+    /// Whether both parameters cannot be set together, whichever side declares it.
+    pub fn conflicts(&self, other: &Parameter) -> bool {
+        self.conflicts_with.contains(&other.id) || other.conflicts_with.contains(&self.id)
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ParameterType {
@@ -31,7 +150,10 @@ pub enum ParameterType {
     Float,
     Path,
     String,
-    // Bool, // Not used?
+    /// On or off, passed as `1` or `0` after the argument (`+sv_cheats 1`).
+    Bool,
+    /// One of the values listed in `choices`.
+    Choice,
 }
 
 
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// What a diagnostic is about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiagnosticKind {
+    /// The map is not sealed from the void (`**** leaked ****`).
+    Leak,
+    /// A material or texture referenced by the map could not be found.
+    TextureNotFound(String),
+    /// VRAD ran out of lightmap space.
+    TooManyLightmaps,
+    /// A `MAX_MAP_*` engine limit was hit.
+    MapLimit(String),
+    /// A mistake found in the map source before compiling it.
+    Preflight,
+    /// Any other warning or error line.
+    Other,
+}
+
+/// A problem reported by a compiler, extracted from its output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    /// The output line the diagnostic was created from, trimmed, or the text of a pre-flight check.
+    pub message: String,
+    /// Name of the step that printed the line (VBSP, VRAD, ...).
+    pub step: String,
+    pub brush_id: Option<u32>,
+    pub entity_id: Option<u32>,
+    pub side_id: Option<u32>,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.severity == Severity::Warning
+    }
+}
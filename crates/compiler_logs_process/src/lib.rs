@@ -1 +1,13 @@
-fn x() {}
\ No newline at end of file
+//! Interpretation of compiler output (VBSP, VVIS, VRAD, BSPZIP) into structured diagnostics.
+
+mod diagnostic;
+pub use diagnostic::{Diagnostic, DiagnosticKind, Severity};
+
+mod parser;
+pub use parser::{LogParser, Tool};
+
+mod pointfile;
+pub use pointfile::{format_point, LeakInfo, LeakedEntity, Point, Pointfile, PointfileError};
+
+mod progress;
+pub use progress::{ProgressParser, ProgressUpdate};
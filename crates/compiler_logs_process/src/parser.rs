@@ -0,0 +1,144 @@
+use crate::{Diagnostic, DiagnosticKind, Severity};
+
+/// Compilers with known output patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Vbsp,
+    Vvis,
+    Vrad,
+    Bspzip,
+    /// Any other step, only generic `Error`/`Warning` lines are recognized.
+    Other,
+}
+
+impl Tool {
+    /// Guesses the tool from the name of a compile step.
+    pub fn from_step_name(step_name: &str) -> Self {
+        match step_name.to_ascii_uppercase().as_str() {
+            "VBSP" => Self::Vbsp,
+            "VVIS" => Self::Vvis,
+            "VRAD" => Self::Vrad,
+            "BSPZIP" => Self::Bspzip,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Turns the output of one compile step into diagnostics, line by line.
+pub struct LogParser {
+    step: String,
+    tool: Tool,
+}
+
+impl LogParser {
+    pub fn new(step_name: &str) -> Self {
+        Self {
+            step: step_name.to_string(),
+            tool: Tool::from_step_name(step_name),
+        }
+    }
+
+    pub fn tool(&self) -> Tool {
+        self.tool
+    }
+
+    /// Parses one output line, returns a diagnostic if the line reports a problem.
+    pub fn parse_line(&mut self, line: &str) -> Option<Diagnostic> {
+        let message = line.trim();
+        if message.is_empty() {
+            return None;
+        }
+        let lower = message.to_ascii_lowercase();
+
+        let (severity, kind) = self.classify(message, &lower)?;
+        Some(Diagnostic {
+            severity,
+            kind,
+            message: message.to_string(),
+            step: self.step.clone(),
+            brush_id: id_after(&lower, "brush"),
+            entity_id: id_after(&lower, "entity"),
+            side_id: id_after(&lower, "side"),
+        })
+    }
+
+    fn classify(&self, message: &str, lower: &str) -> Option<(Severity, DiagnosticKind)> {
+        if self.tool == Tool::Vbsp && (lower.contains("**** leaked ****") || lower.ends_with("leaked!")) {
+            return Some((Severity::Error, DiagnosticKind::Leak));
+        }
+
+        if self.tool == Tool::Vrad && lower.contains("too many lightmaps") {
+            return Some((Severity::Error, DiagnosticKind::TooManyLightmaps));
+        }
+
+        if let Some(pos) = message.find("MAX_MAP_") {
+            let limit: String = message[pos..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect();
+            return Some((Severity::Error, DiagnosticKind::MapLimit(limit)));
+        }
+
+        if let Some(texture) = missing_texture(message, lower) {
+            return Some((Severity::Warning, DiagnosticKind::TextureNotFound(texture)));
+        }
+
+        if lower.starts_with("error") || lower.contains("error!") || lower.contains("error:") || lower.starts_with("fatal") {
+            return Some((Severity::Error, DiagnosticKind::Other));
+        }
+
+        if lower.contains("warning") || (self.tool == Tool::Vbsp && is_vbsp_brush_problem(lower)) {
+            return Some((Severity::Warning, DiagnosticKind::Other));
+        }
+
+        None
+    }
+}
+
+/// Extracts the material name from "material not found" style lines.
+fn missing_texture(message: &str, lower: &str) -> Option<String> {
+    const PATTERNS: [&str; 5] = [
+        "material not found!:",
+        "texture not found:",
+        "couldn't find material",
+        "can't find material",
+        "can't find texture",
+    ];
+
+    let (pattern, pos) = PATTERNS
+        .iter()
+        .find_map(|pattern| lower.find(pattern).map(|pos| (pattern, pos)))?;
+
+    let texture = message[pos + pattern.len()..]
+        .trim_start_matches([':', ' ', '"', '\''])
+        .split(['"', '\'', ' '])
+        .next()
+        .unwrap_or_default();
+    Some(texture.to_string())
+}
+
+/// VBSP reports some brush problems without a "warning" prefix (`Brush 12: mixed face contents`).
+fn is_vbsp_brush_problem(lower: &str) -> bool {
+    (lower.starts_with("brush ") || lower.starts_with("entity ")) && id_after(lower, "brush").is_some()
+        || lower.contains("microbrush")
+        || lower.contains("duplicate plane")
+        || lower.contains("mixed face contents")
+}
+
+/// Finds the number that follows `keyword` ("Brush 12", "entity #3", "side id: 7").
+fn id_after(lower: &str, keyword: &str) -> Option<u32> {
+    lower.match_indices(keyword).find_map(|(pos, _)| {
+        // Only whole words: "brushes" or "subbrush" must not match "brush".
+        let before = lower[..pos].chars().next_back();
+        if before.is_some_and(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+
+        let rest = lower[pos + keyword.len()..]
+            .trim_start_matches([' ', '#', ':'])
+            .trim_start_matches("id")
+            .trim_start_matches([' ', '#', ':']);
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse().ok()
+    })
+}
@@ -0,0 +1,128 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A point in map space.
+pub type Point = [f32; 3];
+
+/// The leak path VBSP writes into `<map>.lin`, one `x y z` point per line.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pointfile {
+    pub points: Vec<Point>,
+}
+
+/// Error returned when a pointfile line is not three numbers.
+#[derive(Debug)]
+pub enum PointfileError {
+    Io(io::Error),
+    InvalidLine(usize, String), // (line number, line)
+}
+
+impl fmt::Display for PointfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read pointfile: {e}"),
+            Self::InvalidLine(line_no, line) => write!(f, "invalid pointfile line {line_no}: '{line}'"),
+        }
+    }
+}
+
+impl Pointfile {
+    /// Parses the contents of a `.lin` file.
+    pub fn parse(text: &str) -> Result<Self, PointfileError> {
+        let mut points = Vec::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let coords: Vec<f32> = line
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|_| PointfileError::InvalidLine(idx + 1, line.to_string()))?;
+            let [x, y, z] = coords[..] else {
+                return Err(PointfileError::InvalidLine(idx + 1, line.to_string()));
+            };
+            points.push([x, y, z]);
+        }
+        Ok(Self { points })
+    }
+
+    /// Reads and parses a `.lin` file.
+    pub fn load(path: &Path) -> Result<Self, PointfileError> {
+        let text = fs::read_to_string(path).map_err(PointfileError::Io)?;
+        Self::parse(&text)
+    }
+
+    /// First point of the leak path, inside the map.
+    pub fn start(&self) -> Option<Point> {
+        self.points.first().copied()
+    }
+
+    /// Total length of the leak path in units.
+    pub fn length(&self) -> f32 {
+        self.points
+            .windows(2)
+            .map(|pair| {
+                let [a, b] = [pair[0], pair[1]];
+                ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2) + (b[2] - a[2]).powi(2)).sqrt()
+            })
+            .sum()
+    }
+}
+
+/// The entity VBSP reports as reachable from the void.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeakedEntity {
+    pub classname: String,
+    pub origin: Point,
+}
+
+impl LeakedEntity {
+    /// Parses VBSP's `Entity info_player_start (-1024.00 512.00 64.00) leaked!` line.
+    pub fn from_log_line(line: &str) -> Option<Self> {
+        let rest = line.trim().strip_prefix("Entity ")?;
+        let rest = rest.strip_suffix("leaked!")?.trim_end();
+
+        let open = rest.find('(')?;
+        let close = rest.rfind(')')?;
+        let classname = rest[..open].trim().to_string();
+        let coords: Vec<f32> = rest[open + 1..close]
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        let [x, y, z] = coords[..] else { return None };
+
+        Some(Self { classname, origin: [x, y, z] })
+    }
+}
+
+/// Everything known about a leak of a compiled map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeakInfo {
+    /// Path of the `.lin` file, for Hammer's "Load Pointfile".
+    pub pointfile_path: Option<PathBuf>,
+    pub pointfile: Pointfile,
+    pub entity: Option<LeakedEntity>,
+}
+
+impl LeakInfo {
+    pub fn start(&self) -> Option<Point> {
+        self.pointfile.start()
+    }
+
+    pub fn length(&self) -> f32 {
+        self.pointfile.length()
+    }
+}
+
+/// Formats a point the way Hammer accepts it in "Go to Coordinates": `x y z`.
+pub fn format_point(point: &Point) -> String {
+    format!("{} {} {}", point[0], point[1], point[2])
+}
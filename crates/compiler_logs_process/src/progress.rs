@@ -0,0 +1,131 @@
+use crate::Tool;
+
+/// Progress information extracted from compiler output.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressUpdate {
+    /// A `Phase: 0...1...2` sequence advanced.
+    Phase {
+        /// Name printed before the sequence (`BuildFacelights`, `PortalFlow`, ...).
+        phase: String,
+        /// Progress of the phase, `0.0..=1.0`.
+        fraction: f32,
+        /// Estimated progress of the whole step, `0.0..=1.0`.
+        step_fraction: f32,
+    },
+    /// A size reported by the compiler (`1234 portalclusters`).
+    Count(String, u64), // (name, value)
+}
+
+/// Phases that print a progress sequence, in the order the tool runs them.
+fn known_phases(tool: Tool) -> &'static [&'static str] {
+    match tool {
+        Tool::Vvis => &["BasePortalVis", "PortalFlow"],
+        Tool::Vrad => &[
+            "BuildFacelights",
+            "BuildVisLeafs",
+            "ThreadComputeLeafAmbient",
+            "Computing detail prop lighting",
+            "Computing static prop lighting",
+        ],
+        _ => &[],
+    }
+}
+
+/// Counts worth showing while a map compiles, matched as `<number> <name>`.
+const COUNTERS: [&str; 5] = ["portalclusters", "numportals", "visclusters", "portals", "leafs"];
+
+/// Follows the `0...1...2...3...4...5...6...7...8...9...10` sequences VVIS and VRAD print.
+///
+/// The sequences are written without line breaks, so the parser is fed raw output chunks
+/// instead of whole lines.
+pub struct ProgressParser {
+    tool: Tool,
+    /// Output since the last line break.
+    line: String,
+    /// Last number of the sequence on the current line, to report each tick once.
+    last_tick: Option<u32>,
+    step_fraction: f32,
+}
+
+impl ProgressParser {
+    pub fn new(step_name: &str) -> Self {
+        Self {
+            tool: Tool::from_step_name(step_name),
+            line: String::new(),
+            last_tick: None,
+            step_fraction: 0.0,
+        }
+    }
+
+    /// Feeds a chunk of output, returns the progress it reveals.
+    pub fn feed(&mut self, chunk: &str) -> Vec<ProgressUpdate> {
+        let mut updates = Vec::new();
+
+        let mut parts = chunk.split('\n').peekable();
+        while let Some(part) = parts.next() {
+            self.line.push_str(part);
+            updates.extend(self.parse_tick());
+
+            // Every part but the last one was terminated by a line break
+            if parts.peek().is_some() {
+                updates.extend(self.parse_count());
+                self.line.clear();
+                self.last_tick = None;
+            }
+        }
+        updates
+    }
+
+    fn parse_tick(&mut self) -> Option<ProgressUpdate> {
+        let start = sequence_start(&self.line)?;
+        let pieces: Vec<&str> = self.line[start..].split("...").collect();
+        let (last, complete) = pieces.split_last()?;
+        // The trailing number may still be arriving ("1" of "10"), unless the sequence is over
+        let tick = complete
+            .iter()
+            .map_while(|n| leading_number(n))
+            .chain(leading_number(last).filter(|n| *n == 10))
+            .filter(|n| *n <= 10)
+            .last()?;
+        if self.last_tick == Some(tick) {
+            return None;
+        }
+        self.last_tick = Some(tick);
+
+        let phase = self.line[..start].trim().trim_end_matches(':').trim().to_string();
+        let fraction = tick as f32 / 10.0;
+
+        let phases = known_phases(self.tool);
+        if let Some(idx) = phases.iter().position(|p| phase.eq_ignore_ascii_case(p)) {
+            let estimate = (idx as f32 + fraction) / phases.len() as f32;
+            // Optional phases are skipped by some runs, never move backwards
+            self.step_fraction = self.step_fraction.max(estimate);
+        }
+
+        Some(ProgressUpdate::Phase { phase, fraction, step_fraction: self.step_fraction })
+    }
+
+    fn parse_count(&self) -> Option<ProgressUpdate> {
+        let mut words = self.line.split_whitespace();
+        let value = words.next()?.parse::<u64>().ok()?;
+        let name = words.next()?.to_ascii_lowercase();
+        if words.next().is_some() || !COUNTERS.contains(&name.as_str()) {
+            return None;
+        }
+        Some(ProgressUpdate::Count(name, value))
+    }
+}
+
+/// Finds the `0...` that starts a progress sequence.
+fn sequence_start(line: &str) -> Option<usize> {
+    line.match_indices("0...").find_map(|(pos, _)| {
+        let before = line[..pos].chars().next_back();
+        (!before.is_some_and(|c| c.is_ascii_digit() || c == '.')).then_some(pos)
+    })
+}
+
+/// Parses the digits a sequence piece starts with (`10 (12)` -> 10).
+fn leading_number(piece: &str) -> Option<u32> {
+    let digits: String = piece.trim_start().chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
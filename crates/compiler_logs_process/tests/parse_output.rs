@@ -0,0 +1,114 @@
+use compiler_logs_process::{DiagnosticKind, LogParser, ProgressParser, ProgressUpdate, Severity, Tool};
+
+const VBSP_OUTPUT: &str = "\
+Valve Software - vbsp.exe (Jan 14 2024)
+4 threads
+materialPath: c:\\games\\hl2\\materials
+Loading test.vmf
+Material not found!: TOOLS/TOOLSNODRAW2
+Brush 12: mixed face contents
+WARNING: Entity 5 (side id: 17) has a displacement on a brush entity
+Processing areas...done (0)
+Found 3 brushes in the subbrush list
+Entity info_player_start (-1024.00 512.00 64.00) leaked!
+**** leaked ****
+";
+
+#[test]
+fn finds_vbsp_problems() {
+    let mut parser = LogParser::new("vbsp");
+    assert_eq!(parser.tool(), Tool::Vbsp);
+    let diagnostics: Vec<_> = VBSP_OUTPUT.lines().filter_map(|line| parser.parse_line(line)).collect();
+
+    let kinds: Vec<_> = diagnostics.iter().map(|diagnostic| (diagnostic.severity, &diagnostic.kind)).collect();
+    assert_eq!(kinds, [
+        (Severity::Warning, &DiagnosticKind::TextureNotFound("TOOLS/TOOLSNODRAW2".to_string())),
+        (Severity::Warning, &DiagnosticKind::Other),
+        (Severity::Warning, &DiagnosticKind::Other),
+        (Severity::Error, &DiagnosticKind::Leak),
+        (Severity::Error, &DiagnosticKind::Leak),
+    ]);
+    assert!(diagnostics.iter().all(|diagnostic| diagnostic.step == "vbsp"));
+
+    assert_eq!(diagnostics[1].message, "Brush 12: mixed face contents");
+    assert_eq!(diagnostics[1].brush_id, Some(12));
+    assert_eq!((diagnostics[2].entity_id, diagnostics[2].side_id, diagnostics[2].brush_id), (Some(5), Some(17), None));
+    assert_eq!(diagnostics[3].entity_id, None);
+}
+
+#[test]
+fn recognizes_limits_and_generic_errors() {
+    let mut parser = LogParser::new("VRAD");
+    let limit = parser.parse_line("Error: MAX_MAP_PLANES (65536) exceeded").unwrap();
+    assert_eq!(limit.kind, DiagnosticKind::MapLimit("MAX_MAP_PLANES".to_string()));
+    assert!(limit.is_error());
+
+    let lightmaps = parser.parse_line("  Too many lightmaps on face 3  ").unwrap();
+    assert_eq!(lightmaps.kind, DiagnosticKind::TooManyLightmaps);
+    assert_eq!(lightmaps.message, "Too many lightmaps on face 3");
+
+    let fatal = parser.parse_line("FATAL ERROR: could not load test.bsp").unwrap();
+    assert_eq!((fatal.severity, fatal.kind), (Severity::Error, DiagnosticKind::Other));
+
+    assert_eq!(parser.parse_line(""), None);
+    assert_eq!(parser.parse_line("BuildFacelights: 0...1...2...3...4...5...6...7...8...9...10 (2)"), None);
+}
+
+#[test]
+fn tool_specific_patterns_need_their_tool() {
+    let mut vrad = LogParser::new("VRAD");
+    assert_eq!(vrad.parse_line("**** leaked ****"), None);
+    assert_eq!(vrad.parse_line("Brush 12: mixed face contents"), None);
+
+    let mut other = LogParser::new("CUSTOM");
+    assert_eq!(other.tool(), Tool::Other);
+    assert_eq!(other.parse_line("Too many lightmaps"), None);
+    assert!(other.parse_line("Warning: something happened").unwrap().is_warning());
+}
+
+fn phase_fractions(updates: &[ProgressUpdate]) -> Vec<(&str, f32, f32)> {
+    updates.iter()
+        .filter_map(|update| match update {
+            ProgressUpdate::Phase { phase, fraction, step_fraction } => Some((phase.as_str(), *fraction, *step_fraction)),
+            ProgressUpdate::Count(..) => None,
+        })
+        .collect()
+}
+
+#[test]
+fn follows_progress_split_across_chunks() {
+    let mut parser = ProgressParser::new("VVIS");
+
+    let updates = parser.feed("1234 portalclusters\n5678 numportals\n10 brushes\nBasePortalVis: 0...1...2");
+    assert_eq!(updates[..2], [
+        ProgressUpdate::Count("portalclusters".to_string(), 1234),
+        ProgressUpdate::Count("numportals".to_string(), 5678),
+    ]);
+    // "2" may be the start of a longer number until the next "..." arrives
+    assert_eq!(phase_fractions(&updates), [("BasePortalVis", 0.1, 0.05)]);
+
+    // The same tick is reported once
+    assert!(parser.feed("").is_empty());
+
+    let updates = parser.feed("...3...4...5...6...7...8...9...10 (0)\nPortalFlow: 0...1...2...3...4...5");
+    assert_eq!(phase_fractions(&updates), [("BasePortalVis", 1.0, 0.5), ("PortalFlow", 0.4, 0.7)]);
+
+    let updates = parser.feed("...6...7...8...9...10 (12)\n");
+    assert_eq!(phase_fractions(&updates), [("PortalFlow", 1.0, 1.0)]);
+}
+
+#[test]
+fn step_progress_never_moves_backwards() {
+    let mut parser = ProgressParser::new("VRAD");
+
+    let updates = parser.feed("BuildVisLeafs: 0...1...2...3...4...5...6...7...8...9...10 (1)\n");
+    assert_eq!(phase_fractions(&updates), [("BuildVisLeafs", 1.0, 0.4)]);
+
+    // An earlier phase running late keeps the estimate
+    let updates = parser.feed("BuildFacelights: 0...1...2...3...4...5...6...7...8...9...10 (5)\n");
+    assert_eq!(phase_fractions(&updates), [("BuildFacelights", 1.0, 0.4)]);
+
+    // Phases VRAD is not known to print still report their own progress
+    let updates = parser.feed("Unknown phase: 0...1...2...");
+    assert_eq!(phase_fractions(&updates), [("Unknown phase", 0.2, 0.4)]);
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use compiler_logs_process::{format_point, LeakedEntity, Pointfile, PointfileError};
+
+fn pointfile_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/pointfiles").join(name)
+}
+
+#[test]
+fn loads_leak_path() {
+    let pointfile = Pointfile::load(&pointfile_path("leak.lin")).unwrap();
+    assert_eq!(pointfile.points, [
+        [-1024.0, 512.0, 64.0],
+        [-1024.0, 512.0, -128.0],
+        [-1024.0, 112.0, -128.0],
+        [-1024.0, 112.0, -428.5],
+    ]);
+    assert_eq!(pointfile.start(), Some([-1024.0, 512.0, 64.0]));
+    assert_eq!(pointfile.length(), 892.5);
+    assert_eq!(format_point(&pointfile.start().unwrap()), "-1024 512 64");
+}
+
+#[test]
+fn rejects_invalid_lines() {
+    assert!(matches!(
+        Pointfile::parse("0 0 0\n1 2\n"),
+        Err(PointfileError::InvalidLine(2, line)) if line == "1 2"
+    ));
+    assert!(matches!(
+        Pointfile::parse("\n0 0 zero\n"),
+        Err(PointfileError::InvalidLine(2, line)) if line == "0 0 zero"
+    ));
+    assert!(matches!(Pointfile::load(&pointfile_path("missing.lin")), Err(PointfileError::Io(_))));
+
+    let empty = Pointfile::parse("").unwrap();
+    assert_eq!((empty.start(), empty.length()), (None, 0.0));
+}
+
+#[test]
+fn parses_leaked_entity_line() {
+    let entity = LeakedEntity::from_log_line("Entity info_player_start (-1024.00 512.00 64.00) leaked!\n").unwrap();
+    assert_eq!(entity.classname, "info_player_start");
+    assert_eq!(entity.origin, [-1024.0, 512.0, 64.0]);
+
+    assert_eq!(LeakedEntity::from_log_line("**** leaked ****"), None);
+    assert_eq!(LeakedEntity::from_log_line("Entity light (0 0) leaked!"), None);
+}
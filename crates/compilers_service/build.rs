@@ -1,4 +1,4 @@
-use std::{env, fs, path::PathBuf};
+use std::{env, fs, path::{Path, PathBuf}};
 
 use compiler_data_model::CompilerConfig;
 
@@ -15,20 +15,54 @@ fn main() {
         panic!("'compiler_configs' directory not found at {:?} or is not a directory.", compilers_configs_dir);
     }
 
-    for entry in fs::read_dir(&compilers_configs_dir)
-        .expect(&format!("Failed to read compilers directory at {:?}", compilers_configs_dir))
+    check_dir(&compilers_configs_dir);
+}
+
+/// Validates the TOML files of `dir` and its subfolders, the way `include_dir!` embeds them.
+fn check_dir(dir: &Path) {
+    for entry in fs::read_dir(dir)
+        .unwrap_or_else(|_| panic!("Failed to read compilers directory at {:?}", dir))
     {
         let entry = entry.expect("Failed to read entry");
         let path = entry.path();
 
+        if path.is_dir() {
+            println!("cargo:rerun-if-changed={}", path.display());
+            check_dir(&path);
+            continue;
+        }
+
         if path.is_file() {
             println!("cargo:rerun-if-changed={}", path.display());
         }
 
         if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("toml") {
             let contents = fs::read_to_string(&path)
-                .expect(&format!("Failed to read TOML file: {}", path.display()));
-            if let Err(e) = toml::from_str::<CompilerConfig>(&contents) {
+                .unwrap_or_else(|_| panic!("Failed to read TOML file: {}", path.display()));
+            let config = toml::from_str::<CompilerConfig>(&contents).map(|mut config| {
+                config.fill_missing_ids();
+                config
+            });
+            if let Ok(config) = &config
+                && let Err(e) = config.check_parameters()
+            {
+                panic!(
+                    "\n\n\
+                    ===============================================================\n\
+                    [BUILD SCRIPT ERROR] Invalid Parameter Definition\n\
+                    ===============================================================\n\
+                    File:    {}\n\
+                    Error:   {}\n\
+                    ---------------------------------------------------------------\n\
+                    Action: `requires` and `conflicts_with` must list ids of other\n\
+                            parameters of the same compiler, and defaults of\n\
+                            choice and bool parameters must be legal values.\n\
+                    ===============================================================\n\n",
+                    path.display(),
+                    e
+                );
+            }
+            if let Err(e) = config {
                 panic!(
                     "\n\n\
                     ===============================================================\n\
@@ -1,16 +1,19 @@
-use std::sync::LazyLock;
+use std::sync::{LazyLock, RwLock};
 use include_dir::{include_dir, Dir};
 use compiler_data_model::{CompilerConfig, Parameter}; 
 
+mod user_configs;
+pub use user_configs::ConfigLoadError;
+
 static COMPILERS_DIR: Dir = include_dir!("compiler_configs");
 
-static LOADED_COMPILERS: LazyLock<Vec<CompilerConfig>> = LazyLock::new(|| {
-    println!("--- LOADED_COMPILERS INITED RN!!!");
+static BUILTIN_COMPILERS: LazyLock<Vec<CompilerConfig>> = LazyLock::new(|| {
     let mut v = Vec::with_capacity(24);
     
     for file in COMPILERS_DIR.find("*.toml").unwrap().filter_map(|e| e.as_file()) {
         // Compile-time validations ensure only verified TOML configurations are processed.
-        let config: CompilerConfig = toml::from_str(file.contents_utf8().unwrap()).unwrap();
+        let mut config: CompilerConfig = toml::from_str(file.contents_utf8().unwrap()).unwrap();
+        config.fill_missing_ids();
         v.push(config);
     }
 
@@ -18,38 +21,73 @@ static LOADED_COMPILERS: LazyLock<Vec<CompilerConfig>> = LazyLock::new(|| {
     v
 });
 
+/// Built-in compilers followed by the user ones, replaced as a whole on reload.
 ///
+/// The previous list is leaked on reload so the `&'static` references handed out stay valid,
+/// reloads only happen when the user edits their configs.
+static LOADED_COMPILERS: LazyLock<RwLock<&'static [CompilerConfig]>> =
+    LazyLock::new(|| RwLock::new(BUILTIN_COMPILERS.as_slice()));
+
+/// Stands in for a compiler that disappeared from the user configs.
+static MISSING_COMPILER: LazyLock<CompilerConfig> = LazyLock::new(|| CompilerConfig {
+    name: "Missing compiler".to_string(),
+    description: "This compiler config was removed or failed to load".to_string(),
+    ..Default::default()
+});
+
+/// Get all loaded compiler configs
 pub fn all_configs() -> &'static [CompilerConfig] {
-    LOADED_COMPILERS.as_slice()
+    *LOADED_COMPILERS.read().unwrap()
 }
 
-/// Get compiler by index
-pub fn get_compiler(idx: usize) -> Option<&'static CompilerConfig> {
-    LOADED_COMPILERS.get(idx)
+/// Get compiler by id
+pub fn get_compiler(id: &str) -> Option<&'static CompilerConfig> {
+    all_configs().iter().find(|c| c.id == id)
 }
 
-/// Find compiler index by name
-pub fn find_compiler_idx(name: &str) -> Option<usize> {
-    LOADED_COMPILERS.iter().position(|c| c.name == name)
+/// Get compiler by id, or a placeholder config without parameters if it is not loaded
+pub fn get_compiler_or_missing(id: &str) -> &'static CompilerConfig {
+    get_compiler(id).unwrap_or(&MISSING_COMPILER)
 }
 
 /// Get compiler by name
 pub fn get_compiler_by_name(name: &str) -> Option<&'static CompilerConfig> {
-    find_compiler_idx(name).map(|idx| &LOADED_COMPILERS[idx])
+    all_configs().iter().find(|c| c.name == name)
 }
 
-/// Get parameter by index from a specific compiler
-pub fn get_parameter(compiler_idx: usize, param_idx: usize) -> Option<&'static Parameter> {
-    LOADED_COMPILERS.get(compiler_idx)
-        .and_then(|compiler| compiler.parameters.get(param_idx))
+/// Get parameter by id from a specific compiler
+pub fn get_parameter(compiler_id: &str, parameter_id: &str) -> Option<&'static Parameter> {
+    get_compiler(compiler_id)
+        .and_then(|compiler| compiler.find_parameter(parameter_id))
 }
 
-///
+/// Get the number of loaded compiler configs
 pub fn total_definitions() -> usize {
-    LOADED_COMPILERS.len()
+    all_configs().len()
 }
 
-///
+/// Get the number of built-in compiler configs, user ones come after them
+pub fn builtin_definitions() -> usize {
+    BUILTIN_COMPILERS.len()
+}
+
+/// Iterate over all loaded compiler configs
 pub fn iter_configs() -> impl Iterator<Item = &'static CompilerConfig> {
-    LOADED_COMPILERS.iter()
-}
\ No newline at end of file
+    all_configs().iter()
+}
+
+/// Loads the user compiler configs (`*.toml`) of `dir` and merges them with the built-ins.
+///
+/// A user config named like a built-in one replaces it. Invalid files are skipped and reported.
+pub fn load_user_configs(dir: &std::path::Path) -> Vec<ConfigLoadError> {
+    let (configs, errors) = user_configs::load(dir, &BUILTIN_COMPILERS);
+    *LOADED_COMPILERS.write().unwrap() = Box::leak(configs.into_boxed_slice());
+    errors
+}
+
+/// Reloads the user compiler configs if files of `dir` changed since the last load.
+///
+/// Returns the errors of the new load, or `None` if nothing changed.
+pub fn reload_user_configs_if_changed(dir: &std::path::Path) -> Option<Vec<ConfigLoadError>> {
+    user_configs::has_changed(dir).then(|| load_user_configs(dir))
+}
@@ -0,0 +1,148 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use compiler_data_model::{CompilerConfig, ParameterType};
+
+/// A user compiler config that could not be loaded.
+#[derive(Debug, Clone)]
+pub struct ConfigLoadError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file = self.path.file_name().unwrap_or(self.path.as_os_str());
+        write!(f, "{}: {}", file.to_string_lossy(), self.message)
+    }
+}
+
+/// (path, modification time, size) of every config file at the last load.
+type Fingerprint = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+static LAST_FINGERPRINT: Mutex<Option<Fingerprint>> = Mutex::new(None);
+
+/// Returns the built-ins merged with the valid user configs of `dir`, and the errors of the others.
+pub fn load(dir: &Path, builtins: &[CompilerConfig]) -> (Vec<CompilerConfig>, Vec<ConfigLoadError>) {
+    let mut configs = builtins.to_vec();
+    let mut errors = Vec::new();
+    let (files, fingerprint) = match config_files(dir) {
+        Ok(files) => {
+            let fingerprint = fingerprint(&files);
+            (files, fingerprint)
+        }
+        Err(message) => {
+            errors.push(ConfigLoadError { path: dir.to_path_buf(), message });
+            (Vec::new(), Vec::new())
+        }
+    };
+    *LAST_FINGERPRINT.lock().unwrap() = Some(fingerprint);
+
+    for path in files {
+        let result = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read the file: {e}"))
+            .and_then(|contents| toml::from_str::<CompilerConfig>(&contents).map_err(|e| e.to_string().trim_end().to_string()))
+            .and_then(|config| merge(&mut configs, builtins, config));
+        if let Err(message) = result {
+            errors.push(ConfigLoadError { path, message });
+        }
+    }
+
+    (configs, errors)
+}
+
+/// Whether config files of `dir` were added, removed or modified since the last load.
+pub fn has_changed(dir: &Path) -> bool {
+    let current = config_files(dir).map(|files| fingerprint(&files)).unwrap_or_default();
+    LAST_FINGERPRINT.lock().unwrap().as_ref() != Some(&current)
+}
+
+/// `*.toml` files of `dir`, sorted by name so user compilers keep their order. A missing folder has none.
+fn config_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read the folder: {e}")),
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("toml")))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn fingerprint(files: &[PathBuf]) -> Fingerprint {
+    files.iter()
+        .map(|path| {
+            let metadata = fs::metadata(path).ok();
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+            (path.clone(), modified, metadata.map_or(0, |m| m.len()))
+        })
+        .collect()
+}
+
+/// Adds a user config, or replaces the built-in config with the same id.
+fn merge(configs: &mut Vec<CompilerConfig>, builtins: &[CompilerConfig], mut config: CompilerConfig) -> Result<(), String> {
+    config.fill_missing_ids();
+    validate(&config)?;
+
+    if let Some(existing) = configs.iter().find(|existing| existing.name == config.name && existing.id != config.id) {
+        return Err(format!("The name '{}' is already used by compiler '{}'", config.name, existing.id));
+    }
+    match configs.iter().position(|existing| existing.id == config.id) {
+        Some(idx) if idx >= builtins.len() => {
+            Err(format!("A compiler with id '{}' is already defined by another file", config.id))
+        }
+        Some(idx) if configs[idx].is_builtin != config.is_builtin => Err(format!(
+            "'{}' replaces a built-in compiler, `is_builtin` must stay {}",
+            config.name, configs[idx].is_builtin
+        )),
+        Some(idx) => {
+            configs[idx] = config;
+            Ok(())
+        }
+        None if config.is_builtin => Err(format!(
+            "'{}' is not a built-in command of VMFlow, set `is_builtin = false`",
+            config.name
+        )),
+        None => {
+            configs.push(config);
+            Ok(())
+        }
+    }
+}
+
+fn validate(config: &CompilerConfig) -> Result<(), String> {
+    if config.name.trim().is_empty() {
+        return Err("The compiler has no name".to_string());
+    }
+
+    for (idx, parameter) in config.parameters.iter().enumerate() {
+        if parameter.name.trim().is_empty() {
+            return Err(format!("Parameter #{} has no name", idx + 1));
+        }
+        if let Some(other) = config.parameters[..idx].iter().find(|other| other.id == parameter.id) {
+            return Err(format!(
+                "Parameters '{}' and '{}' have the same id '{}', set `id` on one of them",
+                other.name, parameter.name, parameter.id
+            ));
+        }
+        if matches!(parameter.value_type, ParameterType::Flag) && parameter.argument.is_empty() {
+            return Err(format!("Flag '{}' has no argument", parameter.name));
+        }
+        if let Some(constraints) = &parameter.constraints
+            && let (Some(min), Some(max)) = (constraints.min_value, constraints.max_value)
+            && min > max
+        {
+            return Err(format!("Parameter '{}' has a minimum ({min}) above its maximum ({max})", parameter.name));
+        }
+    }
+    config.check_parameters()
+}
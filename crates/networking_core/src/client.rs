@@ -0,0 +1,136 @@
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+};
+
+use compilation_core::{BatchReport, JobEventHandler};
+use vmflow_config_types::preset::Preset;
+
+use crate::{
+    protocol::{
+        read_file, read_message, send_file, write_message, ClientMessage, CompileRequest, ServerInfo, ServerMessage,
+        UploadedFile, PROTOCOL_VERSION,
+    },
+    NetworkError,
+};
+
+/// Biggest .bsp or log accepted from a server.
+const MAX_DOWNLOAD_SIZE: u64 = u32::MAX as u64;
+
+/// A map to compile on a server.
+pub struct RemoteJob {
+    /// Name of a game configuration of the server.
+    pub game: String,
+    pub preset: Preset,
+    pub map_path: PathBuf,
+    /// Custom content to upload, `(path relative to the game folder, file on disk)`.
+    pub content: Vec<(String, PathBuf)>,
+}
+
+/// What a server sends back once a job ends.
+pub struct RemoteResult {
+    pub report: BatchReport,
+    /// Content of the compiled .bsp, if the compile produced one.
+    pub bsp: Option<Vec<u8>>,
+    pub log: String,
+}
+
+/// Connection to a compile server, used for one compile.
+pub struct RemoteClient {
+    stream: TcpStream,
+    server_info: ServerInfo,
+}
+
+/// Cancels the job of a [`RemoteClient`] from another thread.
+pub struct CancelHandle {
+    stream: TcpStream,
+}
+
+impl CancelHandle {
+    pub fn cancel(&self) -> Result<(), NetworkError> {
+        write_message(&mut &self.stream, &ClientMessage::Cancel)
+    }
+}
+
+impl RemoteClient {
+    /// Connects and exchanges the protocol version, the token of the server and its info.
+    pub fn connect(addr: impl ToSocketAddrs, token: Option<&str>) -> Result<Self, NetworkError> {
+        let mut stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        let hello = ClientMessage::Hello {
+            protocol_version: PROTOCOL_VERSION,
+            token: token.map(str::to_string),
+        };
+        write_message(&mut stream, &hello)?;
+
+        match read_message::<ServerMessage>(&mut stream)? {
+            Some(ServerMessage::Hello(server_info)) if server_info.protocol_version == PROTOCOL_VERSION => {
+                Ok(Self { stream, server_info })
+            }
+            Some(ServerMessage::Hello(server_info)) => {
+                Err(NetworkError::VersionMismatch(PROTOCOL_VERSION, server_info.protocol_version))
+            }
+            Some(ServerMessage::Rejected(reason)) => Err(NetworkError::Rejected(reason)),
+            Some(_) => Err(NetworkError::Protocol("Expected a hello".to_string())),
+            None => Err(NetworkError::Protocol("Server closed the connection".to_string())),
+        }
+    }
+
+    pub fn server_info(&self) -> &ServerInfo {
+        &self.server_info
+    }
+
+    pub fn cancel_handle(&self) -> Result<CancelHandle, NetworkError> {
+        Ok(CancelHandle { stream: self.stream.try_clone()? })
+    }
+
+    /// Uploads the job and blocks until it ends, passing the events of the server to `event_handler`.
+    pub fn compile(mut self, job: &RemoteJob, event_handler: &dyn JobEventHandler) -> Result<RemoteResult, NetworkError> {
+        if self.server_info.find_game(&job.game).is_none() {
+            return Err(NetworkError::Rejected(format!("The server has no game configuration named '{}'", job.game)));
+        }
+
+        let map_name = job.map_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let files: Vec<(&str, &Path)> = std::iter::once((map_name.as_str(), job.map_path.as_path()))
+            .chain(job.content.iter().map(|(relative, path)| (relative.as_str(), path.as_path())))
+            .collect();
+        let uploads = files.iter()
+            .map(|(relative, path)| Ok(UploadedFile { path: relative.to_string(), size: fs::metadata(path)?.len() }))
+            .collect::<Result<Vec<_>, NetworkError>>()?;
+
+        let request = CompileRequest {
+            game: job.game.clone(),
+            preset: job.preset.clone(),
+            map_name: map_name.clone(),
+            files: uploads,
+        };
+        let sizes: Vec<u64> = request.files.iter().map(|file| file.size).collect();
+        write_message(&mut self.stream, &ClientMessage::Compile(request))?;
+        // Files are streamed from disk, custom content may be bigger than the memory
+        let mut writer = BufWriter::new(&self.stream);
+        for ((_, path), size) in files.iter().zip(sizes) {
+            send_file(&mut writer, &mut File::open(path)?, size)?;
+        }
+        drop(writer);
+
+        loop {
+            match read_message::<ServerMessage>(&mut self.stream)? {
+                Some(ServerMessage::Event(event)) => event_handler.handle_event(event),
+                Some(ServerMessage::Finished { report, has_bsp }) => {
+                    let bsp = has_bsp.then(|| read_file(&mut self.stream, MAX_DOWNLOAD_SIZE)).transpose()?;
+                    let log = read_file(&mut self.stream, MAX_DOWNLOAD_SIZE)?;
+                    return Ok(RemoteResult {
+                        report,
+                        bsp,
+                        log: String::from_utf8_lossy(&log).to_string(),
+                    });
+                }
+                Some(ServerMessage::Rejected(reason)) => return Err(NetworkError::Rejected(reason)),
+                Some(ServerMessage::Hello(_)) => return Err(NetworkError::Protocol("Unexpected hello".to_string())),
+                None => return Err(NetworkError::Protocol("Server closed the connection during the compile".to_string())),
+            }
+        }
+    }
+}
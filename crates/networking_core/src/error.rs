@@ -0,0 +1,34 @@
+use std::{fmt, io};
+
+/// Errors of the remote compilation protocol.
+#[derive(Debug)]
+pub enum NetworkError {
+    Io(io::Error),
+    /// The peer sent something that does not follow the protocol.
+    Protocol(String),
+    /// The server refused the job (unknown game, invalid upload...).
+    Rejected(String),
+    VersionMismatch(u32, u32), // (ours, theirs)
+    /// The server refuses to start with this configuration.
+    Config(String),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Connection error: {e}"),
+            Self::Protocol(message) => write!(f, "Protocol error: {message}"),
+            Self::Rejected(message) => write!(f, "Rejected by the server: {message}"),
+            Self::VersionMismatch(ours, theirs) => {
+                write!(f, "Protocol version mismatch: we speak {ours}, the peer speaks {theirs}")
+            }
+            Self::Config(message) => write!(f, "Invalid server configuration: {message}"),
+        }
+    }
+}
+
+impl From<io::Error> for NetworkError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
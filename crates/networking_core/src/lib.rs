@@ -1 +1,18 @@
-fn x() {}
\ No newline at end of file
+//! Remote compilation: a server running maps through `compilation_core` for clients over TCP.
+//!
+//! The client uploads a map with its custom content, the server compiles it with one of its own
+//! game configurations, streams every `CoreEvent` back live and finally returns the .bsp and logs.
+
+mod error;
+pub use error::NetworkError;
+
+pub mod protocol;
+
+mod server;
+pub use server::{CompileServer, ServerConfig};
+
+mod client;
+pub use client::{CancelHandle, RemoteClient, RemoteJob, RemoteResult};
+
+/// Default TCP port of the compile server.
+pub const DEFAULT_PORT: u16 = 27080;
@@ -0,0 +1,193 @@
+//! Messages exchanged between the compile client and server, and how they are framed on the wire.
+//!
+//! Every frame is a kind byte, a big-endian `u32` payload length and the payload. Messages are JSON
+//! frames, uploaded and downloaded files are binary frames following the message announcing them.
+//!
+//! A connection runs at most one compile:
+//! 1. the client sends [`ClientMessage::Hello`], the server answers [`ServerMessage::Hello`] or `Rejected`;
+//! 2. the client sends [`ClientMessage::Compile`] followed by one file frame per [`UploadedFile`];
+//! 3. the server streams [`ServerMessage::Event`]s, the client may send [`ClientMessage::Cancel`];
+//! 4. the server sends [`ServerMessage::Finished`] followed by the .bsp (if any) and log file frames.
+
+use std::io::{self, Read, Write};
+
+use compilation_core::{BatchReport, CoreEvent};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use vmflow_config_types::preset::Preset;
+
+use crate::NetworkError;
+
+/// Bumped whenever messages change, presets and their compiler ids included.
+pub const PROTOCOL_VERSION: u32 = 4;
+
+const MESSAGE_FRAME: u8 = 0;
+const FILE_FRAME: u8 = 1;
+/// Messages carry events and reports, anything bigger is a broken peer.
+const MAX_MESSAGE_SIZE: u32 = 64 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize)]
+pub enum ClientMessage {
+    Hello {
+        protocol_version: u32,
+        /// Secret of the server, if it requires one.
+        #[serde(default)]
+        token: Option<String>,
+    },
+    Compile(CompileRequest),
+    Cancel,
+}
+
+/// A map to compile, followed on the wire by the content of `files`.
+#[derive(Serialize, Deserialize)]
+pub struct CompileRequest {
+    /// Name of a game configuration of the server.
+    pub game: String,
+    pub preset: Preset,
+    /// File name of the map, its content is the first uploaded file.
+    pub map_name: String,
+    /// The map, then custom content, with paths relative to the game folder (`materials/...`).
+    pub files: Vec<UploadedFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UploadedFile {
+    /// `/` separated relative path.
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ServerMessage {
+    Hello(ServerInfo),
+    Rejected(String),
+    Event(CoreEvent),
+    /// Followed by the .bsp file frame if `has_bsp`, then the log file frame.
+    Finished { report: BatchReport, has_bsp: bool },
+}
+
+/// What a server can compile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerInfo {
+    pub protocol_version: u32,
+    pub games: Vec<RemoteGame>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteGame {
+    pub name: String,
+    pub steam_app_id: Option<u32>,
+    /// Names of the compilers installed for this game that the server allows.
+    pub compilers: Vec<String>,
+}
+
+impl ServerInfo {
+    pub fn find_game(&self, name: &str) -> Option<&RemoteGame> {
+        self.games.iter().find(|game| game.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Writes a JSON message frame.
+pub fn write_message(writer: &mut impl Write, message: &impl Serialize) -> Result<(), NetworkError> {
+    let payload = serde_json::to_vec(message)
+        .map_err(|e| NetworkError::Protocol(format!("Failed to encode a message: {e}")))?;
+    write_frame(writer, MESSAGE_FRAME, &payload)
+}
+
+/// Writes a binary file frame.
+pub fn write_file(writer: &mut impl Write, content: &[u8]) -> Result<(), NetworkError> {
+    write_frame(writer, FILE_FRAME, content)
+}
+
+/// Writes a binary file frame of `size` bytes read from `reader`, without holding it in memory.
+pub fn send_file(writer: &mut impl Write, reader: &mut impl Read, size: u64) -> Result<(), NetworkError> {
+    let length = u32::try_from(size)
+        .map_err(|_| NetworkError::Protocol(format!("File of {size} bytes is too big")))?;
+    write_header(writer, FILE_FRAME, length)?;
+
+    let copied = io::copy(&mut reader.take(size), writer)?;
+    if copied != size {
+        return Err(NetworkError::Protocol(format!("File of {size} bytes announced, {copied} read")));
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_frame(writer: &mut impl Write, kind: u8, payload: &[u8]) -> Result<(), NetworkError> {
+    let length = u32::try_from(payload.len())
+        .map_err(|_| NetworkError::Protocol(format!("Frame of {} bytes is too big", payload.len())))?;
+
+    write_header(writer, kind, length)?;
+    writer.write_all(payload)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_header(writer: &mut impl Write, kind: u8, length: u32) -> Result<(), NetworkError> {
+    let mut header = [0u8; 5];
+    header[0] = kind;
+    header[1..].copy_from_slice(&length.to_be_bytes());
+    writer.write_all(&header)?;
+    Ok(())
+}
+
+/// Reads a JSON message frame. `Ok(None)` means the peer closed the connection between frames.
+pub fn read_message<T: DeserializeOwned>(reader: &mut impl Read) -> Result<Option<T>, NetworkError> {
+    let Some(payload) = read_frame(reader, MESSAGE_FRAME, MAX_MESSAGE_SIZE)? else {
+        return Ok(None);
+    };
+    serde_json::from_slice(&payload)
+        .map(Some)
+        .map_err(|e| NetworkError::Protocol(format!("Invalid message: {e}")))
+}
+
+/// Reads a binary file frame of at most `max_size` bytes.
+pub fn read_file(reader: &mut impl Read, max_size: u64) -> Result<Vec<u8>, NetworkError> {
+    let max_size = u32::try_from(max_size).unwrap_or(u32::MAX);
+    read_frame(reader, FILE_FRAME, max_size)?
+        .ok_or_else(|| NetworkError::Protocol("Connection closed before a file was received".to_string()))
+}
+
+/// Copies a binary file frame announced as `size` bytes to `writer`, without holding it in memory.
+pub fn copy_file(reader: &mut impl Read, writer: &mut impl Write, size: u64) -> Result<(), NetworkError> {
+    let Some(length) = read_header(reader, FILE_FRAME)? else {
+        return Err(NetworkError::Protocol("Connection closed before a file was received".to_string()));
+    };
+    if u64::from(length) != size {
+        return Err(NetworkError::Protocol(format!("File of {size} bytes announced, {length} sent")));
+    }
+
+    let copied = io::copy(&mut reader.take(size), writer)?;
+    if copied != size {
+        return Err(NetworkError::Protocol("Connection closed during a file".to_string()));
+    }
+    Ok(())
+}
+
+/// Reads a frame header, returns the payload length. `Ok(None)` means the peer closed the connection.
+fn read_header(reader: &mut impl Read, expected_kind: u8) -> Result<Option<u32>, NetworkError> {
+    let mut header = [0u8; 5];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let kind = header[0];
+    if kind != expected_kind {
+        return Err(NetworkError::Protocol(format!("Expected a frame of kind {expected_kind}, got {kind}")));
+    }
+    Ok(Some(u32::from_be_bytes([header[1], header[2], header[3], header[4]])))
+}
+
+fn read_frame(reader: &mut impl Read, expected_kind: u8, max_size: u32) -> Result<Option<Vec<u8>>, NetworkError> {
+    let Some(length) = read_header(reader, expected_kind)? else {
+        return Ok(None);
+    };
+    if length > max_size {
+        return Err(NetworkError::Protocol(format!("Frame of {length} bytes exceeds the limit of {max_size}")));
+    }
+
+    let mut payload = vec![0u8; length as usize];
+    reader.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
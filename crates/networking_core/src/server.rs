@@ -0,0 +1,397 @@
+use std::{
+    ffi::OsStr,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    path::{Component, Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use compilation_core::{CompilationSession, CoreEvent, JobEventHandler};
+use vmflow_config_types::{preset::Preset, GameConfiguration, VmfMap};
+
+use crate::{
+    protocol::{
+        copy_file, read_message, write_file, write_message, ClientMessage, CompileRequest, RemoteGame, ServerInfo,
+        ServerMessage, PROTOCOL_VERSION,
+    },
+    NetworkError,
+};
+
+/// Biggest file a client may upload.
+const MAX_UPLOAD_SIZE: u64 = 1024 * 1024 * 1024;
+/// Most files a job may upload, the map included.
+const MAX_UPLOAD_FILES: usize = 10_000;
+/// Biggest total size of the files of a job.
+const MAX_UPLOAD_TOTAL_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+
+/// What a compile server offers and where it works.
+#[derive(Clone)]
+pub struct ServerConfig {
+    /// Game configurations clients can compile for.
+    pub games: Vec<GameConfiguration>,
+    /// Folder uploaded maps are compiled in, one subfolder per job.
+    pub work_dir: PathBuf,
+    /// Ids of the compilers presets may run. Built-in steps never run remotely, they act on the server itself.
+    pub allowed_compilers: Vec<String>,
+    /// Secret clients must send to be served. Required unless the server only listens on loopback.
+    pub token: Option<String>,
+}
+
+impl ServerConfig {
+    /// Games and installed compilers advertised to clients.
+    pub fn server_info(&self) -> ServerInfo {
+        let games = self.games.iter().map(|game| RemoteGame {
+            name: game.name.clone(),
+            steam_app_id: game.steam_app_id,
+            compilers: compilers_service::iter_configs()
+                .filter(|config| self.allows(&config.id) && game.compiler_path(&config.id).is_some())
+                .map(|config| config.name.clone())
+                .collect(),
+        });
+
+        ServerInfo {
+            protocol_version: PROTOCOL_VERSION,
+            games: games.collect(),
+        }
+    }
+
+    /// Whether presets may run this compiler.
+    pub fn allows(&self, compiler_id: &str) -> bool {
+        let is_builtin = compilers_service::get_compiler(compiler_id).is_none_or(|config| config.is_builtin);
+        !is_builtin && self.allowed_compilers.iter().any(|id| id == compiler_id)
+    }
+
+    /// Refuses presets with an activated step the server does not allow.
+    fn check_preset(&self, preset: &Preset) -> Result<(), NetworkError> {
+        match preset.apps.iter().find(|step| step.activated && !self.allows(&step.compiler_id)) {
+            Some(step) => Err(NetworkError::Rejected(format!("The server does not run the {} step ({})", step.name(), step.compiler_id))),
+            None => Ok(()),
+        }
+    }
+
+    fn accepts_token(&self, token: Option<&str>) -> bool {
+        match (&self.token, token) {
+            (None, _) => true,
+            (Some(expected), Some(token)) => constant_time_eq(expected.as_bytes(), token.as_bytes()),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Compares secrets in a time that does not depend on where they differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Accepts remote compile jobs over TCP, each connection is served on its own thread.
+pub struct CompileServer {
+    listener: TcpListener,
+    config: Arc<ServerConfig>,
+    /// Start time and process id, so job folders of a previous or concurrent server are never reused.
+    run_id: String,
+    next_job_id: Arc<AtomicU64>,
+}
+
+impl CompileServer {
+    /// Listens on `addr`. Without a token, only loopback addresses are accepted.
+    pub fn bind(addr: impl ToSocketAddrs, config: ServerConfig) -> Result<Self, NetworkError> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        if config.token.is_none() && !local_addr.ip().is_loopback() {
+            return Err(NetworkError::Config(format!(
+                "{local_addr} is reachable from other machines, a token is required"
+            )));
+        }
+
+        fs::create_dir_all(&config.work_dir)?;
+        let started = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        Ok(Self {
+            listener,
+            config: Arc::new(config),
+            run_id: format!("{}_{}", started.as_millis(), std::process::id()),
+            next_job_id: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, NetworkError> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves clients until the listener fails.
+    pub fn run(&self) -> Result<(), NetworkError> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let config = Arc::clone(&self.config);
+            let job_id = self.next_job_id.fetch_add(1, Ordering::Relaxed);
+            let job_name = format!("{}_{job_id}", self.run_id);
+
+            thread::spawn(move || {
+                let peer = stream.peer_addr().map_or("unknown client".to_string(), |addr| addr.to_string());
+                eprintln!("[remote] job {job_id}: connection from {peer}");
+                match serve_client(stream, &config, &job_name) {
+                    Ok(()) => eprintln!("[remote] job {job_id}: done"),
+                    Err(e) => eprintln!("[remote] job {job_id}: {e}"),
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Sends events to the client and keeps the log lines to return them at the end.
+struct ForwardingHandler {
+    stream: Mutex<TcpStream>,
+    logs: Mutex<Vec<String>>,
+}
+
+impl JobEventHandler for ForwardingHandler {
+    fn handle_event(&self, event: CoreEvent) {
+        let line = match &event {
+            CoreEvent::StepStarted(_, _, step) => Some(format!("==> {step}")),
+            CoreEvent::StepLog(_, _, _, line) | CoreEvent::StepWarn(_, _, _, line) | CoreEvent::StepErr(_, _, _, line) => {
+                Some(line.clone())
+            }
+            CoreEvent::StepFailed(_, _, step, error) => Some(format!("==> {step} failed: {error}")),
+            CoreEvent::StepSkipped(_, _, step, reason) => Some(format!("==> {step} skipped: {reason}")),
+            _ => None,
+        };
+        if let Some(line) = line {
+            self.logs.lock().unwrap().push(line);
+        }
+
+        // A client gone away is noticed by the reader, which cancels the job
+        let mut stream = self.stream.lock().unwrap();
+        let _ = write_message(&mut *stream, &ServerMessage::Event(event));
+    }
+}
+
+/// Serves one connection. `job_name` names the folders of the job, unique across server runs.
+fn serve_client(mut stream: TcpStream, config: &ServerConfig, job_name: &str) -> Result<(), NetworkError> {
+    match read_message::<ClientMessage>(&mut stream)? {
+        Some(ClientMessage::Hello { protocol_version, token }) if protocol_version == PROTOCOL_VERSION => {
+            if !config.accepts_token(token.as_deref()) {
+                let error = NetworkError::Rejected("Invalid or missing token".to_string());
+                reject(&mut stream, &error)?;
+                return Err(error);
+            }
+            write_message(&mut stream, &ServerMessage::Hello(config.server_info()))?;
+        }
+        Some(ClientMessage::Hello { protocol_version, .. }) => {
+            let error = NetworkError::VersionMismatch(PROTOCOL_VERSION, protocol_version);
+            reject(&mut stream, &error)?;
+            return Err(error);
+        }
+        Some(_) => return Err(NetworkError::Protocol("Expected a hello".to_string())),
+        None => return Ok(()),
+    }
+
+    // Clients may only ask for the server info
+    let request = match read_message::<ClientMessage>(&mut stream)? {
+        Some(ClientMessage::Compile(request)) => request,
+        Some(_) => return Err(NetworkError::Protocol("Expected a compile request".to_string())),
+        None => return Ok(()),
+    };
+
+    let job_dir = RemoveOnDrop(config.work_dir.join(job_name));
+    match receive_job(&mut stream, config, &request, &job_dir.0, job_name) {
+        // The content folder is removed once the compile ends
+        Ok((game, map, _content_dir)) => run_job(stream, game, request, map),
+        Err(error) => {
+            let _ = reject(&mut stream, &error);
+            Err(error)
+        }
+    }
+}
+
+/// Tells the client why it is not served, the client adds that the server rejected it.
+fn reject(stream: &mut TcpStream, error: &NetworkError) -> Result<(), NetworkError> {
+    let reason = match error {
+        NetworkError::Rejected(reason) => reason.clone(),
+        error => error.to_string(),
+    };
+    write_message(stream, &ServerMessage::Rejected(reason))
+}
+
+/// Folder of a job, deleted with everything in it when dropped.
+struct RemoveOnDrop(PathBuf);
+
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.0) && self.0.exists() {
+            eprintln!("[remote] failed to remove {}: {e}", self.0.display());
+        }
+    }
+}
+
+/// What a valid request compiles: the game, the map file name and where its custom content goes.
+struct CheckedRequest<'a> {
+    game: &'a GameConfiguration,
+    map_name: &'a OsStr,
+    /// Relative to the custom content folder, one per uploaded file after the map.
+    content_paths: Vec<PathBuf>,
+}
+
+/// Checks a request before anything it uploads is written.
+fn check_request<'a>(config: &'a ServerConfig, request: &'a CompileRequest) -> Result<CheckedRequest<'a>, NetworkError> {
+    let game = config.games.iter()
+        .find(|game| game.name.eq_ignore_ascii_case(&request.game))
+        .ok_or_else(|| NetworkError::Rejected(format!("No game configuration named '{}'", request.game)))?;
+    config.check_preset(&request.preset)?;
+
+    let map_name = Path::new(&request.map_name)
+        .file_name()
+        .filter(|name| Path::new(name).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("vmf")))
+        .ok_or_else(|| NetworkError::Rejected(format!("'{}' is not a .vmf file", request.map_name)))?;
+    if request.files.is_empty() {
+        return Err(NetworkError::Rejected("The map was not uploaded".to_string()));
+    }
+    if request.files.len() > MAX_UPLOAD_FILES {
+        return Err(NetworkError::Rejected(format!(
+            "{} files uploaded, the limit is {MAX_UPLOAD_FILES}",
+            request.files.len()
+        )));
+    }
+    if let Some(file) = request.files.iter().find(|file| file.size > MAX_UPLOAD_SIZE) {
+        return Err(NetworkError::Rejected(format!("'{}' is bigger than {MAX_UPLOAD_SIZE} bytes", file.path)));
+    }
+    let total_size: u64 = request.files.iter().map(|file| file.size).sum();
+    if total_size > MAX_UPLOAD_TOTAL_SIZE {
+        return Err(NetworkError::Rejected(format!(
+            "The job uploads {total_size} bytes, the limit is {MAX_UPLOAD_TOTAL_SIZE}"
+        )));
+    }
+
+    let content_paths = request.files.iter()
+        .skip(1)
+        .map(|file| {
+            sanitize_upload_path(&file.path)
+                .ok_or_else(|| NetworkError::Rejected(format!("Invalid content path '{}'", file.path)))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(CheckedRequest { game, map_name, content_paths })
+}
+
+/// Receives the uploaded files. The map goes in the job folder, custom content in a `custom` folder of the game.
+///
+/// Returns the game, the map and the custom content folder, deleted when dropped.
+fn receive_job(
+    stream: &mut TcpStream,
+    config: &ServerConfig,
+    request: &CompileRequest,
+    job_dir: &Path,
+    job_name: &str,
+) -> Result<(GameConfiguration, VmfMap, RemoveOnDrop), NetworkError> {
+    let checked = match check_request(config, request) {
+        Ok(checked) => checked,
+        Err(error) => {
+            // Uploads are skipped so the rejection reaches a client that is not sending anymore
+            for file in &request.files {
+                copy_file(stream, &mut io::sink(), file.size)?;
+            }
+            return Err(error);
+        }
+    };
+
+    clear_dir(job_dir)?;
+    let map_path = job_dir.join(checked.map_name);
+    receive_file(stream, &map_path, request.files[0].size)?;
+
+    // The game mounts every folder of `custom`, a leftover of a crashed run must not join this job
+    let content_dir = RemoveOnDrop(
+        Path::new(&checked.game.game_dir).join("custom").join(format!("vmflow_remote_{job_name}")),
+    );
+    clear_dir(&content_dir.0)?;
+    for (file, relative) in request.files.iter().skip(1).zip(&checked.content_paths) {
+        receive_file(stream, &content_dir.0.join(relative), file.size)?;
+    }
+
+    let map = VmfMap {
+        name: checked.map_name.to_string_lossy().to_string(),
+        path: map_path,
+        activated: true,
+        order_idx: 0,
+    };
+    Ok((checked.game.clone(), map, content_dir))
+}
+
+/// Creates `dir` empty, deleting what a previous job left in it.
+fn clear_dir(dir: &Path) -> io::Result<()> {
+    match fs::remove_dir_all(dir) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    fs::create_dir_all(dir)
+}
+
+/// Writes the next uploaded file to `path` as it arrives.
+fn receive_file(stream: &mut TcpStream, path: &Path, size: u64) -> Result<(), NetworkError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = BufWriter::new(File::create(path)?);
+    copy_file(stream, &mut file, size)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Keeps uploaded paths inside the folder they are written to.
+fn sanitize_upload_path(path: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    let is_safe = path.components().all(|component| matches!(component, Component::Normal(_)));
+    (is_safe && path.components().next().is_some()).then(|| path.to_path_buf())
+}
+
+fn run_job(stream: TcpStream, game: GameConfiguration, request: CompileRequest, map: VmfMap) -> Result<(), NetworkError> {
+    let mut reader = stream.try_clone()?;
+    let handler = Arc::new(ForwardingHandler {
+        stream: Mutex::new(stream.try_clone()?),
+        logs: Mutex::new(Vec::new()),
+    });
+    let session = Arc::new(CompilationSession::new(request.preset, game, 1, Some(handler.clone())));
+    let finished = Arc::new(AtomicBool::new(false));
+
+    // Watches for cancellation, a client disconnecting mid-compile cancels too
+    let reader_session = Arc::clone(&session);
+    let reader_finished = Arc::clone(&finished);
+    thread::spawn(move || {
+        loop {
+            match read_message::<ClientMessage>(&mut reader) {
+                Ok(Some(ClientMessage::Cancel)) => reader_session.cancel_batch(),
+                Ok(Some(_)) => {}
+                Ok(None) | Err(_) => {
+                    if !reader_finished.load(Ordering::Relaxed) {
+                        reader_session.cancel_batch();
+                    }
+                    break;
+                }
+            }
+        }
+    });
+
+    let bsp_path = map.path.with_extension("bsp");
+    let report = session
+        .start_batch(vec![map])
+        .join()
+        .map_err(|_| NetworkError::Protocol("Compilation thread panicked".to_string()))?;
+    finished.store(true, Ordering::Relaxed);
+
+    let bsp = fs::read(&bsp_path).ok();
+    let log = handler.logs.lock().unwrap().join("\n");
+    let mut stream = handler.stream.lock().unwrap();
+    write_message(&mut *stream, &ServerMessage::Finished { report, has_bsp: bsp.is_some() })?;
+    if let Some(bsp) = &bsp {
+        write_file(&mut *stream, bsp)?;
+    }
+    write_file(&mut *stream, log.as_bytes())?;
+
+    // The client closes the connection once it has read everything, which stops the reader
+    let _ = stream.shutdown(Shutdown::Write);
+    Ok(())
+}
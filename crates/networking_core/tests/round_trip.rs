@@ -0,0 +1,204 @@
+use std::{
+    fs,
+    net::{SocketAddr, TcpStream},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use compilation_core::{CoreEvent, JobEventHandler};
+use networking_core::{
+    protocol::{read_message, write_file, write_message, ClientMessage, CompileRequest, ServerMessage, UploadedFile, PROTOCOL_VERSION},
+    CompileServer, NetworkError, RemoteClient, RemoteJob, ServerConfig,
+};
+use vmflow_config_types::{
+    preset::{PreflightPolicy, Preset},
+    selected_compiler::SelectedCompiler,
+    GameConfiguration,
+};
+
+const TOKEN: &str = "secret";
+
+struct IgnoreEvents;
+
+impl JobEventHandler for IgnoreEvents {
+    fn handle_event(&self, _event: CoreEvent) {}
+}
+
+/// Empty folder for one test, removed by the next run of the same test.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vmflow_remote_test_{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("game")).unwrap();
+    dir
+}
+
+fn game(dir: &Path) -> GameConfiguration {
+    GameConfiguration {
+        name: "HL2".to_string(),
+        game_dir: dir.join("game").to_string_lossy().to_string(),
+        bin_dir: dir.to_string_lossy().to_string(),
+        ..Default::default()
+    }
+}
+
+fn start_server(dir: &Path, game: GameConfiguration) -> SocketAddr {
+    let config = ServerConfig {
+        games: vec![game],
+        work_dir: dir.join("work"),
+        allowed_compilers: vec!["vbsp".to_string()],
+        token: Some(TOKEN.to_string()),
+    };
+    let server = CompileServer::bind("127.0.0.1:0", config).unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    addr
+}
+
+fn preset(compiler_ids: &[&str]) -> Preset {
+    Preset {
+        name: "Remote".to_string(),
+        apps: compiler_ids.iter().map(|id| SelectedCompiler::from_id(id)).collect(),
+        preflight: PreflightPolicy::Off,
+        ..Default::default()
+    }
+}
+
+fn job(dir: &Path, preset: Preset, content: &[(&str, &str)]) -> RemoteJob {
+    let map_path = dir.join("test.vmf");
+    fs::write(&map_path, "world\n{\n}\n").unwrap();
+    let content = content.iter()
+        .map(|(relative, text)| {
+            let path = dir.join(relative.replace('/', "_"));
+            fs::write(&path, text).unwrap();
+            (relative.to_string(), path)
+        })
+        .collect();
+    RemoteJob { game: "HL2".to_string(), preset, map_path, content }
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path).map_or(true, |mut entries| entries.next().is_none())
+}
+
+/// The server cleans up once the client has read the result, so give it a moment.
+fn eventually_empty(path: &Path) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if is_empty_dir(path) {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+#[cfg(unix)]
+#[test]
+fn compiles_and_cleans_up() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = test_dir("compile");
+    let mut game = game(&dir);
+    let vbsp = dir.join("vbsp.sh");
+    let script = format!(
+        "#!/bin/sh\nfor a in \"$@\"; do last=\"$a\"; done\ncd '{}/custom' && find . -type f\necho BSPDATA > \"${{last%.vmf}}.bsp\"\n",
+        game.game_dir
+    );
+    fs::write(&vbsp, script).unwrap();
+    fs::set_permissions(&vbsp, fs::Permissions::from_mode(0o755)).unwrap();
+    game.compiler_paths.insert("vbsp".to_string(), vbsp.to_string_lossy().to_string());
+    let addr = start_server(&dir, game);
+
+    let client = RemoteClient::connect(addr, Some(TOKEN)).unwrap();
+    assert_eq!(client.server_info().games[0].compilers, ["VBSP"]);
+    let job = job(&dir, preset(&["vbsp"]), &[("materials/test/test.vmt", "LightmappedGeneric {}")]);
+    let result = client.compile(&job, &IgnoreEvents).unwrap();
+
+    assert!(result.report.is_success());
+    assert_eq!(result.bsp.as_deref(), Some(&b"BSPDATA\n"[..]));
+    // The content folder is named after the server run too, a folder of an earlier run is never reused
+    let uploaded: Vec<&str> = result.log.lines().filter(|line| line.starts_with("./vmflow_remote_")).collect();
+    assert_eq!(uploaded.len(), 1, "{}", result.log);
+    assert!(uploaded[0].ends_with("_0/materials/test/test.vmt"), "{}", result.log);
+    assert!(!uploaded[0].starts_with("./vmflow_remote_0/"), "{}", result.log);
+    assert!(eventually_empty(&dir.join("game/custom")));
+    assert!(eventually_empty(&dir.join("work")));
+}
+
+#[test]
+fn rejects_before_writing_anything() {
+    let dir = test_dir("reject");
+    let addr = start_server(&dir, game(&dir));
+
+    let rejection = |token, job: &RemoteJob| {
+        match RemoteClient::connect(addr, token).and_then(|client| client.compile(job, &IgnoreEvents)) {
+            Err(NetworkError::Rejected(reason)) => reason,
+            Err(e) => panic!("expected a rejection, got {e}"),
+            Ok(_) => panic!("expected a rejection, the job ran"),
+        }
+    };
+
+    let allowed = job(&dir, preset(&["vbsp"]), &[]);
+    assert_eq!(rejection(None, &allowed), "Invalid or missing token");
+    assert_eq!(rejection(Some("wrong"), &allowed), "Invalid or missing token");
+
+    let builtin = job(&dir, preset(&["vbsp", "copy"]), &[("materials/a.vmt", "a")]);
+    assert!(rejection(Some(TOKEN), &builtin).contains("COPY"));
+    let not_allowed = job(&dir, preset(&["vrad"]), &[]);
+    assert!(rejection(Some(TOKEN), &not_allowed).contains("VRAD"));
+    let mut deactivated = preset(&["vbsp", "shutdown"]);
+    deactivated.apps[1].activated = false;
+    let escaping = job(&dir, deactivated, &[("materials/a.vmt", "a"), ("../../escaped.txt", "b")]);
+    assert!(rejection(Some(TOKEN), &escaping).contains("Invalid content path"));
+
+    assert!(!dir.join("game/custom").exists());
+    assert!(!dir.join("game/escaped.txt").exists());
+    assert!(eventually_empty(&dir.join("work")));
+}
+
+#[test]
+fn rejects_too_many_files() {
+    let dir = test_dir("file_count");
+    let addr = start_server(&dir, game(&dir));
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let hello = ClientMessage::Hello { protocol_version: PROTOCOL_VERSION, token: Some(TOKEN.to_string()) };
+    write_message(&mut stream, &hello).unwrap();
+    assert!(matches!(read_message(&mut stream).unwrap(), Some(ServerMessage::Hello(_))));
+
+    let files: Vec<UploadedFile> = (0..10_001)
+        .map(|idx| UploadedFile { path: format!("materials/{idx}.vmt"), size: 0 })
+        .collect();
+    let file_count = files.len();
+    let request = CompileRequest {
+        game: "HL2".to_string(),
+        preset: preset(&["vbsp"]),
+        map_name: "test.vmf".to_string(),
+        files,
+    };
+    write_message(&mut stream, &ClientMessage::Compile(request)).unwrap();
+    for _ in 0..file_count {
+        write_file(&mut stream, &[]).unwrap();
+    }
+
+    match read_message(&mut stream).unwrap() {
+        Some(ServerMessage::Rejected(reason)) => assert!(reason.contains("10001 files"), "{reason}"),
+        message => panic!("expected a rejection, got {message:?}"),
+    }
+    assert!(!dir.join("game/custom").exists());
+    assert!(eventually_empty(&dir.join("work")));
+}
+
+#[test]
+fn needs_a_token_off_loopback() {
+    let dir = test_dir("bind");
+    let config = ServerConfig {
+        games: Vec::new(),
+        work_dir: dir.join("work"),
+        allowed_compilers: Vec::new(),
+        token: None,
+    };
+    assert!(matches!(CompileServer::bind("0.0.0.0:0", config.clone()), Err(NetworkError::Config(_))));
+    assert!(CompileServer::bind("127.0.0.1:0", config).is_ok());
+}
@@ -0,0 +1,82 @@
+use std::{fmt, io};
+
+use serde::{Deserialize, Serialize};
+
+/// A place in the source, both counted from 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Why a VMF could not be parsed, and where.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub position: Position,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnterminatedString,
+    UnexpectedCloseBrace,
+    UnnamedBlock,
+    UnclosedBlock(String),                 // (block name)
+    MissingValue(String),                  // (key)
+    MissingKey(String, String),            // (block name, key)
+    InvalidValue(String, String, &'static str), // (key, value, expected)
+}
+
+impl ParseError {
+    pub fn new(position: Position, kind: ParseErrorKind) -> Self {
+        Self { position, kind }
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnterminatedString => write!(f, "unterminated string"),
+            Self::UnexpectedCloseBrace => write!(f, "'}}' without a block to close"),
+            Self::UnnamedBlock => write!(f, "'{{' without a block name"),
+            Self::UnclosedBlock(name) => write!(f, "block '{name}' is never closed"),
+            Self::MissingValue(key) => write!(f, "'{key}' has no value"),
+            Self::MissingKey(block, key) => write!(f, "'{block}' has no '{key}'"),
+            Self::InvalidValue(key, value, expected) => write!(f, "'{key}' is '{value}', expected {expected}"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.position, self.kind)
+    }
+}
+
+/// Error returned when reading a VMF file.
+#[derive(Debug)]
+pub enum VmfError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for VmfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read map: {e}"),
+            Self::Parse(e) => write!(f, "invalid map at {e}"),
+        }
+    }
+}
+
+impl From<ParseError> for VmfError {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
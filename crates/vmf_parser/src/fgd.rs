@@ -0,0 +1,141 @@
+//! Entity classes declared by Forge Game Data (.fgd) files, the entity definitions Hammer uses.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// How an entity class is placed in a map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassKind {
+    /// Placed at a point (`@PointClass`, `@NPCClass`, `@KeyFrameClass`...).
+    Point,
+    /// Tied to brushes (`@SolidClass`).
+    Solid,
+    /// Only inherited by other classes, never placed (`@BaseClass`).
+    Base,
+}
+
+/// Entity classes of a game, by lowercase class name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntityClasses {
+    classes: BTreeMap<String, ClassKind>,
+}
+
+impl EntityClasses {
+    /// Reads an .fgd file and the files it `@include`s, relative to its folder.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut classes = Self::default();
+        let mut loaded = Vec::new();
+        classes.load_file(path, &mut loaded)?;
+        Ok(classes)
+    }
+
+    fn load_file(&mut self, path: &Path, loaded: &mut Vec<PathBuf>) -> io::Result<()> {
+        if loaded.iter().any(|done| done == path) {
+            return Ok(());
+        }
+        loaded.push(path.to_path_buf());
+
+        let text = fs::read_to_string(path)?;
+        for include in self.add_declarations(&text) {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            self.load_file(&dir.join(include), loaded)?;
+        }
+        Ok(())
+    }
+
+    /// Parses the classes of an .fgd text, `@include`s are ignored.
+    pub fn parse(text: &str) -> Self {
+        let mut classes = Self::default();
+        classes.add_declarations(text);
+        classes
+    }
+
+    /// Adds the classes declared in `text`, returns the files it includes.
+    fn add_declarations(&mut self, text: &str) -> Vec<String> {
+        let mut includes = Vec::new();
+        let mut tokens = FgdTokens { text };
+
+        while let Some(token) = tokens.next() {
+            let Some(declaration) = token.strip_prefix('@') else { continue };
+            if declaration.eq_ignore_ascii_case("include") {
+                includes.extend(tokens.next().map(|file| file.trim_matches('"').to_string()));
+                continue;
+            }
+            if !declaration.to_ascii_lowercase().ends_with("class") {
+                continue;
+            }
+
+            let kind = match declaration.to_ascii_lowercase().as_str() {
+                "solidclass" => ClassKind::Solid,
+                "baseclass" => ClassKind::Base,
+                _ => ClassKind::Point,
+            };
+            // `@PointClass base(...) studio(...) = name : "description" [ ... ]`
+            if tokens.by_ref().take_while(|token| *token != "[").any(|token| token == "=")
+                && let Some(name) = tokens.next()
+            {
+                self.classes.insert(name.to_ascii_lowercase(), kind);
+            }
+        }
+        includes
+    }
+
+    pub fn get(&self, classname: &str) -> Option<ClassKind> {
+        self.classes.get(&classname.to_ascii_lowercase()).copied()
+    }
+
+    /// Whether the class is declared and can be placed in a map.
+    pub fn is_placeable(&self, classname: &str) -> bool {
+        self.get(classname).is_some_and(|kind| kind != ClassKind::Base)
+    }
+
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    /// Adds the classes of `other`, keeping ours when both declare one.
+    pub fn merge(&mut self, other: Self) {
+        for (name, kind) in other.classes {
+            self.classes.entry(name).or_insert(kind);
+        }
+    }
+}
+
+/// Splits an .fgd text into words, quoted strings and single punctuation, skipping comments.
+struct FgdTokens<'a> {
+    text: &'a str,
+}
+
+impl<'a> Iterator for FgdTokens<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            self.text = self.text.trim_start();
+            match self.text.strip_prefix("//") {
+                Some(comment) => self.text = comment.split_once('\n').map_or("", |(_, rest)| rest),
+                None => break,
+            }
+        }
+
+        let mut chars = self.text.char_indices();
+        let (_, first) = chars.next()?;
+        let end = match first {
+            '"' => self.text[1..].find('"').map_or(self.text.len(), |end| end + 2),
+            '[' | ']' | '(' | ')' | '=' | ':' | ',' | '+' => 1,
+            _ => self.text
+                .find(|c: char| c.is_whitespace() || "[]()=:,+\"".contains(c))
+                .unwrap_or(self.text.len()),
+        };
+        let (token, rest) = self.text.split_at(end);
+        self.text = rest;
+        Some(token)
+    }
+}
@@ -0,0 +1,176 @@
+//! The KeyValues syntax VMF files are written in: quoted keys and values, and named `{ }` blocks.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ParseError, ParseErrorKind, Position};
+
+/// A `key "value"` pair with where it was written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Property {
+    pub key: String,
+    pub value: String,
+    pub position: Position,
+}
+
+/// A named block with its properties and nested blocks, both in file order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    pub name: String,
+    pub position: Position,
+    pub properties: Vec<Property>,
+    pub children: Vec<Block>,
+}
+
+impl Block {
+    /// First property with this key. Keys are case-insensitive, like in Hammer.
+    pub fn property(&self, key: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.key.eq_ignore_ascii_case(key))
+    }
+
+    /// Value of the first property with this key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.property(key).map(|p| p.value.as_str())
+    }
+
+    /// Nested blocks with this name.
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Block> + 'a {
+        self.children.iter().filter(move |child| child.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Parses a KeyValues text into an unnamed root block holding the top-level blocks.
+pub fn parse_keyvalues(text: &str) -> Result<Block, ParseError> {
+    let mut lexer = Lexer::new(text);
+    // The open blocks, the root first
+    let mut stack = vec![Block::default()];
+
+    loop {
+        let (token, position) = lexer.next_token()?;
+        match token {
+            Token::Text(key) => {
+                let (next, next_position) = lexer.next_token()?;
+                let current = stack.last_mut().expect("the root is never popped");
+                match next {
+                    Token::Text(value) => current.properties.push(Property { key, value, position }),
+                    Token::Open => stack.push(Block { name: key, position, ..Default::default() }),
+                    Token::Close | Token::Eof => {
+                        return Err(ParseError::new(next_position, ParseErrorKind::MissingValue(key)));
+                    }
+                }
+            }
+            Token::Open => return Err(ParseError::new(position, ParseErrorKind::UnnamedBlock)),
+            Token::Close => {
+                if stack.len() == 1 {
+                    return Err(ParseError::new(position, ParseErrorKind::UnexpectedCloseBrace));
+                }
+                let block = stack.pop().expect("checked above");
+                stack.last_mut().expect("the root is never popped").children.push(block);
+            }
+            Token::Eof => {
+                let root = stack.swap_remove(0);
+                return match stack.pop() {
+                    Some(unclosed) => Err(ParseError::new(unclosed.position, ParseErrorKind::UnclosedBlock(unclosed.name))),
+                    None => Ok(root),
+                };
+            }
+        }
+    }
+}
+
+enum Token {
+    /// A quoted or bare string.
+    Text(String),
+    Open,
+    Close,
+    Eof,
+}
+
+struct Lexer<'a> {
+    text: &'a str,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(text: &'a str) -> Self {
+        // Some tools save with a byte order mark
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        Self { text, offset: 0, line: 1, column: 1 }
+    }
+
+    fn position(&self) -> Position {
+        Position { line: self.line, column: self.column }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset).copied()
+    }
+
+    fn bump(&mut self) {
+        let byte = self.text.as_bytes()[self.offset];
+        self.offset += 1;
+        if byte == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else if byte & 0xC0 != 0x80 {
+            // Continuation bytes are part of the previous character
+            self.column += 1;
+        }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(byte) = self.peek() {
+            if byte.is_ascii_whitespace() {
+                self.bump();
+            } else if self.text[self.offset..].starts_with("//") {
+                while self.peek().is_some_and(|byte| byte != b'\n') {
+                    self.bump();
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<(Token, Position), ParseError> {
+        self.skip_whitespace_and_comments();
+        let position = self.position();
+        let Some(byte) = self.peek() else {
+            return Ok((Token::Eof, position));
+        };
+
+        let token = match byte {
+            b'{' => {
+                self.bump();
+                Token::Open
+            }
+            b'}' => {
+                self.bump();
+                Token::Close
+            }
+            b'"' => {
+                self.bump();
+                let start = self.offset;
+                // Hammer has no escapes, a value ends at the next quote
+                while self.peek().is_some_and(|byte| byte != b'"') {
+                    self.bump();
+                }
+                if self.peek().is_none() {
+                    return Err(ParseError::new(position, ParseErrorKind::UnterminatedString));
+                }
+                let text = self.text[start..self.offset].to_string();
+                self.bump();
+                Token::Text(text)
+            }
+            _ => {
+                let start = self.offset;
+                while self.peek().is_some_and(|byte| !byte.is_ascii_whitespace() && !matches!(byte, b'{' | b'}' | b'"')) {
+                    self.bump();
+                }
+                Token::Text(self.text[start..self.offset].to_string())
+            }
+        };
+        Ok((token, position))
+    }
+}
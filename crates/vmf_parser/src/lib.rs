@@ -0,0 +1,22 @@
+//! Parsing of Valve Map Format (.vmf) sources into a typed tree, for inspecting maps before they are compiled.
+//!
+//! [`parse_keyvalues`] reads the generic KeyValues structure, [`Vmf`] types the blocks Hammer writes.
+//! Errors carry the line and column they were found at. [`EntityClasses`] reads the entity classes a game declares in its FGDs.
+
+mod error;
+pub use error::{ParseError, ParseErrorKind, Position, VmfError};
+
+mod fgd;
+pub use fgd::{ClassKind, EntityClasses};
+
+mod keyvalues;
+pub use keyvalues::{parse_keyvalues, Block, Property};
+
+mod stats;
+pub use stats::MapStats;
+
+mod vmf;
+pub use vmf::{
+    Camera, Cameras, Connection, Displacement, Editor, Entity, Group, Side, Solid, TextureAxis, Vec3, VersionInfo,
+    VisGroup, Vmf, World,
+};
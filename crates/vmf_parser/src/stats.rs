@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::vmf::{Vmf, VisGroup};
+
+/// Counts describing the size and content of a map.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MapStats {
+    /// Solids of the world and of brush entities.
+    pub solids: usize,
+    pub world_solids: usize,
+    pub sides: usize,
+    pub displacements: usize,
+    pub point_entities: usize,
+    pub brush_entities: usize,
+    pub connections: usize,
+    /// Visgroups, nested ones included.
+    pub visgroups: usize,
+    /// Uses of each material, named uppercase like Hammer writes them.
+    pub materials: BTreeMap<String, usize>,
+    /// Number of entities of each class, lowercase.
+    pub entity_classes: BTreeMap<String, usize>,
+}
+
+impl MapStats {
+    pub fn entities(&self) -> usize {
+        self.point_entities + self.brush_entities
+    }
+}
+
+impl Vmf {
+    /// Counts the brushes, entities and materials of the map.
+    pub fn stats(&self) -> MapStats {
+        let mut stats = MapStats {
+            world_solids: self.world.solids.len(),
+            visgroups: count_visgroups(&self.visgroups),
+            ..Default::default()
+        };
+
+        for solid in self.all_solids() {
+            stats.solids += 1;
+            for side in &solid.sides {
+                stats.sides += 1;
+                stats.displacements += usize::from(side.displacement.is_some());
+                *stats.materials.entry(side.material.to_ascii_uppercase()).or_default() += 1;
+            }
+        }
+        for entity in &self.entities {
+            if entity.is_brush_entity() {
+                stats.brush_entities += 1;
+            } else {
+                stats.point_entities += 1;
+            }
+            stats.connections += entity.connections.len();
+            *stats.entity_classes.entry(entity.classname.to_ascii_lowercase()).or_default() += 1;
+        }
+        stats
+    }
+}
+
+fn count_visgroups(visgroups: &[VisGroup]) -> usize {
+    visgroups.iter().map(|visgroup| 1 + count_visgroups(&visgroup.children)).sum()
+}
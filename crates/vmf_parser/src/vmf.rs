@@ -0,0 +1,447 @@
+use std::{fs, path::Path, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{ParseError, ParseErrorKind, VmfError},
+    keyvalues::{parse_keyvalues, Block, Property},
+};
+
+/// A point or direction in map space.
+pub type Vec3 = [f64; 3];
+
+/// A parsed Valve Map Format file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Vmf {
+    pub version_info: VersionInfo,
+    pub visgroups: Vec<VisGroup>,
+    pub world: World,
+    /// Entities other than `worldspawn`, hidden ones included.
+    pub entities: Vec<Entity>,
+    pub cameras: Cameras,
+    /// Top-level blocks without a typed form (`viewsettings`, `cordons`, editor plugin data...).
+    pub other: Vec<Block>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub editor_version: u32,
+    pub editor_build: u32,
+    pub map_version: u32,
+    pub format_version: u32,
+    pub prefab: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VisGroup {
+    pub id: u32,
+    pub name: String,
+    pub color: Option<[u8; 3]>,
+    pub children: Vec<VisGroup>,
+}
+
+/// The `worldspawn` entity and its brushes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct World {
+    pub id: u32,
+    pub map_version: u32,
+    pub skyname: Option<String>,
+    /// Every key of the world, in file order.
+    pub properties: Vec<(String, String)>,
+    pub solids: Vec<Solid>,
+    /// Hammer groups, referenced by [`Editor::group_id`].
+    pub groups: Vec<Group>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Group {
+    pub id: u32,
+    pub editor: Editor,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Solid {
+    pub id: u32,
+    pub sides: Vec<Side>,
+    pub editor: Editor,
+    /// Hidden in Hammer, it is still compiled.
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Side {
+    pub id: u32,
+    /// Three points of the face plane, clockwise seen from outside the brush.
+    pub plane: [Vec3; 3],
+    pub material: String,
+    pub u_axis: TextureAxis,
+    pub v_axis: TextureAxis,
+    pub rotation: f64,
+    pub lightmap_scale: u32,
+    pub smoothing_groups: u32,
+    pub displacement: Option<Displacement>,
+}
+
+/// A `[x y z shift] scale` texture axis.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextureAxis {
+    pub axis: Vec3,
+    pub shift: f64,
+    pub scale: f64,
+}
+
+/// The `dispinfo` of a side. Per-vertex rows are not kept.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Displacement {
+    pub power: u32,
+    pub start_position: Vec3,
+    pub elevation: f64,
+    pub subdivided: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    pub id: u32,
+    pub classname: String,
+    pub origin: Option<Vec3>,
+    /// Every key of the entity, in file order.
+    pub properties: Vec<(String, String)>,
+    pub connections: Vec<Connection>,
+    /// Brushes of a brush entity, empty for point entities.
+    pub solids: Vec<Solid>,
+    pub editor: Editor,
+    pub hidden: bool,
+}
+
+impl Entity {
+    /// Value of a key. Keys are case-insensitive, like in Hammer.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.properties.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v.as_str())
+    }
+
+    pub fn targetname(&self) -> Option<&str> {
+        self.get("targetname")
+    }
+
+    pub fn is_brush_entity(&self) -> bool {
+        !self.solids.is_empty()
+    }
+}
+
+/// An output of an entity: `output "target,input,parameter,delay,times"`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Connection {
+    pub output: String,
+    pub target: String,
+    pub input: String,
+    pub parameter: String,
+    pub delay: f64,
+    /// `-1` fires every time.
+    pub times_to_fire: i32,
+}
+
+/// Editor state of a solid, entity or group.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Editor {
+    pub color: Option<[u8; 3]>,
+    pub visgroup_ids: Vec<u32>,
+    pub group_id: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cameras {
+    /// Index in `cameras`, `None` when no camera is active.
+    pub active: Option<usize>,
+    pub cameras: Vec<Camera>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Camera {
+    pub position: Vec3,
+    pub look: Vec3,
+}
+
+impl FromStr for Vmf {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
+
+impl Vmf {
+    /// Parses the contents of a `.vmf` file.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let root = parse_keyvalues(text)?;
+        let mut vmf = Self::default();
+
+        for block in root.children {
+            match block.name.to_ascii_lowercase().as_str() {
+                "versioninfo" => vmf.version_info = parse_version_info(&block)?,
+                "visgroups" => vmf.visgroups = parse_visgroups(&block)?,
+                "world" => vmf.world = parse_world(&block)?,
+                "entity" => vmf.entities.push(parse_entity(&block, false)?),
+                "hidden" => {
+                    for entity in block.children_named("entity") {
+                        vmf.entities.push(parse_entity(entity, true)?);
+                    }
+                }
+                "cameras" => vmf.cameras = parse_cameras(&block)?,
+                _ => vmf.other.push(block),
+            }
+        }
+        Ok(vmf)
+    }
+
+    /// Reads and parses a `.vmf` file.
+    pub fn load(path: &Path) -> Result<Self, VmfError> {
+        let text = fs::read_to_string(path).map_err(VmfError::Io)?;
+        Ok(Self::parse(&text)?)
+    }
+
+    /// Solids of the world and of brush entities.
+    pub fn all_solids(&self) -> impl Iterator<Item = &Solid> {
+        self.world.solids.iter().chain(self.entities.iter().flat_map(|entity| &entity.solids))
+    }
+
+    /// Entities of a class, compared case-insensitively.
+    pub fn entities_by_class<'a>(&'a self, classname: &'a str) -> impl Iterator<Item = &'a Entity> + 'a {
+        self.entities.iter().filter(move |entity| entity.classname.eq_ignore_ascii_case(classname))
+    }
+}
+
+fn parse_version_info(block: &Block) -> Result<VersionInfo, ParseError> {
+    Ok(VersionInfo {
+        editor_version: optional(block, "editorversion")?.unwrap_or_default(),
+        editor_build: optional(block, "editorbuild")?.unwrap_or_default(),
+        map_version: optional(block, "mapversion")?.unwrap_or_default(),
+        format_version: optional(block, "formatversion")?.unwrap_or_default(),
+        prefab: optional_with(block, "prefab", parse_bool)?.unwrap_or_default(),
+    })
+}
+
+fn parse_visgroups(block: &Block) -> Result<Vec<VisGroup>, ParseError> {
+    block.children_named("visgroup")
+        .map(|visgroup| {
+            Ok(VisGroup {
+                id: required(visgroup, "visgroupid")?,
+                name: visgroup.get("name").unwrap_or_default().to_string(),
+                color: optional_with(visgroup, "color", parse_color)?,
+                children: parse_visgroups(visgroup)?,
+            })
+        })
+        .collect()
+}
+
+fn parse_world(block: &Block) -> Result<World, ParseError> {
+    let mut world = World {
+        id: optional(block, "id")?.unwrap_or_default(),
+        map_version: optional(block, "mapversion")?.unwrap_or_default(),
+        skyname: block.get("skyname").map(str::to_string),
+        properties: key_values(block),
+        ..Default::default()
+    };
+    world.solids = parse_solids(block)?;
+    for group in block.children_named("group") {
+        world.groups.push(Group {
+            id: optional(group, "id")?.unwrap_or_default(),
+            editor: parse_editor(group)?,
+        });
+    }
+    Ok(world)
+}
+
+/// Solids of a world or entity, the ones in `hidden` blocks included.
+fn parse_solids(block: &Block) -> Result<Vec<Solid>, ParseError> {
+    let visible = block.children_named("solid").map(|solid| parse_solid(solid, false));
+    let hidden = block.children_named("hidden")
+        .flat_map(|hidden| hidden.children_named("solid"))
+        .map(|solid| parse_solid(solid, true));
+    visible.chain(hidden).collect()
+}
+
+fn parse_solid(block: &Block, hidden: bool) -> Result<Solid, ParseError> {
+    Ok(Solid {
+        id: optional(block, "id")?.unwrap_or_default(),
+        sides: block.children_named("side").map(parse_side).collect::<Result<_, _>>()?,
+        editor: parse_editor(block)?,
+        hidden,
+    })
+}
+
+fn parse_side(block: &Block) -> Result<Side, ParseError> {
+    Ok(Side {
+        id: optional(block, "id")?.unwrap_or_default(),
+        plane: required_with(block, "plane", parse_plane)?,
+        material: required_property(block, "material")?.value.clone(),
+        u_axis: optional_with(block, "uaxis", parse_texture_axis)?.unwrap_or_default(),
+        v_axis: optional_with(block, "vaxis", parse_texture_axis)?.unwrap_or_default(),
+        rotation: optional(block, "rotation")?.unwrap_or_default(),
+        lightmap_scale: optional(block, "lightmapscale")?.unwrap_or(16),
+        smoothing_groups: optional(block, "smoothing_groups")?.unwrap_or_default(),
+        displacement: block.children_named("dispinfo").next().map(parse_displacement).transpose()?,
+    })
+}
+
+fn parse_displacement(block: &Block) -> Result<Displacement, ParseError> {
+    Ok(Displacement {
+        power: required(block, "power")?,
+        start_position: required_with(block, "startposition", parse_vec3)?,
+        elevation: optional(block, "elevation")?.unwrap_or_default(),
+        subdivided: optional_with(block, "subdiv", parse_bool)?.unwrap_or_default(),
+    })
+}
+
+fn parse_entity(block: &Block, hidden: bool) -> Result<Entity, ParseError> {
+    let connections = match block.children_named("connections").next() {
+        Some(connections) => connections.properties.iter().map(parse_connection).collect::<Result<_, _>>()?,
+        None => Vec::new(),
+    };
+    Ok(Entity {
+        id: optional(block, "id")?.unwrap_or_default(),
+        classname: required_property(block, "classname")?.value.clone(),
+        origin: optional_with(block, "origin", parse_vec3)?,
+        properties: key_values(block),
+        connections,
+        solids: parse_solids(block)?,
+        editor: parse_editor(block)?,
+        hidden,
+    })
+}
+
+fn parse_connection(property: &Property) -> Result<Connection, ParseError> {
+    let invalid = || invalid_value(property, "'target,input,parameter,delay,times'");
+    // Newer Hammer versions separate the fields with ESC, older ones with commas
+    let separator = if property.value.contains('\x1b') { '\x1b' } else { ',' };
+    let fields: Vec<&str> = property.value.split(separator).collect();
+    let [target, input, parameter, delay, times] = fields[..] else {
+        return Err(invalid());
+    };
+    Ok(Connection {
+        output: property.key.clone(),
+        target: target.to_string(),
+        input: input.to_string(),
+        parameter: parameter.to_string(),
+        delay: delay.trim().parse().map_err(|_| invalid())?,
+        times_to_fire: times.trim().parse().map_err(|_| invalid())?,
+    })
+}
+
+fn parse_editor(block: &Block) -> Result<Editor, ParseError> {
+    let Some(editor) = block.children_named("editor").next() else {
+        return Ok(Editor::default());
+    };
+    let visgroup_ids = editor.properties.iter()
+        .filter(|p| p.key.eq_ignore_ascii_case("visgroupid"))
+        .map(|p| p.value.trim().parse().map_err(|_| invalid_value(p, "an id")))
+        .collect::<Result<_, _>>()?;
+    Ok(Editor {
+        color: optional_with(editor, "color", parse_color)?,
+        visgroup_ids,
+        group_id: optional(editor, "groupid")?,
+    })
+}
+
+fn parse_cameras(block: &Block) -> Result<Cameras, ParseError> {
+    let cameras = block.children_named("camera")
+        .map(|camera| {
+            Ok(Camera {
+                position: required_with(camera, "position", parse_vec3)?,
+                look: required_with(camera, "look", parse_vec3)?,
+            })
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+    let active: i64 = optional(block, "activecamera")?.unwrap_or(-1);
+    Ok(Cameras {
+        active: usize::try_from(active).ok().filter(|idx| *idx < cameras.len()),
+        cameras,
+    })
+}
+
+fn key_values(block: &Block) -> Vec<(String, String)> {
+    block.properties.iter().map(|p| (p.key.clone(), p.value.clone())).collect()
+}
+
+fn invalid_value(property: &Property, expected: &'static str) -> ParseError {
+    ParseError::new(
+        property.position,
+        ParseErrorKind::InvalidValue(property.key.clone(), property.value.clone(), expected),
+    )
+}
+
+fn required_property<'a>(block: &'a Block, key: &str) -> Result<&'a Property, ParseError> {
+    block.property(key)
+        .ok_or_else(|| ParseError::new(block.position, ParseErrorKind::MissingKey(block.name.clone(), key.to_string())))
+}
+
+fn optional_with<T: Expected>(block: &Block, key: &str, parse: fn(&str) -> Option<T>) -> Result<Option<T>, ParseError> {
+    block.property(key)
+        .map(|property| parse(&property.value).ok_or_else(|| invalid_value(property, T::EXPECTED)))
+        .transpose()
+}
+
+fn required_with<T: Expected>(block: &Block, key: &str, parse: fn(&str) -> Option<T>) -> Result<T, ParseError> {
+    let property = required_property(block, key)?;
+    parse(&property.value).ok_or_else(|| invalid_value(property, T::EXPECTED))
+}
+
+fn optional<T: FromStr + Expected>(block: &Block, key: &str) -> Result<Option<T>, ParseError> {
+    optional_with(block, key, |value| value.trim().parse().ok())
+}
+
+fn required<T: FromStr + Expected>(block: &Block, key: &str) -> Result<T, ParseError> {
+    required_with(block, key, |value| value.trim().parse().ok())
+}
+
+/// What a value was expected to be, for error messages.
+trait Expected {
+    const EXPECTED: &'static str;
+}
+
+impl Expected for u32 { const EXPECTED: &'static str = "a positive integer"; }
+impl Expected for i64 { const EXPECTED: &'static str = "an integer"; }
+impl Expected for f64 { const EXPECTED: &'static str = "a number"; }
+impl Expected for bool { const EXPECTED: &'static str = "0 or 1"; }
+impl Expected for Vec3 { const EXPECTED: &'static str = "three numbers"; }
+impl Expected for [Vec3; 3] { const EXPECTED: &'static str = "'(x y z) (x y z) (x y z)'"; }
+impl Expected for [u8; 3] { const EXPECTED: &'static str = "'r g b'"; }
+impl Expected for TextureAxis { const EXPECTED: &'static str = "'[x y z shift] scale'"; }
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim() {
+        "1" => Some(true),
+        "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_numbers<const N: usize, T: FromStr>(value: &str) -> Option<[T; N]> {
+    let numbers: Vec<T> = value.split_whitespace().map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    numbers.try_into().ok()
+}
+
+/// `x y z`, also accepted between brackets like camera positions: `[x y z]`.
+fn parse_vec3(value: &str) -> Option<Vec3> {
+    let value = value.trim();
+    let value = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')).unwrap_or(value);
+    parse_numbers(value)
+}
+
+fn parse_color(value: &str) -> Option<[u8; 3]> {
+    parse_numbers(value)
+}
+
+fn parse_plane(value: &str) -> Option<[Vec3; 3]> {
+    let points: Vec<Vec3> = value.split(')')
+        .map(str::trim)
+        .filter(|point| !point.is_empty())
+        .map(|point| parse_numbers(point.strip_prefix('(')?))
+        .collect::<Option<_>>()?;
+    points.try_into().ok()
+}
+
+fn parse_texture_axis(value: &str) -> Option<TextureAxis> {
+    let (axis, scale) = value.trim().strip_prefix('[')?.split_once(']')?;
+    let [x, y, z, shift] = parse_numbers(axis)?;
+    Some(TextureAxis { axis: [x, y, z], shift, scale: scale.trim().parse().ok()? })
+}
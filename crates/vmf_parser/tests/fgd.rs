@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+use vmf_parser::{ClassKind, EntityClasses};
+
+fn sample(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fgd").join(name)
+}
+
+#[test]
+fn reads_classes_and_includes() {
+    let classes = EntityClasses::load(&sample("game.fgd")).expect("game.fgd loads");
+    assert_eq!(classes.get("info_player_start"), Some(ClassKind::Point));
+    assert_eq!(classes.get("npc_test"), Some(ClassKind::Point));
+    assert_eq!(classes.get("FUNC_DETAIL"), Some(ClassKind::Solid));
+    // Declared in the included file
+    assert_eq!(classes.get("light"), Some(ClassKind::Point));
+    assert_eq!(classes.get("worldspawn"), Some(ClassKind::Solid));
+    assert_eq!(classes.get("Targetname"), Some(ClassKind::Base));
+    assert!(!classes.is_placeable("targetname"));
+    assert!(classes.is_placeable("light_environment"));
+    assert_eq!(classes.get("prop_static"), None);
+    assert_eq!(classes.len(), 10);
+}
+
+#[test]
+fn parse_ignores_includes() {
+    let classes = EntityClasses::parse(&std::fs::read_to_string(sample("game.fgd")).unwrap());
+    assert_eq!(classes.get("light"), None);
+    assert_eq!(classes.get("trigger_once"), Some(ClassKind::Solid));
+}
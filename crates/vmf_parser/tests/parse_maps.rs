@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+use vmf_parser::{parse_keyvalues, ParseErrorKind, Position, Vmf, VmfError};
+
+fn sample(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/maps").join(name)
+}
+
+fn load_room() -> Vmf {
+    Vmf::load(&sample("room.vmf")).expect("room.vmf parses")
+}
+
+#[test]
+fn reads_version_info_and_world() {
+    let vmf = load_room();
+    assert_eq!(vmf.version_info.editor_version, 400);
+    assert_eq!(vmf.version_info.editor_build, 8864);
+    assert_eq!(vmf.version_info.map_version, 12);
+    assert!(!vmf.version_info.prefab);
+
+    assert_eq!(vmf.world.id, 1);
+    assert_eq!(vmf.world.skyname.as_deref(), Some("sky_day01_01"));
+    assert!(vmf.world.properties.contains(&("detailvbsp".to_string(), "detail.vbsp".to_string())));
+    assert_eq!(vmf.world.groups.len(), 1);
+    assert_eq!(vmf.world.groups[0].id, 11);
+}
+
+#[test]
+fn reads_solids_and_sides() {
+    let vmf = load_room();
+    // Three visible brushes then the hidden one
+    assert_eq!(vmf.world.solids.len(), 4);
+    assert!(vmf.world.solids[3].hidden);
+    assert!(vmf.world.solids[..3].iter().all(|solid| !solid.hidden));
+
+    let floor = &vmf.world.solids[0];
+    assert_eq!(floor.id, 2);
+    assert_eq!(floor.sides.len(), 6);
+    assert_eq!(floor.editor.visgroup_ids, vec![7]);
+    assert_eq!(floor.editor.color, Some([0, 149, 182]));
+
+    let top = &floor.sides[0];
+    assert_eq!(top.material, "DEV/DEV_MEASUREGENERIC01B");
+    assert_eq!(top.plane, [[-512.0, 512.0, 0.0], [512.0, 512.0, 0.0], [512.0, -512.0, 0.0]]);
+    assert_eq!(top.u_axis.axis, [1.0, 0.0, 0.0]);
+    assert_eq!(top.u_axis.scale, 0.25);
+    assert_eq!(top.v_axis.axis, [0.0, -1.0, 0.0]);
+    assert_eq!(top.lightmap_scale, 16);
+}
+
+#[test]
+fn reads_displacements() {
+    let vmf = load_room();
+    let wall = &vmf.world.solids[2];
+    let displacement = wall.sides[0].displacement.as_ref().expect("the wall top is a displacement");
+    assert_eq!(displacement.power, 3);
+    assert_eq!(displacement.start_position, [-512.0, -576.0, 256.0]);
+    assert!(wall.sides[1..].iter().all(|side| side.displacement.is_none()));
+}
+
+#[test]
+fn reads_entities_and_connections() {
+    let vmf = load_room();
+    assert_eq!(vmf.entities.len(), 5);
+
+    let light = vmf.entities_by_class("light").next().expect("a light");
+    assert_eq!(light.targetname(), Some("room_light"));
+    assert_eq!(light.origin, Some([0.0, 0.0, 192.0]));
+    assert_eq!(light.get("_LIGHT"), Some("255 255 255 200"));
+
+    let logic_auto = vmf.entities_by_class("logic_auto").next().expect("a logic_auto");
+    assert_eq!(logic_auto.connections.len(), 2);
+    let comma = &logic_auto.connections[0];
+    assert_eq!((comma.output.as_str(), comma.target.as_str(), comma.input.as_str()), ("OnMapSpawn", "room_light", "TurnOn"));
+    assert_eq!(comma.times_to_fire, -1);
+    let escape = &logic_auto.connections[1];
+    assert_eq!(escape.input, "TurnOff");
+    assert_eq!(escape.delay, 2.5);
+    assert_eq!(escape.times_to_fire, 1);
+
+    let detail = vmf.entities_by_class("func_detail").next().expect("a func_detail");
+    assert!(detail.is_brush_entity());
+    assert_eq!(detail.origin, None);
+    assert_eq!(detail.solids[0].editor.group_id, Some(11));
+
+    let prop = vmf.entities_by_class("prop_static").next().expect("the hidden prop");
+    assert!(prop.hidden);
+    assert_eq!(prop.get("model"), Some("models/props/de_dust/du_crate_64x64.mdl"));
+}
+
+#[test]
+fn reads_visgroups_and_cameras() {
+    let vmf = load_room();
+    assert_eq!(vmf.visgroups.len(), 1);
+    assert_eq!(vmf.visgroups[0].name, "Geometry");
+    assert_eq!(vmf.visgroups[0].children[0].id, 8);
+    assert_eq!(vmf.visgroups[0].children[0].color, Some([100, 200, 50]));
+
+    assert_eq!(vmf.cameras.active, Some(0));
+    assert_eq!(vmf.cameras.cameras[0].position, [-400.0, -400.0, 200.0]);
+    assert_eq!(vmf.cameras.cameras[0].look, [0.0, 0.0, 64.0]);
+
+    let other: Vec<&str> = vmf.other.iter().map(|block| block.name.as_str()).collect();
+    assert_eq!(other, ["viewsettings", "cordons"]);
+}
+
+#[test]
+fn counts_map_stats() {
+    let stats = load_room().stats();
+    assert_eq!(stats.solids, 5);
+    assert_eq!(stats.world_solids, 4);
+    assert_eq!(stats.sides, 30);
+    assert_eq!(stats.displacements, 1);
+    assert_eq!(stats.point_entities, 4);
+    assert_eq!(stats.brush_entities, 1);
+    assert_eq!(stats.entities(), 5);
+    assert_eq!(stats.connections, 2);
+    assert_eq!(stats.visgroups, 2);
+    assert_eq!(stats.materials["TOOLS/TOOLSNODRAW"], 25);
+    assert_eq!(stats.entity_classes["light"], 1);
+}
+
+#[test]
+fn reports_unclosed_block_position() {
+    let Err(VmfError::Parse(error)) = Vmf::load(&sample("broken_unclosed.vmf")) else {
+        panic!("broken_unclosed.vmf must not parse");
+    };
+    assert_eq!(error.kind, ParseErrorKind::UnclosedBlock("world".to_string()));
+    assert_eq!(error.position, Position { line: 5, column: 1 });
+}
+
+#[test]
+fn reports_invalid_value_position() {
+    let Err(VmfError::Parse(error)) = Vmf::load(&sample("broken_plane.vmf")) else {
+        panic!("broken_plane.vmf must not parse");
+    };
+    assert!(matches!(&error.kind, ParseErrorKind::InvalidValue(key, _, _) if key == "plane"));
+    assert_eq!(error.position, Position { line: 11, column: 4 });
+}
+
+#[test]
+fn reports_syntax_errors() {
+    let error = parse_keyvalues("world\n{\n\t\"id\" \"1\n}").unwrap_err();
+    assert_eq!(error.kind, ParseErrorKind::UnterminatedString);
+    assert_eq!(error.position, Position { line: 3, column: 7 });
+
+    let error = parse_keyvalues("world\n{\n}\n}").unwrap_err();
+    assert_eq!(error.kind, ParseErrorKind::UnexpectedCloseBrace);
+    assert_eq!(error.position.line, 4);
+
+    let error = parse_keyvalues("world\n{\n\t\"classname\"\n}").unwrap_err();
+    assert_eq!(error.kind, ParseErrorKind::MissingValue("classname".to_string()));
+
+    let error = Vmf::parse("entity\n{\n\t\"id\" \"2\"\n}").unwrap_err();
+    assert_eq!(error.kind, ParseErrorKind::MissingKey("entity".to_string(), "classname".to_string()));
+}
+
+#[test]
+fn skips_comments_and_bare_tokens() {
+    let root = parse_keyvalues("// header\nworld // trailing\n{\n\tid 1 // a comment\n\tclassname worldspawn\n}\n").unwrap();
+    let world = &root.children[0];
+    assert_eq!(world.get("id"), Some("1"));
+    assert_eq!(world.get("classname"), Some("worldspawn"));
+}
+
+#[test]
+fn parses_large_maps() {
+    let brush = std::fs::read_to_string(sample("room.vmf")).unwrap();
+    let start = brush.find("\tsolid").unwrap();
+    let end = brush.find("\tsolid\r\n\t{\r\n\t\t\"id\" \"3\"").unwrap();
+    let solid = &brush[start..end];
+
+    let mut text = String::from("world\n{\n\t\"id\" \"1\"\n\t\"classname\" \"worldspawn\"\n");
+    for _ in 0..5_000 {
+        text.push_str(solid);
+    }
+    text.push_str("}\n");
+
+    let vmf = Vmf::parse(&text).unwrap();
+    assert_eq!(vmf.world.solids.len(), 5_000);
+    assert_eq!(vmf.stats().sides, 30_000);
+}
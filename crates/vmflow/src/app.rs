@@ -1,27 +1,41 @@
-use vmflow_config_types::VmfMap;
+use vmflow_config_types::{migration, VmfMap, CONFIG_APP_NAME, CONFIG_NAME, COMPILE_HISTORY_FILE, FINGERPRINTS_FILE, LIMIT_BUDGETS_FILE, USER_COMPILERS_DIR};
 use serde::{de, Deserialize, Serialize};
 
 use crate::settings::AppSettings;
 use crate::ui;
 use std::path::{Path, PathBuf};
-use std::sync;
+use std::sync::{self, Arc};
 use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+/// How often the user compiler configs are checked for changes.
+const COMPILERS_RELOAD_INTERVAL: Duration = Duration::from_secs(1);
 
 // Error scan and info about them
 // Automatic creation of particle manifests for optimization and correct operation of particles on the map
 // Automatic detection and packaging of additional files such as NAV, RADAR, Soundscapes, Detail VBSP, etc.
-// !preservation of original compiler indexes, validation and correlation when changing
 
 #[derive(Default)]
 pub struct VmFlowApp {
     pub settings: AppSettings,
     pub maps: Vec<VmfMap>,
     pub compile_session: Option<compilation_core::CompilationSession>,
+    /// Events of the running compile, read by the compile window.
+    pub backend_rx: Option<Receiver<compilation_core::CoreEvent>>,
+    /// Compile the maps unchanged since their last successful compile too, for the next compile only.
+    pub force_rebuild: bool,
+
+    /// Folder of the user compiler configs, watched for changes.
+    pub user_compilers_dir: Option<PathBuf>,
+    /// Errors of the last load of the user compiler configs.
+    pub compiler_config_errors: Vec<compilers_service::ConfigLoadError>,
+    last_compilers_check: Option<Instant>,
 
     // additionals windows
     pub settings_window: ui::settings::SettingsWindow,
     pub presets_window: ui::presets::PresetEditorWindow,
     pub compile_window: ui::compile_info::CompileWindow,
+    pub history_window: ui::history::HistoryWindow,
 
 
     #[cfg(debug_assertions)]
@@ -30,6 +44,7 @@ pub struct VmFlowApp {
 
 impl eframe::App for VmFlowApp {
     fn update(&mut self, ctx: &eframe::egui::Context, frame: &mut eframe::Frame) {
+        self.reload_user_compilers(ctx);
         ui::build_ui(ctx, self);
 
         if ctx.input(|i| i.viewport().close_requested()) {
@@ -42,34 +57,120 @@ impl eframe::App for VmFlowApp {
 
 impl VmFlowApp {
     pub fn new() -> Self {
-        let settings = confy::load("VMFlow_wrapper", "config").unwrap_or_default();
+        let mut settings: AppSettings = confy::load(CONFIG_APP_NAME, CONFIG_NAME).unwrap_or_default();
+        let user_compilers_dir = confy::get_configuration_file_path(CONFIG_APP_NAME, CONFIG_NAME)
+            .ok()
+            .and_then(|path| Some(path.parent()?.join(USER_COMPILERS_DIR)));
+        let compiler_config_errors = user_compilers_dir.as_deref()
+            .map(compilers_service::load_user_configs)
+            .unwrap_or_default();
+
+        // Saved positions refer to the list with user compilers, so they are loaded first
+        let unmapped = migration::migrate_settings(&mut settings.compile_presets, &mut settings.games);
+        if !unmapped.is_empty() {
+            unmapped.iter().for_each(|message| log::warn!("Settings migration: {message}"));
+            rfd::MessageDialog::new()
+                .set_title("Some settings could not be migrated")
+                .set_description(format!(
+                    "Compilers and parameters are now saved by id. These could not be converted:\n\n{}",
+                    unmapped.join("\n")
+                ))
+                .set_level(rfd::MessageLevel::Warning)
+                .show();
+        }
+
         Self {
             settings,
+            user_compilers_dir,
+            compiler_config_errors,
+            last_compilers_check: Some(Instant::now()),
             ..Default::default()
         }
     }
 
+    /// Reloads the user compiler configs when their files changed, checking every
+    /// [`COMPILERS_RELOAD_INTERVAL`] while the preset editor is open.
+    ///
+    /// Otherwise nothing shows the compilers, they are checked again before a compile.
+    fn reload_user_compilers(&mut self, ctx: &eframe::egui::Context) {
+        if !self.presets_window.is_open || self.user_compilers_dir.is_none() {
+            return;
+        }
+        // Keeps checking while the app is idle
+        ctx.request_repaint_after(COMPILERS_RELOAD_INTERVAL);
+        if self.last_compilers_check.is_some_and(|checked| checked.elapsed() < COMPILERS_RELOAD_INTERVAL) {
+            return;
+        }
+        self.check_user_compilers();
+    }
+
+    /// Reloads the user compiler configs if their files changed.
+    fn check_user_compilers(&mut self) {
+        let Some(dir) = &self.user_compilers_dir else {
+            return;
+        };
+        self.last_compilers_check = Some(Instant::now());
+
+        if let Some(errors) = compilers_service::reload_user_configs_if_changed(dir) {
+            log::info!("User compiler configs reloaded from {}", dir.display());
+            self.compiler_config_errors = errors;
+        }
+    }
+
     pub fn save_config(&self) -> Result<(), confy::ConfyError> {
         println!("INFO: Saving data...");
-        confy::store("VMFlow_wrapper", "config", &self.settings)
+        confy::store(CONFIG_APP_NAME, CONFIG_NAME, &self.settings)
     }
 
-    pub fn start_compile(&mut self) {
+    pub fn start_compile(&mut self, ctx: &eframe::egui::Context) {
         self.save_config();
+        self.check_user_compilers();
 
-        self.compile_window.logs.clear();
-        self.compile_window.start_time = std::time::Instant::now();
+        self.compile_window.reset();
 
         // TODO!: remove cloning, now only for test
         let preset = self.settings.current_preset().unwrap().clone();
         let game = self.settings.current_game().unwrap().clone();
         let maps = self.maps.clone();
 
-        // let (tx, rx) = sync::mpsc::channel();
-        let session = compilation_core::CompilationSession::new(preset, game, 1, None);
+        let (tx, rx) = sync::mpsc::channel();
+        let event_handler = Arc::new(ui::compile_info::ChannelEventHandler::new(tx, ctx.clone()));
+        let mut session = compilation_core::CompilationSession::new(preset, game, 1, Some(event_handler));
+        if let Some(history) = self.data_file_path(LIMIT_BUDGETS_FILE) {
+            session = session.with_budget_history(history);
+        }
+        if let Some(history) = self.data_file_path(COMPILE_HISTORY_FILE) {
+            session = session.with_compile_history(history);
+        }
+        if let Some(fingerprints) = self.data_file_path(FINGERPRINTS_FILE) {
+            session = session.with_fingerprints(fingerprints).with_force_rebuild(self.force_rebuild);
+        }
+        if let Some(settings_dir) = self.settings_dir() {
+            session = session.with_log_files(self.settings.logs.resolve_dir(&settings_dir), self.settings.logs.keep_batches);
+        }
         session.start_batch(maps);
+        self.force_rebuild = false;
         self.compile_session = Some(session);
-        // compilation_core::start_compilation_thread(tx, preset, game, maps, cancel_flag);
+        self.backend_rx = Some(rx);
+    }
+
+    /// Folder of the settings file, the data files of the app are kept in it.
+    pub fn settings_dir(&self) -> Option<PathBuf> {
+        confy::get_configuration_file_path(CONFIG_APP_NAME, CONFIG_NAME)
+            .ok()
+            .and_then(|path| Some(path.parent()?.to_path_buf()))
+    }
+
+    /// File named `name` next to the settings file.
+    pub fn data_file_path(&self, name: &str) -> Option<PathBuf> {
+        self.settings_dir().map(|dir| dir.join(name))
+    }
+
+    /// Opens the compile history window with the compiles recorded so far.
+    pub fn open_history(&mut self) {
+        let path = self.data_file_path(COMPILE_HISTORY_FILE);
+        self.history_window.reload(path.as_deref());
+        self.history_window.is_open = true;
     }
 
     pub fn cancel_compile(&mut self) {
@@ -82,7 +183,7 @@ impl VmFlowApp {
 }
 
 impl VmFlowApp {
-    pub fn handle_dropped_files(&mut self, files: &Vec<eframe::egui::DroppedFile>) {
+    pub fn handle_dropped_files(&mut self, files: &[eframe::egui::DroppedFile]) {
         for file in files.iter().cloned() {
             if let Some(path) = &file.path {
                 if path.is_dir() {
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use vmflow_config_types::{preset::Preset, GameConfiguration};
+use vmflow_config_types::{preset::Preset, GameConfiguration, LogSettings};
 use serde::{Deserialize, Serialize};
 
 
@@ -11,6 +11,8 @@ pub struct AppSettings {
     pub current_preset_index: usize,
     pub current_game_index: usize,
     pub theme: super::ui::themes::Themes,
+    #[serde(default)]
+    pub logs: LogSettings,
 }
 
 
@@ -22,6 +24,7 @@ impl Default for AppSettings {
             current_preset_index: 0,
             current_game_index: 0,
             theme: super::ui::themes::Themes::DefaultDark,
+            logs: LogSettings::default(),
         }
     }
 }
@@ -0,0 +1,247 @@
+use std::{sync::mpsc::Sender, time::Instant};
+
+use compilation_core::{format_point, CoreEvent, JobEventHandler, MapStatus, Severity, StepOutcome};
+use eframe::egui::{Color32, Context, RichText};
+use vmflow_config_types::validation::IssueSeverity;
+
+use super::CompileWindow;
+
+/// Forwards compilation events to the GUI thread and wakes the GUI up.
+pub struct ChannelEventHandler {
+    sender: Sender<CoreEvent>,
+    ctx: Context,
+}
+
+impl ChannelEventHandler {
+    pub fn new(sender: Sender<CoreEvent>, ctx: Context) -> Self {
+        Self { sender, ctx }
+    }
+}
+
+impl JobEventHandler for ChannelEventHandler {
+    fn handle_event(&self, event: CoreEvent) {
+        // The receiver is gone once a new compile replaced it, nothing left to update
+        if self.sender.send(event).is_ok() {
+            self.ctx.request_repaint();
+        }
+    }
+}
+
+/// State of a map or a step shown in the compile window.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+    Skipped,
+}
+
+impl RunStatus {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Running => "⏵",
+            Self::Succeeded => "✔",
+            Self::Failed => "✖",
+            Self::Cancelled => "⏹",
+            Self::Skipped => "➖",
+        }
+    }
+
+    pub fn color(&self) -> Color32 {
+        match self {
+            Self::Running => Color32::LIGHT_BLUE,
+            Self::Succeeded => Color32::GREEN,
+            Self::Failed => Color32::RED,
+            Self::Cancelled => Color32::YELLOW,
+            Self::Skipped => Color32::GRAY,
+        }
+    }
+}
+
+impl From<&MapStatus> for RunStatus {
+    fn from(status: &MapStatus) -> Self {
+        match status {
+            MapStatus::Succeeded => Self::Succeeded,
+            MapStatus::Failed { .. } => Self::Failed,
+            MapStatus::Cancelled => Self::Cancelled,
+            MapStatus::Skipped | MapStatus::UpToDate => Self::Skipped,
+        }
+    }
+}
+
+impl From<StepOutcome> for RunStatus {
+    fn from(outcome: StepOutcome) -> Self {
+        match outcome {
+            StepOutcome::Succeeded => Self::Succeeded,
+            StepOutcome::Failed | StepOutcome::FailureIgnored => Self::Failed,
+            StepOutcome::Skipped => Self::Skipped,
+        }
+    }
+}
+
+/// Progress of one map in the compile window.
+pub struct MapRunState {
+    pub map_id: usize,
+    pub name: String,
+    pub status: RunStatus,
+    /// (step name, status)
+    pub steps: Vec<(String, RunStatus)>,
+}
+
+impl CompileWindow {
+    /// Updates the window state with an event of the running batch.
+    pub fn apply_event(&mut self, event: CoreEvent) {
+        match event {
+            CoreEvent::BatchStarted => self.logs.push(RichText::new("Compilation started").strong()),
+            CoreEvent::ValidationIssues(issues) => {
+                for issue in issues {
+                    let color = match issue.severity {
+                        IssueSeverity::Error => Color32::RED,
+                        IssueSeverity::Warning => Color32::YELLOW,
+                    };
+                    self.logs.push(RichText::new(format!("Preset: {issue}")).color(color));
+                }
+            }
+
+            CoreEvent::MapStarted(map_id, map_name) => {
+                self.logs.push(RichText::new(format!("==> {map_name}")).strong());
+                self.current_file = map_name.clone();
+                self.maps.push(MapRunState {
+                    map_id,
+                    name: map_name,
+                    status: RunStatus::Running,
+                    steps: Vec::new(),
+                });
+            }
+            CoreEvent::StepStarted(map_id, map_name, step_name) => {
+                self.logs.push(RichText::new(format!("[{map_name}] {step_name}")).strong());
+                self.current_file = map_name;
+                self.current_step = step_name.clone();
+                self.step_start_time = Instant::now();
+                self.statistics.clear();
+                if let Some(map) = self.map_mut(map_id) {
+                    // A step that never finished failed, its policy let the map go on
+                    set_step_status(map, RunStatus::Failed);
+                    map.steps.push((step_name, RunStatus::Running));
+                }
+            }
+            CoreEvent::StepSkipped(map_id, map_name, step_name, reason) => {
+                self.logs.push(RichText::new(format!("[{map_name}] {step_name} skipped: {reason}")).color(Color32::GRAY));
+                if let Some(map) = self.map_mut(map_id) {
+                    set_step_status(map, RunStatus::Failed);
+                    map.steps.push((step_name, RunStatus::Skipped));
+                }
+            }
+
+            CoreEvent::StepLog(_, _, _, line) => self.logs.push(RichText::new(line)),
+            CoreEvent::StepWarn(_, _, _, line) => self.logs.push(RichText::new(line).color(Color32::YELLOW)),
+            CoreEvent::StepErr(_, _, _, line) => self.logs.push(RichText::new(line).color(Color32::RED)),
+            CoreEvent::StepDiagnostic(_, _, _, diagnostic) => {
+                let color = match diagnostic.severity {
+                    Severity::Error => Color32::RED,
+                    Severity::Warning => Color32::YELLOW,
+                    Severity::Info => return,
+                };
+                // The diagnostic follows the log line it comes from, highlight that line
+                if let Some(last) = self.logs.last_mut()
+                    && last.text().trim() == diagnostic.message
+                {
+                    *last = last.clone().color(color);
+                }
+            }
+
+            CoreEvent::StepProgress(_, _, _, progress) => self.progress = Some(progress),
+            CoreEvent::StepStatistic(_, _, _, name, value) => {
+                match self.statistics.iter_mut().find(|(stat, _)| *stat == name) {
+                    Some((_, stat_value)) => *stat_value = value,
+                    None => self.statistics.push((name, value)),
+                }
+            }
+
+            CoreEvent::LeakDetected(_, map_name, leak) => {
+                let entity = leak.entity.as_ref().map_or("unknown entity".to_string(), |entity| {
+                    format!("{} at ({})", entity.classname, format_point(&entity.origin))
+                });
+                self.logs.push(RichText::new(format!(
+                    "LEAK in {map_name}: {entity}, leak path of {:.0} units",
+                    leak.length()
+                )).color(Color32::RED));
+                self.leaks.push((map_name, leak));
+            }
+
+            CoreEvent::BspSummary(_, map_name, summary) => {
+                self.logs.push(RichText::new(format!("{map_name}: {summary}")).color(Color32::LIGHT_BLUE));
+            }
+            CoreEvent::LimitAlert(_, map_name, alert) => {
+                let color = if alert.is_error() { Color32::RED } else { Color32::YELLOW };
+                self.logs.push(RichText::new(format!("{map_name}: {alert}")).color(color));
+                self.limit_alerts.push((map_name, alert));
+            }
+
+            CoreEvent::StepFinished(map_id, _, step_name) => {
+                self.logs.push(RichText::new(format!("{step_name} finished")).color(Color32::GREEN));
+                if let Some(map) = self.map_mut(map_id) {
+                    set_step_status(map, RunStatus::Succeeded);
+                }
+            }
+            CoreEvent::StepFailed(map_id, _, step_name, error) => {
+                self.logs.push(RichText::new(format!("{step_name} failed: {error}")).color(Color32::RED));
+                if let Some(map) = self.map_mut(map_id) {
+                    set_step_status(map, RunStatus::Failed);
+                }
+            }
+            CoreEvent::MapFinished(map_id, map_name, report) => {
+                let status = RunStatus::from(&report.status);
+                let text = match &report.status {
+                    MapStatus::Failed { step, error, .. } => format!("{map_name} failed at {step}: {error}"),
+                    MapStatus::Cancelled => format!("{map_name} cancelled"),
+                    MapStatus::UpToDate => format!("{map_name} skipped, unchanged since its last compile"),
+                    _ => format!("{map_name} compiled"),
+                };
+                self.logs.push(RichText::new(text).color(status.color()));
+
+                if let Some(map) = self.map_mut(map_id) {
+                    map.status = status;
+                    // The report knows how every step ended, including failures ignored by their policy
+                    for ((_, step_status), step) in map.steps.iter_mut().zip(&report.steps) {
+                        *step_status = RunStatus::from(step.outcome);
+                    }
+                    // A step still running was interrupted by cancellation
+                    set_step_status(map, status);
+                }
+            }
+
+            CoreEvent::BatchCompleted(report) => {
+                self.logs.push(RichText::new(format!(
+                    "Batch {:?}: {} warning(s), {} error(s)",
+                    report.outcome,
+                    report.warnings(),
+                    report.errors()
+                )).strong());
+                self.is_finished = true;
+                self.report = Some(report);
+            }
+            CoreEvent::BatchCancelled => {
+                self.logs.push(RichText::new("Compilation cancelled").color(Color32::YELLOW));
+                self.is_cancelled = true;
+            }
+            CoreEvent::CancellationRequested => {
+                self.logs.push(RichText::new("Cancelling...").color(Color32::YELLOW));
+            }
+        }
+    }
+
+    fn map_mut(&mut self, map_id: usize) -> Option<&mut MapRunState> {
+        self.maps.iter_mut().find(|map| map.map_id == map_id)
+    }
+}
+
+/// Sets the status of the step currently running on a map.
+fn set_step_status(map: &mut MapRunState, status: RunStatus) {
+    if let Some((_, step_status)) = map.steps.last_mut()
+        && *step_status == RunStatus::Running
+    {
+        *step_status = status;
+    }
+}
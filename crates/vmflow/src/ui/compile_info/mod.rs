@@ -1,20 +1,38 @@
-use std::sync::{Arc, atomic::AtomicBool};
+use std::time::{Duration, Instant};
+
+use compilation_core::{budget::LimitAlert, format_point, BatchReport, BspSummary, LeakInfo, Progress};
 
 use eframe::egui::{self, CentralPanel, Color32, Context, RichText, Ui, ViewportClass};
 
 use crate::{app::VmFlowApp, ui::utils::UiExt};
 
+mod events;
+pub use events::ChannelEventHandler;
+use events::{MapRunState, RunStatus};
+
 const SIDE_PANEL_WIDTH: f32 = 140.0;
 
-/// TODO comment.
+/// State of the compile window, driven by the events of the running batch.
 pub struct CompileWindow {
     pub start_time: std::time::Instant,
-    pub current_map: usize,
+    pub current_file: String,
     pub current_step: String,
+    /// Moment the current step started, used to estimate its remaining time.
+    pub step_start_time: Instant,
+    /// Last progress reported by the backend.
+    pub progress: Option<Progress>,
+    /// Sizes reported by the current step (portal clusters, leafs...).
+    pub statistics: Vec<(String, u64)>,
+    /// Maps that started, in start order.
+    pub maps: Vec<MapRunState>,
+    /// (map name, leak)
+    pub leaks: Vec<(String, LeakInfo)>,
+    /// (map name, alert) for the compiled maps crossing the limit budget of the preset.
+    pub limit_alerts: Vec<(String, LimitAlert)>,
     pub logs: Vec<RichText>,
-    pub errors: String,
-    // pub warnings: CompileError,
-    pub is_canceled: Arc<AtomicBool>,
+    /// Summary of the batch, set once it completed.
+    pub report: Option<BatchReport>,
+    pub is_cancelled: bool,
     pub is_finished: bool,
     pub is_open: bool,
 }
@@ -23,27 +41,48 @@ impl Default for CompileWindow {
     fn default() -> Self {
         Self {
             start_time: std::time::Instant::now(),
-            current_map: Default::default(),
+            current_file: Default::default(),
             current_step: Default::default(),
+            step_start_time: Instant::now(),
+            progress: None,
+            statistics: Default::default(),
+            maps: Default::default(),
+            leaks: Default::default(),
+            limit_alerts: Default::default(),
             logs: Default::default(),
-            errors: Default::default(),
-            is_canceled: Default::default(),
+            report: None,
+            is_cancelled: false,
             is_finished: false,
             is_open: false,
         }
     }
 }
 
+impl CompileWindow {
+    /// Clears the state of the previous compile, keeping the window open state.
+    pub fn reset(&mut self) {
+        *self = Self {
+            is_open: self.is_open,
+            ..Default::default()
+        };
+    }
+}
+
 pub fn build_viewport(ctx: &Context, class: ViewportClass, app: &mut VmFlowApp) {
     assert!(
         class == ViewportClass::Immediate,
         "This egui backend doesn't support multiple viewports"
     );
 
+    if let Some(rx) = &app.backend_rx {
+        for event in rx.try_iter() {
+            app.compile_window.apply_event(event);
+        }
+    }
+
     let window_state = &mut app.compile_window;
-    let settings = &app.settings;
-    let maps = &app.maps;
     let mut should_canceled = false;
+    let mut should_close = false;
     CentralPanel::default().show(ctx, |ui| {
         ui.vertical(|ui| {
             draw_logs(ui, &window_state.logs);
@@ -53,80 +92,63 @@ pub fn build_viewport(ctx: &Context, class: ViewportClass, app: &mut VmFlowApp)
             .default_width(SIDE_PANEL_WIDTH)
             .resizable(false)
             .show(ctx, |ui| {
-                if !window_state.is_finished && app.maps.len() > window_state.current_map {
+                if !window_state.is_finished && !window_state.current_file.is_empty() {
                     // Current file
                     ui.horizontal(|ui| {
-                        let file = &app.maps[window_state.current_map].name; // fuck me
                         ui.label_with_size("Current File:", 10.);
-                        ui.label_with_size(file, 10.);
+                        ui.label_with_size(&window_state.current_file, 10.);
                         // Current compiler
                         ui.label_with_size(&window_state.current_step, 10.);
                     });
                 }
                 ui.add_space(10.);
 
-                // Progress (TODO)
-                draw_progress_frame(ui);
+                draw_progress_frame(ui, window_state);
                 ui.add_space(14.);
 
-                if !window_state.is_finished {
+                if let Some(report) = &window_state.report {
+                    draw_batch_summary(ui, report, &window_state.leaks, &window_state.limit_alerts);
+                    if ui
+                        .button_with_dimensions("Close", [ui.available_width(), 18.])
+                        .clicked()
+                    {
+                        should_close = true;
+                    }
+                } else {
                     ui.horizontal(|ui| {
                         let elapsed = window_state.start_time.elapsed();
                         ui.label_with_size("Total Elapsed Time:", 10.);
                         ui.label_with_size(format_duration(elapsed), 10.);
                     });
                     if ui
-                        .button_with_dimensions("Abort", [ui.available_width(), 18.])
+                        .button_with_dimensions_and_state("Abort", [ui.available_width(), 18.], window_state.is_cancelled)
                         .clicked()
                     {
                         should_canceled = true;
                     }
+                    // Keep the elapsed time ticking between events
+                    ctx.request_repaint_after(Duration::from_secs(1));
                 }
             });
     });
 
-    // todo placeholder:
-    // if let Some(rx) = &app.backend_rx {
-    //     for msg in rx.try_iter() {
-    //         println!("{msg:?}");
-
-    //         use compilation_core::CoreEvent;
-    //         match msg {
-    //             CoreEvent::SetCurrentStepName(name) => {
-    //                 window_state.logs.push(RichText::new(&name));
-    //                 window_state.current_step = name;
-    //             }
-    //             CoreEvent::LogInfo(log) => window_state.logs.push(RichText::new(log)),
-    //             CoreEvent::LogSuccess(log) => window_state.logs.push(RichText::new(log)),
-    //             CoreEvent::LogWarning(log) => window_state
-    //                 .logs
-    //                 .push(RichText::new(log).color(Color32::YELLOW)),
-    //             CoreEvent::LogError(log) => window_state
-    //                 .logs
-    //                 .push(RichText::new(log).color(Color32::RED)),
-    //             CoreEvent::StepFinished => window_state
-    //                 .logs
-    //                 .push(RichText::new("Finished!").color(Color32::GREEN)),
-    //             CoreEvent::CompilationFinished => window_state.current_map += 1,
-    //             CoreEvent::AllStepsFinished => window_state.is_finished = true,
-    //             CoreEvent::CompilationFailed(backend_error) => should_canceled = true,
-    //             _ => {}
-    //         }
-    //     }
-    // }
-
     // Tell parent viewport that we should not show next frame:
     if ctx.input(|i| i.viewport().close_requested()) {
-        should_canceled = true;
+        should_canceled = !window_state.is_finished;
+        should_close = true;
     }
 
-    if should_canceled {
+    if should_close {
         window_state.is_open = false;
+    }
+    // Aborting keeps the window open until the batch reports how it ended
+    if should_canceled && !window_state.is_cancelled {
+        window_state.is_cancelled = true;
         app.cancel_compile();
     }
 }
 
-fn draw_logs(ui: &mut Ui, logs: &Vec<RichText>) {
+fn draw_logs(ui: &mut Ui, logs: &[RichText]) {
     egui::Frame::dark_canvas(ui.style())
         .stroke(egui::Stroke::new(1.0, egui::Color32::GRAY))
         .show(ui, |ui| {
@@ -144,18 +166,157 @@ fn draw_logs(ui: &mut Ui, logs: &Vec<RichText>) {
         });
 }
 
-fn draw_progress_frame(ui: &mut Ui) {
+fn draw_progress_frame(ui: &mut Ui, window_state: &CompileWindow) {
     ui.label_with_size("Progress:", 10.);
     egui::Frame::canvas(ui.style()).show(ui, |ui| {
         ui.set_height(ui.available_height() - 100.);
         ui.set_width(ui.available_width());
 
-        egui::ScrollArea::vertical().show(ui, |ui| {});
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let Some(progress) = &window_state.progress else {
+                ui.label_with_size("Waiting for progress...", 10.);
+                return;
+            };
+
+            let step_label = if progress.phase.is_empty() {
+                window_state.current_step.clone()
+            } else {
+                format!("{}: {}", window_state.current_step, progress.phase)
+            };
+            draw_progress_bar(ui, &step_label, progress.step_fraction);
+            draw_progress_bar(ui, "Map", progress.map_fraction);
+            draw_progress_bar(ui, "Total", progress.batch_fraction);
+
+            if let Some(eta) = estimate_remaining(window_state.step_start_time, progress.step_fraction) {
+                ui.horizontal(|ui| {
+                    ui.label_with_size("Step ETA:", 10.);
+                    ui.label_with_size(format_duration(eta), 10.);
+                });
+            }
+            if let Some(eta) = estimate_remaining(window_state.start_time, progress.batch_fraction) {
+                ui.horizontal(|ui| {
+                    ui.label_with_size("Total ETA:", 10.);
+                    ui.label_with_size(format_duration(eta), 10.);
+                });
+            }
+
+            for (name, value) in &window_state.statistics {
+                ui.horizontal(|ui| {
+                    ui.label_with_size(format!("{name}:"), 10.);
+                    ui.label_with_size(value.to_string(), 10.);
+                });
+            }
+
+            ui.separator();
+            draw_map_states(ui, &window_state.maps);
+        });
     });
 }
 
+/// Draws the status of every started map and of its steps.
+///
+/// # Arguments
+///
+/// * `ui` - The UI context.
+/// * `maps` - Maps of the running batch.
+fn draw_map_states(ui: &mut Ui, maps: &[MapRunState]) {
+    for map in maps {
+        ui.label(status_text(map.status, &map.name));
+        for (step, status) in &map.steps {
+            ui.horizontal(|ui| {
+                ui.add_space(8.);
+                ui.label(status_text(*status, step));
+            });
+        }
+    }
+}
+
+fn status_text(status: RunStatus, text: &str) -> RichText {
+    RichText::new(format!("{} {text}", status.icon()))
+        .size(10.)
+        .color(status.color())
+}
+
+/// Draws the outcome of the completed batch, what the compiled maps hold, their logs, their limit alerts and the leaks found.
+///
+/// # Arguments
+///
+/// * `ui` - The UI context.
+/// * `report` - Report of the completed batch.
+/// * `leaks` - Leaks reported during the batch, with their map names.
+/// * `limit_alerts` - Limits crossing the budget of the preset, with their map names.
+fn draw_batch_summary(ui: &mut Ui, report: &BatchReport, leaks: &[(String, LeakInfo)], limit_alerts: &[(String, LimitAlert)]) {
+    let color = if report.is_success() { Color32::GREEN } else { Color32::RED };
+    ui.label(RichText::new(format!("{:?}", report.outcome)).size(12.).color(color));
+    ui.label_with_size(format!("Time: {}", format_duration(report.duration)), 10.);
+    ui.label_with_size(format!("Warnings: {}  Errors: {}", report.warnings(), report.errors()), 10.);
+
+    for map in &report.maps {
+        if let Some(bsp) = &map.bsp {
+            ui.label_with_size(format!("{}: {bsp}", map.map_name), 10.)
+                .on_hover_text(format_limits(bsp));
+        }
+        if let Some(log) = &map.log_file
+            && ui.button_with_size(format!("Copy {} log path", map.map_name), 10.).on_hover_text(log.display().to_string()).clicked()
+        {
+            ui.ctx().copy_text(log.display().to_string());
+        }
+    }
+    for (map_name, alert) in limit_alerts {
+        let color = if alert.is_error() { Color32::RED } else { Color32::YELLOW };
+        ui.label(RichText::new(format!("{map_name}: {alert}")).size(10.).color(color));
+    }
+
+    for (map_name, leak) in leaks {
+        ui.label(RichText::new(format!("Leak: {map_name}")).size(10.).color(Color32::RED));
+        ui.horizontal(|ui| {
+            if let Some(start) = leak.start()
+                && ui.button_with_size("Copy Start", 10.).on_hover_text(format_point(&start)).clicked()
+            {
+                ui.ctx().copy_text(format_point(&start));
+            }
+            if let Some(path) = &leak.pointfile_path
+                && ui.button_with_size("Copy .lin", 10.).on_hover_text(path.display().to_string()).clicked()
+            {
+                ui.ctx().copy_text(path.display().to_string());
+            }
+        });
+    }
+    ui.add_space(6.);
+}
+
+/// One line per engine limit of the map, with its usage.
+fn format_limits(bsp: &BspSummary) -> String {
+    bsp.limits.iter()
+        .map(|limit| format!("{}: {}/{} ({:.0}%)", limit.name, limit.used, limit.max, limit.percent()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Draws a labelled progress bar.
+///
+/// # Arguments
+///
+/// * `ui` - The UI context.
+/// * `label` - Text shown above the bar.
+/// * `fraction` - Progress in `0.0..=1.0`.
+fn draw_progress_bar(ui: &mut Ui, label: &str, fraction: f32) {
+    ui.label_with_size(label, 10.);
+    ui.add(egui::ProgressBar::new(fraction).show_percentage());
+}
+
+/// Extrapolates the remaining time from the elapsed time and the progress made.
+fn estimate_remaining(started: Instant, fraction: f32) -> Option<Duration> {
+    // Too early estimates are wild, wait for a bit of progress
+    if !(0.01..1.0).contains(&fraction) {
+        return None;
+    }
+    let elapsed = started.elapsed().as_secs_f32();
+    Some(Duration::from_secs_f32(elapsed * (1.0 - fraction) / fraction))
+}
+
 /// Function to format Duration into HH:MM:SS
-fn format_duration(duration: std::time::Duration) -> String {
+pub fn format_duration(duration: std::time::Duration) -> String {
     let total_seconds = duration.as_secs();
     let hours = total_seconds / 3600;
     let minutes = (total_seconds % 3600) / 60;
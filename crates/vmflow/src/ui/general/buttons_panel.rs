@@ -17,7 +17,7 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
     });
 }
 
-/// Builds the left side of the button panel with Add and Clear buttons.
+/// Builds the left side of the button panel with Add, Clear and History buttons.
 ///
 /// # Arguments
 ///
@@ -43,11 +43,14 @@ fn build_left_ui(ui: &mut egui::Ui, app: &mut App) {
         if ui.button_with_size("Clear", 12.0).clicked() {
             app.maps.clear();
         }
+        if ui.button_with_size("History", 12.0).clicked() {
+            app.open_history();
+        }
     });
 }
 
 
-/// Builds the right side of the button panel with the compile button or processing indicator.
+/// Builds the right side of the button panel with the compile button and the force rebuild toggle, or the processing indicator.
 ///
 /// # Arguments
 ///
@@ -60,9 +63,13 @@ fn build_right_ui(ui: &mut egui::Ui, app: &mut App) {
             ui.add_enabled(false, button);
             ui.add(egui::widgets::Spinner::new())
                 .on_hover_cursor(egui::CursorIcon::Progress);
-        } else if ui.button("\tBegin Compile!\t").clicked() {
-            app.start_compile();
-            app.compile_window.is_open = true;
+        } else {
+            if ui.button("\tBegin Compile!\t").clicked() {
+                app.start_compile(ui.ctx());
+                app.compile_window.is_open = true;
+            }
+            ui.checkbox_with_size(&mut app.force_rebuild, "Force rebuild", 10.)
+                .on_hover_text("Compile the maps unchanged since their last successful compile too");
         }
     });
 }
@@ -0,0 +1,37 @@
+use crate::app::VmFlowApp as App;
+use eframe::egui::{self, Color32, RichText};
+
+use crate::ui::utils::UiExt;
+
+/// Shows the errors of the user compiler configs in a bottom panel until they are dismissed or fixed.
+///
+/// # Arguments
+///
+/// * `ctx` - The egui context
+/// * `app` - The application state
+pub fn show(ctx: &egui::Context, app: &mut App) {
+    if app.compiler_config_errors.is_empty() {
+        return;
+    }
+
+    egui::TopBottomPanel::bottom("compiler_config_errors").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Invalid compiler configs, they were not loaded:").size(8.0).color(Color32::RED));
+            ui.add_space(ui.available_width() - 40.0);
+            if ui.button_with_size("Dismiss", 8.0).clicked() {
+                app.compiler_config_errors.clear();
+            }
+        });
+
+        egui::ScrollArea::vertical().max_height(80.0).show(ui, |ui| {
+            for error in &app.compiler_config_errors {
+                ui.label(RichText::new(error.to_string()).size(8.0).monospace())
+                    .on_hover_text(error.path.display().to_string());
+            }
+        });
+
+        if let Some(dir) = &app.user_compilers_dir {
+            ui.label(RichText::new(format!("Fix the files in {}, they are reloaded on save", dir.display())).size(8.0).weak());
+        }
+    });
+}
@@ -7,6 +7,7 @@ use super::utils::UiExt;
 
 
 mod buttons_panel;
+pub mod compiler_errors;
 mod render_apps_grid;
 
 pub fn show(ui: &mut Ui, app: &mut VmFlowApp) {
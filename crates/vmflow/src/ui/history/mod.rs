@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use compilation_core::{
+    history::{self, format_timestamp, CompileRecord, HistoryQuery},
+    MapStatus, StepOutcome,
+};
+use eframe::egui::{self, CentralPanel, Color32, Context, RichText, Ui, ViewportClass};
+
+use crate::ui::{compile_info::format_duration, utils::UiExt};
+
+/// State of the compile history window.
+#[derive(Default)]
+pub struct HistoryWindow {
+    pub is_open: bool,
+    /// Records of the history file, the most recent first.
+    pub records: Vec<CompileRecord>,
+    /// Why the history file could not be read.
+    pub load_error: Option<String>,
+    pub map_filter: String,
+    pub step_filter: String,
+}
+
+impl HistoryWindow {
+    /// Reads the history file again, keeping the filters.
+    pub fn reload(&mut self, path: Option<&Path>) {
+        let records = match path {
+            Some(path) => history::load(path).map_err(|e| format!("Cannot read {}: {e}", path.display())),
+            None => Err("The config directory is unknown, no history is recorded".to_string()),
+        };
+        match records {
+            Ok(mut records) => {
+                records.reverse();
+                self.records = records;
+                self.load_error = None;
+            }
+            Err(error) => {
+                self.records.clear();
+                self.load_error = Some(error);
+            }
+        }
+    }
+
+    fn query(&self) -> HistoryQuery {
+        let filter = |text: &str| Some(text.trim().to_string()).filter(|text| !text.is_empty());
+        HistoryQuery {
+            map: filter(&self.map_filter),
+            step: filter(&self.step_filter),
+            limit: None,
+        }
+    }
+}
+
+/// Builds the compile history viewport.
+///
+/// # Arguments
+///
+/// * `ctx` - The egui context.
+/// * `class` - The viewport class (must be `Immediate`).
+/// * `window_state` - The mutable state of the history window.
+/// * `path` - The history file, read again by the reload button.
+pub fn build_viewport(ctx: &Context, class: ViewportClass, window_state: &mut HistoryWindow, path: Option<&Path>) {
+    assert!(
+        class == ViewportClass::Immediate,
+        "This egui backend doesn't support multiple viewports"
+    );
+
+    CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.label_with_size("Map:", 10.);
+            ui.add(egui::TextEdit::singleline(&mut window_state.map_filter).desired_width(120.));
+            ui.label_with_size("Step:", 10.);
+            ui.add(egui::TextEdit::singleline(&mut window_state.step_filter).desired_width(80.));
+            if ui.button_with_size("Reload", 10.).clicked() {
+                window_state.reload(path);
+            }
+        });
+        ui.separator();
+
+        if let Some(error) = &window_state.load_error {
+            ui.label(RichText::new(error).size(10.).color(Color32::RED));
+            return;
+        }
+
+        let query = window_state.query();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let mut shown = 0;
+            for record in window_state.records.iter().filter(|record| query.matches(record)) {
+                draw_record(ui, record, query.step.as_deref());
+                shown += 1;
+            }
+            if shown == 0 {
+                ui.label_with_size("No compiles recorded", 10.);
+            }
+        });
+    });
+
+    if ctx.input(|i| i.viewport().close_requested()) {
+        window_state.is_open = false;
+    }
+}
+
+/// Draws one compile, collapsed to a line, with its steps inside.
+///
+/// # Arguments
+///
+/// * `ui` - The UI context.
+/// * `record` - The compile to draw.
+/// * `step` - Step filtered on, its duration is shown on the collapsed line.
+fn draw_record(ui: &mut Ui, record: &CompileRecord, step: Option<&str>) {
+    let (color, status) = match &record.status {
+        MapStatus::Succeeded => (Color32::GREEN, "Succeeded".to_string()),
+        MapStatus::Failed { step, .. } => (Color32::RED, format!("Failed at {step}")),
+        MapStatus::Cancelled => (Color32::YELLOW, "Cancelled".to_string()),
+        MapStatus::Skipped | MapStatus::UpToDate => (Color32::GRAY, "Skipped".to_string()),
+    };
+    let duration = match step.and_then(|name| record.step(name)) {
+        Some(step) => format!("{} {}", step.name, format_duration(step.duration)),
+        None => format_duration(record.duration()),
+    };
+    let title = format!("{}  {}  {status}  {duration}", format_timestamp(record.started_at), record.map_name);
+
+    egui::CollapsingHeader::new(RichText::new(title).size(10.).color(color))
+        .id_salt((record.batch_id, &record.map_path))
+        .show(ui, |ui| {
+            ui.label_with_size(format!("Preset: {}  Game: {}", record.preset, record.game), 10.);
+            ui.label_with_size(format!(
+                "Started: {}  Finished: {}  Warnings: {}  Errors: {}",
+                format_timestamp(record.started_at),
+                format_timestamp(record.finished_at),
+                record.warnings(),
+                record.errors()
+            ), 10.);
+
+            egui::Grid::new((record.batch_id, &record.map_path, "steps"))
+                .striped(true)
+                .show(ui, |ui| {
+                    for step in &record.steps {
+                        let name = ui.label_with_size(&step.name, 10.);
+                        if !step.parameters.is_empty() {
+                            name.on_hover_text(step.parameters.join(" "));
+                        }
+                        ui.label_with_size(format_duration(step.duration), 10.);
+                        ui.label(RichText::new(format!("{:?}", step.outcome)).size(10.).color(outcome_color(step.outcome)));
+                        ui.label_with_size(step.exit_code.map(|code| format!("exit {code}")).unwrap_or_default(), 10.);
+                        ui.label_with_size(format!("W {}  E {}", step.warnings, step.errors), 10.);
+                        ui.end_row();
+                    }
+                });
+
+            if let Some(log) = &record.log_file {
+                ui.horizontal(|ui| {
+                    ui.label_with_size(format!("Log: {}", log.display()), 10.);
+                    if ui.button_with_size("Copy", 10.).clicked() {
+                        ui.ctx().copy_text(log.display().to_string());
+                    }
+                });
+            }
+            if let Some(hash) = &record.bsp_hash {
+                ui.horizontal(|ui| {
+                    ui.label_with_size(format!("BSP SHA-256: {}", &hash[..hash.len().min(16)]), 10.)
+                        .on_hover_text(hash);
+                    if ui.button_with_size("Copy", 10.).clicked() {
+                        ui.ctx().copy_text(hash.clone());
+                    }
+                });
+            }
+        });
+}
+
+fn outcome_color(outcome: StepOutcome) -> Color32 {
+    match outcome {
+        StepOutcome::Succeeded => Color32::GREEN,
+        StepOutcome::Failed => Color32::RED,
+        StepOutcome::FailureIgnored => Color32::YELLOW,
+        StepOutcome::Skipped => Color32::GRAY,
+    }
+}
@@ -2,6 +2,8 @@ use eframe::egui::{
     CentralPanel, Context, ViewportBuilder, ViewportClass, ViewportId,
 };
 
+use vmflow_config_types::COMPILE_HISTORY_FILE;
+
 use crate::app::VmFlowApp as App;
 
 pub mod themes;
@@ -12,6 +14,7 @@ pub mod constants;
 pub mod about;
 pub mod compile_info;
 pub mod general;
+pub mod history;
 pub mod settings;
 pub mod presets;
 
@@ -43,7 +46,7 @@ pub fn build_ui(ctx: &Context, app: &mut App) {
     // Process additional/immediate windows
     let mut is_any_immediate_open = false;
     if app.settings_window.is_open {
-        show_viewport_immediate(ctx, "AppSettings", [270.0, 235.0], |ctx, class| {
+        show_viewport_immediate(ctx, "AppSettings", [270.0, 290.0], |ctx, class| {
             settings::build_viewport(ctx, class, &mut app.settings, &mut app.settings_window)
         });
         is_any_immediate_open = true;
@@ -54,6 +57,13 @@ pub fn build_ui(ctx: &Context, app: &mut App) {
         });
         is_any_immediate_open = true;
     }
+    if app.history_window.is_open {
+        let path = app.data_file_path(COMPILE_HISTORY_FILE);
+        show_viewport_immediate(ctx, "Compile History", [600.0, 400.0], |ctx, class| {
+            history::build_viewport(ctx, class, &mut app.history_window, path.as_deref())
+        });
+        is_any_immediate_open = true;
+    }
     if app.compile_window.is_open {
         show_viewport_immediate(ctx, "Compile Process", [600.0, 400.0], |ctx, class| {
             compile_info::build_viewport(ctx, class, app)
@@ -62,6 +72,8 @@ pub fn build_ui(ctx: &Context, app: &mut App) {
     }
 
 
+    general::compiler_errors::show(ctx, app);
+
     // Main Panel
     CentralPanel::default().show(ctx, |ui| {
         if is_any_immediate_open {
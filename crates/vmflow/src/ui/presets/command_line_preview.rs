@@ -16,12 +16,12 @@ pub fn draw(ui: &mut egui::Ui, settings: &AppSettings, window_state: &PresetEdit
         ui.set_width(width);
         ui.set_height(height);
         
-        if let Some(preset) = settings.current_preset() {
-            if let Some(app) = preset.apps.get(window_state.selected_app) {
-                let args = app.get_command_params();
-                let preview_string = args.join(" "); 
-                ui.add(egui::Label::new(preview_string).truncate());
-            }
+        if let Some(preset) = settings.current_preset()
+            && let Some(app) = preset.apps.get(window_state.selected_app)
+        {
+            let args = app.get_command_params();
+            let preview_string = args.join(" "); 
+            ui.add(egui::Label::new(preview_string).truncate());
         }
     });
 }
@@ -57,6 +57,8 @@ pub fn build_viewport(
         // Draw the preset selector (ComboBox and buttons).
         ui.label_with_size("Preset Configurations:", 10.);
         preset_selector::build(ui, settings, window_state);
+        preset_selector::draw_preflight_policy(ui, settings);
+        preset_selector::draw_limit_budget(ui, settings);
         ui.add_space(5.); // Add some vertical spacing
 
         // Label for the preset editor section.
@@ -110,7 +112,7 @@ fn draw_preset_editor(
 
             ui.label("Parameters");
             ui.add_space(5.);
-            command_line_preview::draw(ui, &settings, &window_state);
+            command_line_preview::draw(ui, settings, window_state);
 
             // Disable parameter controls if there are no presets.
             if settings.compile_presets.is_empty() || settings.current_preset().unwrap().apps.is_empty() {
@@ -1,6 +1,9 @@
 use compiler_data_model::ParameterType;
-use vmflow_config_types::selected_compiler::SelectedCompiler;
-use eframe::egui;
+use vmflow_config_types::parameter_override::ParameterOverride;
+use vmflow_config_types::selected_compiler::{SelectedCompiler, StepPolicy};
+use vmflow_config_types::validation::{validate_step_parameters, IssueSeverity, ParameterIssue};
+use vmflow_config_types::GameConfiguration;
+use eframe::egui::{self, Color32};
 use egui_extras::{Column, TableBody};
 
 use crate::settings::AppSettings;
@@ -19,23 +22,45 @@ use crate::ui::constants::{font, table};
 ///
 /// Some(()) if successful, None if no preset or app is available
 pub fn build(ui: &mut egui::Ui, settings: &mut AppSettings, selected_app: usize) -> Option<()> {
+    let game = settings.current_game().cloned();
     let preset = settings.current_preset_mut()?;
     let app = preset.apps.get_mut(selected_app)?;
     
     ui.vertical(|ui| {
-        build_parameters_table(ui, app);
+        draw_step_policy(ui, app);
+        build_parameters_table(ui, app, game.as_ref());
     });
 
     Some(())
 }
 
+/// Draws the selector of the policy deciding when the step runs
+///
+/// # Arguments
+///
+/// * `ui` - The UI to draw on
+/// * `app` - The selected compiler application
+fn draw_step_policy(ui: &mut egui::Ui, app: &mut SelectedCompiler) {
+    ui.horizontal(|ui| {
+        ui.label_with_size("Run policy:", font::SMALL);
+        egui::ComboBox::from_id_salt("step_policy")
+            .selected_text(app.policy.label())
+            .show_ui(ui, |ui| {
+                for policy in StepPolicy::ALL {
+                    ui.selectable_value(&mut app.policy, policy, policy.label());
+                }
+            });
+    });
+}
+
 /// Creates a table with parameter information and editing capabilities
 ///
 /// # Arguments
 ///
 /// * `ui` - The UI to draw on
 /// * `app` - The selected compiler application containing parameters
-fn build_parameters_table(ui: &mut egui::Ui, app: &mut SelectedCompiler) {
+/// * `game` - The selected game configuration, used for compatibility checks
+fn build_parameters_table(ui: &mut egui::Ui, app: &mut SelectedCompiler, game: Option<&GameConfiguration>) {
     let columns = vec![
         Column::auto(), // 100
         Column::remainder(), // 515
@@ -49,7 +74,7 @@ fn build_parameters_table(ui: &mut egui::Ui, app: &mut SelectedCompiler) {
             header.col(|ui| { ui.strong("Description"); });
             header.col(|ui| { ui.strong("Value"); });
         },
-        |body| table_body(body, app)
+        |body| table_body(body, app, game)
     );
 }
 
@@ -59,23 +84,85 @@ fn build_parameters_table(ui: &mut egui::Ui, app: &mut SelectedCompiler) {
 ///
 /// * `body` - The table body to populate
 /// * `app` - The selected compiler application containing parameters
-fn table_body(body: TableBody, app: &mut SelectedCompiler) {
+/// * `game` - The selected game configuration, used for compatibility checks
+fn table_body(body: TableBody, app: &mut SelectedCompiler, game: Option<&GameConfiguration>) {
     let mut body = body;
-    for parm_wrapper in &mut app.parameters {
+    let step_issues = validate_step_parameters(app, game);
+    for (parm_wrapper, issues) in app.parameters.iter_mut().zip(step_issues) {
         body.row(table::ROW_HEIGHT, |mut row| {
-            row.col(|ui| { ui.centered_label_with_size(parm_wrapper.name(), font::SMALL); });
+            row.col(|ui| { draw_parameter_name(ui, parm_wrapper.name(), &issues); });
             row.col(|ui| { ui.label_with_size(parm_wrapper.description(), font::SMALL); });
-            if !matches!(parm_wrapper.value_type(), ParameterType::Flag) {
-                if let Some(inner_value) = &mut parm_wrapper.value {                    
-                    row.col(|ui| { 
-                        ui.horizontal_centered(|ui| {
-                            ui.single_line_text_field(inner_value, 0.);
-                        });
-                    });
+            row.col(|ui| { draw_parameter_value(ui, parm_wrapper); });
+        });
+    }
+}
+
+/// Draws the widget editing a parameter value, chosen by the parameter type
+///
+/// # Arguments
+///
+/// * `ui` - The UI to draw on
+/// * `parameter` - The parameter override to edit
+fn draw_parameter_value(ui: &mut egui::Ui, parameter: &mut ParameterOverride) {
+    match parameter.value_type() {
+        ParameterType::Flag => {}
+        ParameterType::Bool => {
+            let mut checked = parameter.value_or_default()
+                .and_then(compiler_data_model::parse_bool)
+                .unwrap_or(false);
+            ui.horizontal_centered(|ui| {
+                if ui.checkbox(&mut checked, "").changed() {
+                    parameter.value = Some(checked.to_string());
                 }
-            } else {
-                row.col(|ui| {});
+            });
+        }
+        ParameterType::Choice => {
+            let Some(definition) = parameter.parameter() else { return };
+            let current = parameter.value_or_default().unwrap_or_default().to_string();
+            let selected_text = definition.find_choice(&current).map_or(current.as_str(), |choice| choice.label());
+            ui.horizontal_centered(|ui| {
+                egui::ComboBox::from_id_salt(("choice", &parameter.parameter_id))
+                    .selected_text(egui::RichText::new(selected_text).size(font::SMALL))
+                    .width(ui.available_width())
+                    .show_ui(ui, |ui| {
+                        for choice in &definition.choices {
+                            if ui.selectable_label(choice.value == current, choice.label()).clicked() {
+                                parameter.value = Some(choice.value.clone());
+                            }
+                        }
+                    });
+            });
+        }
+        _ => {
+            if let Some(inner_value) = &mut parameter.value {
+                ui.horizontal_centered(|ui| {
+                    ui.single_line_text_field(inner_value, 0.);
+                });
             }
-        });
+        }
     }
 }
+
+/// Draws the name of a parameter, flagged with its validation issues if it has any
+///
+/// # Arguments
+///
+/// * `ui` - The UI to draw on
+/// * `name` - The parameter name
+/// * `issues` - Validation issues of the parameter value
+fn draw_parameter_name(ui: &mut egui::Ui, name: &str, issues: &[ParameterIssue]) {
+    let color = if issues.iter().any(|issue| issue.severity == IssueSeverity::Error) {
+        Color32::RED
+    } else if !issues.is_empty() {
+        Color32::YELLOW
+    } else {
+        ui.centered_label_with_size(name, font::SMALL);
+        return;
+    };
+
+    let hover_text = issues.iter().map(|issue| issue.kind.to_string()).collect::<Vec<_>>().join("\n");
+    ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+        ui.label(egui::RichText::new(format!("⚠ {name}")).size(font::SMALL).color(color))
+            .on_hover_text(hover_text);
+    });
+}
@@ -1,4 +1,4 @@
-use vmflow_config_types::preset::Preset;
+use vmflow_config_types::preset::{PreflightPolicy, Preset};
 use eframe::egui;
 
 use crate::settings::AppSettings;
@@ -41,14 +41,52 @@ pub fn build(ui: &mut egui::Ui, settings: &mut AppSettings, window_state: &mut s
             }
         }
 
-        if ui.button_with_dimensions("Clone", [button_width, 18.]).clicked() {
-            if let Some(preset) = settings.current_preset() {
-                let mut cloned_preset = preset.clone();
-                cloned_preset.name += " clone";
-                settings.add_preset(cloned_preset);
-                settings.current_preset_index = settings.compile_presets.len() - 1;
-            }
+        if ui.button_with_dimensions("Clone", [button_width, 18.]).clicked()
+            && let Some(preset) = settings.current_preset()
+        {
+            let mut cloned_preset = preset.clone();
+            cloned_preset.name += " clone";
+            settings.add_preset(cloned_preset);
+            settings.current_preset_index = settings.compile_presets.len() - 1;
         }
         
     });
 }
+
+/// Draws the selector of the pre-flight checks run on maps before the first step
+///
+/// # Arguments
+///
+/// * `ui` - The UI to draw on
+/// * `settings` - Application settings holding the current preset
+pub fn draw_preflight_policy(ui: &mut egui::Ui, settings: &mut AppSettings) {
+    let Some(preset) = settings.current_preset_mut() else { return };
+    ui.horizontal(|ui| {
+        ui.label_with_size("Pre-flight checks:", 10.);
+        egui::ComboBox::from_id_salt("preflight_policy")
+            .selected_text(preset.preflight.label())
+            .show_ui(ui, |ui| {
+                for policy in PreflightPolicy::ALL {
+                    ui.selectable_value(&mut preset.preflight, policy, policy.label());
+                }
+            });
+    });
+}
+
+/// Draws the thresholds of the limit usage warnings shown after a map compiled
+///
+/// # Arguments
+///
+/// * `ui` - The UI to draw on
+/// * `settings` - Application settings holding the current preset
+pub fn draw_limit_budget(ui: &mut egui::Ui, settings: &mut AppSettings) {
+    let Some(preset) = settings.current_preset_mut() else { return };
+    ui.horizontal(|ui| {
+        ui.label_with_size("Warn at:", 10.)
+            .on_hover_text("Usage of a MAX_MAP_* limit warned about after a compile");
+        ui.add(egui::DragValue::new(&mut preset.budget.warn_percent).range(1.0..=100.0).suffix("% of a limit"));
+        ui.label_with_size("or a jump of:", 10.)
+            .on_hover_text("Growth of a limit usage since the last compile of the map warned about");
+        ui.add(egui::DragValue::new(&mut preset.budget.jump_percent).range(1.0..=100.0).suffix("%"));
+    });
+}
@@ -16,12 +16,21 @@ pub fn draw(
         ui.vertical(|ui| {
             egui_dnd::dnd(ui, "dnd_process_list").show_vec(&mut preset.apps, |ui, item, handle, state| {
                 let is_selected = window_state.selected_app == state.index || state.dragged;
-                let selectable = egui::SelectableLabel::new(is_selected, item.name());
-                handle.ui(ui, |ui| {
-                    let label = ui.add_sized([ui.available_width(), 12.0], selectable);
-                    if state.dragged || label.clicked() {
-                        window_state.selected_app = state.index;    
-                    }
+                let name = if item.activated {
+                    egui::RichText::new(item.name())
+                } else {
+                    egui::RichText::new(item.name()).weak().strikethrough()
+                };
+                let selectable = egui::SelectableLabel::new(is_selected, name);
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut item.activated, "")
+                        .on_hover_text("Run this step");
+                    handle.ui(ui, |ui| {
+                        let label = ui.add_sized([ui.available_width(), 12.0], selectable);
+                        if state.dragged || label.clicked() {
+                            window_state.selected_app = state.index;    
+                        }
+                    });
                 });
             });            
         });
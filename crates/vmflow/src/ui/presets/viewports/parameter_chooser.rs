@@ -114,10 +114,10 @@ fn handle_row_click(
         if *selected_row == idx { // double-clicked
             *param_selected = true;
             // Use safe unwrapping with error handling
-            if let Some(preset) = settings.current_preset_mut() {
-                if let Some(app) = preset.apps.get_mut(app_idx) {
-                    app.add_parameter(idx);
-                }
+            if let Some(preset) = settings.current_preset_mut()
+                && let Some(app) = preset.apps.get_mut(app_idx)
+            {
+                app.add_parameter(idx);
             }
             return;
         }
@@ -1,7 +1,8 @@
 use eframe::egui;
 use rfd::FileDialog;
-use vmflow_config_types::GameConfiguration;
+use vmflow_config_types::{GameConfiguration, WineConfiguration};
 use crate::ui::settings::dir_field;
+use crate::ui::utils::UiExt;
 
 use super::SettingsWindow;
 
@@ -32,11 +33,9 @@ pub fn draw_advanced_settings(ui: &mut egui::Ui, game: &mut GameConfiguration, w
     state
         .show_header(ui, |ui| {
             let response = ui.label("Advanced Compiler AppSettings");
-            if ui.button("Auto Scan").clicked() {
-                if !window_state.additional_collapsing_is_open {
-                    window_state.additional_should_toggle = true;
-                    // TODO: Add Auto Scan functionality here.
-                }
+            if ui.button("Auto Scan").clicked() && !window_state.additional_collapsing_is_open {
+                window_state.additional_should_toggle = true;
+                // TODO: Add Auto Scan functionality here.
             }
 
             let id = ui.make_persistent_id("collapsing_header_interact");
@@ -68,17 +67,67 @@ fn draw_collapse_menu(ui: &mut egui::Ui, game: &mut GameConfiguration) {
             }
         });
 
+        // Entity definitions used by the pre-flight checks.
+        dir_field::draw_dir_field(ui, "FGD", &mut game.fgd, |fgd| {
+            if let Some(path) = FileDialog::new().add_filter("FGD", &["fgd"]).pick_file() {
+                *fgd = path.display().to_string();
+            }
+        });
+
         // Set the path for custom compiler applications.
-        for (idx, compiler) in compilers_service::iter_configs().enumerate() {
+        for compiler in compilers_service::iter_configs() {
             if compiler.is_builtin {
                 continue
             }
 
-            dir_field::draw_dir_field(ui, &compiler.name, &mut game.custom_apps_paths[idx], |dir| {
+            dir_field::draw_dir_field(ui, &compiler.name, game.compiler_paths.entry(compiler.id.clone()).or_default(), |dir| {
                 if let Some(path) = FileDialog::new().pick_file() {
                     *dir = path.display().to_string();
                 }
             });
         }
+
+        #[cfg(unix)]
+        draw_wine_settings(ui, &mut game.wine);
+    });
+}
+
+/// Draws the Wine settings used to run Windows compilers of the game.
+///
+/// # Arguments
+///
+/// * `ui` - The mutable reference to the egui UI.
+/// * `wine` - The mutable reference to the Wine configuration of the game.
+#[cfg(unix)]
+fn draw_wine_settings(ui: &mut egui::Ui, wine: &mut WineConfiguration) {
+    ui.separator();
+    dir_field::draw_dir_field(ui, "Wine Prefix (empty for default)", &mut wine.prefix, |dir| {
+        if let Some(path) = FileDialog::new().pick_folder() {
+            *dir = path.display().to_string();
+        }
     });
+    dir_field::draw_dir_field(ui, "Wine Binary (empty for 'wine')", &mut wine.binary, |dir| {
+        if let Some(path) = FileDialog::new().pick_file() {
+            *dir = path.display().to_string();
+        }
+    });
+
+    ui.label_with_size("Wine Environment Variables", 10.0);
+    let mut idx_to_remove = None;
+    for (idx, (name, value)) in wine.env_vars.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            egui::TextEdit::singleline(name).hint_text("NAME").desired_width(70.0).show(ui);
+            ui.label("=");
+            egui::TextEdit::singleline(value).hint_text("value").desired_width(ui.available_width() - 30.0).show(ui);
+            if ui.small_button("🗑").clicked() {
+                idx_to_remove = Some(idx);
+            }
+        });
+    }
+    if let Some(idx) = idx_to_remove {
+        wine.env_vars.remove(idx);
+    }
+    if ui.button("Add Variable").clicked() {
+        wine.env_vars.push(Default::default());
+    }
 }
@@ -0,0 +1,25 @@
+use eframe::egui;
+use rfd::FileDialog;
+use vmflow_config_types::LogSettings;
+
+use super::dir_field;
+use crate::ui::utils::UiExt;
+
+/// Draws the folder the compile logs are written to and how many batches are kept.
+///
+/// # Arguments
+///
+/// * `ui` - The mutable reference to the egui UI.
+/// * `logs` - The mutable log settings.
+pub fn draw_log_settings(ui: &mut egui::Ui, logs: &mut LogSettings) {
+    dir_field::draw_dir_field(ui, "Log Dir (empty: next to the settings):", &mut logs.dir, |dir| {
+        if let Some(path) = FileDialog::new().pick_folder() {
+            *dir = path.display().to_string();
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label_with_size("Keep logs of the last", 10.);
+        ui.add(egui::DragValue::new(&mut logs.keep_batches).range(0..=1000));
+        ui.label_with_size("batches (0: all)", 10.);
+    });
+}
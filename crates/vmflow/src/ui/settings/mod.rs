@@ -1,6 +1,7 @@
 pub mod config_editor;
 pub mod collapse_menu;
 pub mod dir_field;
+pub mod log_settings;
 pub mod theme_selector;
 
 use eframe::egui::{self, CentralPanel, Context, RichText, ViewportClass};
@@ -24,7 +25,7 @@ pub struct SettingsWindow {
 
 /// Builds the main settings viewport.
 ///
-/// This function renders the overall settings window, including the theme selector,
+/// This function renders the overall settings window, including the theme selector, the compile logs,
 /// game configurations, advanced settings, and the game configuration editor (if open).
 ///
 /// # Arguments
@@ -63,6 +64,10 @@ pub fn build_viewport(
         // Build the theme selector.
         theme_selector::build_theme_selector(ui, settings);
 
+        // Compile logs, shared by every game.
+        log_settings::draw_log_settings(ui, &mut settings.logs);
+        ui.add_space(10.);
+
         // Game configurations combo box.
         let games_conf = &settings.games;
         let idx = settings.current_game_index;
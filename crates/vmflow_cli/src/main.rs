@@ -0,0 +1,221 @@
+//! Headless map compiler using the presets and game configurations of the VMFlow GUI.
+
+mod remote;
+mod reporter;
+mod settings;
+
+use std::{
+    io::IsTerminal,
+    path::PathBuf,
+    process::ExitCode,
+    sync::Arc,
+};
+
+use clap::{Parser, ValueEnum};
+use compilation_core::history::{self, HistoryQuery};
+use compilation_core::CompilationSession;
+use vmflow_config_types::{migration::migrate_settings, preset::PreflightPolicy, VmfMap, COMPILE_HISTORY_FILE, FINGERPRINTS_FILE, LIMIT_BUDGETS_FILE};
+
+use reporter::TerminalReporter;
+use settings::{data_file_path, expand_map_paths, settings_dir, load_user_compilers, CliSettings};
+
+/// Compiles maps with VMFlow presets, without a GUI.
+///
+/// Exit codes: 0 when every map compiled, 1 when a map failed, 2 on invalid arguments or settings.
+#[derive(Parser)]
+#[command(name = "vmflow-cli", version)]
+struct Cli {
+    /// Map files, folders or glob patterns (`maps/*.vmf`).
+    #[arg(required_unless_present_any = ["list", "serve", "history"])]
+    maps: Vec<String>,
+
+    /// Preset to compile with. Defaults to the preset selected in the GUI.
+    #[arg(short, long)]
+    preset: Option<String>,
+
+    /// Game configuration to compile for. Defaults to the game selected in the GUI.
+    #[arg(short, long)]
+    game: Option<String>,
+
+    /// Number of maps compiled at the same time.
+    #[arg(short, long, default_value_t = 1)]
+    jobs: usize,
+
+    /// Print events as JSON lines instead of text.
+    #[arg(long)]
+    json: bool,
+
+    /// Disable coloured output.
+    #[arg(long)]
+    no_color: bool,
+
+    /// Hide compiler output, print only diagnostics and results.
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Pre-flight checks of the maps, instead of the preset setting.
+    #[arg(long, value_enum)]
+    preflight: Option<PreflightArg>,
+
+    /// Settings file to use instead of the one saved by the GUI.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// List the available presets and game configurations, then exit.
+    /// With `--remote`, lists the games and compilers of the server instead.
+    #[arg(long)]
+    list: bool,
+
+    /// Run a compile server on this address (`127.0.0.1:27080`, or `0.0.0.0:27080` with `--token`) for the configured games.
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["remote", "list"])]
+    serve: Option<String>,
+
+    /// Compile on the server at this address. The .bsp and a .log are written next to each map.
+    #[arg(long, value_name = "ADDR")]
+    remote: Option<String>,
+
+    /// Secret shared by the server and its clients. Required to serve on a non-loopback address.
+    #[arg(long)]
+    token: Option<String>,
+
+    /// With `--serve`, ids of the compilers clients may run. Built-in steps are never allowed.
+    #[arg(long, value_name = "IDS", value_delimiter = ',', default_value = "vbsp,vvis,vrad", requires = "serve")]
+    allow_compilers: Vec<String>,
+
+    /// Compile every map, even the ones unchanged since their last successful compile.
+    #[arg(short, long)]
+    force: bool,
+
+    /// Print the recorded compiles, the most recent last, then exit.
+    #[arg(long, conflicts_with_all = ["serve", "remote", "list"])]
+    history: bool,
+
+    /// With `--history`, only maps whose name contains this text.
+    #[arg(long, value_name = "NAME", requires = "history")]
+    history_map: Option<String>,
+
+    /// With `--history`, only compiles that ran this step, showing only its timing.
+    #[arg(long, value_name = "STEP", requires = "history")]
+    history_step: Option<String>,
+
+    /// With `--history`, number of most recent compiles printed [default: 20].
+    #[arg(long, value_name = "COUNT", requires = "history")]
+    history_limit: Option<usize>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PreflightArg {
+    /// Do not check the maps.
+    Off,
+    /// Report problems, compile anyway.
+    Warn,
+    /// Fail maps with errors before the first step.
+    Block,
+}
+
+impl From<PreflightArg> for PreflightPolicy {
+    fn from(arg: PreflightArg) -> Self {
+        match arg {
+            PreflightArg::Off => Self::Off,
+            PreflightArg::Warn => Self::Warn,
+            PreflightArg::Block => Self::Block,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(message) => {
+            eprintln!("error: {message}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Runs the batch, returns whether every map compiled.
+fn run(cli: Cli) -> Result<bool, String> {
+    let mut settings = CliSettings::load(cli.config.as_deref())?;
+    load_user_compilers(cli.config.as_deref());
+    for message in migrate_settings(&mut settings.compile_presets, &mut settings.games) {
+        eprintln!("warning: settings migration: {message}");
+    }
+
+    if let Some(addr) = &cli.serve {
+        return remote::serve(addr, settings.games, cli.allow_compilers, cli.token).map(|()| true);
+    }
+    if cli.list && let Some(addr) = &cli.remote {
+        return remote::list(addr, cli.token.as_deref()).map(|()| true);
+    }
+
+    if cli.list {
+        println!("Presets:");
+        settings.compile_presets.iter().for_each(|preset| println!("  {}", preset.name));
+        println!("Game configurations:");
+        settings.games.iter().for_each(|game| println!("  {}", game.name));
+        return Ok(true);
+    }
+
+    let color = !cli.no_color && std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal();
+    let reporter = Arc::new(TerminalReporter {
+        json: cli.json,
+        color,
+        quiet: cli.quiet,
+    });
+
+    if cli.history {
+        let path = data_file_path(cli.config.as_deref(), COMPILE_HISTORY_FILE)
+            .ok_or("Failed to locate the compile history")?;
+        let records = history::load(&path).map_err(|e| format!("Failed to read '{}': {e}", path.display()))?;
+        let query = HistoryQuery {
+            map: cli.history_map,
+            step: cli.history_step.clone(),
+            limit: Some(cli.history_limit.unwrap_or(20)),
+        };
+        reporter.print_history(&query.apply(records), cli.history_step.as_deref());
+        return Ok(true);
+    }
+
+    let mut preset = settings.find_preset(cli.preset.as_deref())?.clone();
+    if let Some(preflight) = cli.preflight {
+        preset.preflight = preflight.into();
+    }
+    let game = settings.find_game(cli.game.as_deref())?.clone();
+
+    let maps: Vec<VmfMap> = expand_map_paths(&cli.maps)?
+        .into_iter()
+        .enumerate()
+        .map(|(order_idx, path)| VmfMap {
+            name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            path,
+            activated: true,
+            order_idx,
+        })
+        .collect();
+
+    if let Some(addr) = &cli.remote {
+        return remote::compile(addr, cli.token.as_deref(), &preset, &game, &maps, reporter.as_ref());
+    }
+
+    let mut session = CompilationSession::new(preset, game, cli.jobs.max(1), Some(reporter.clone()));
+    if let Some(history) = data_file_path(cli.config.as_deref(), LIMIT_BUDGETS_FILE) {
+        session = session.with_budget_history(history);
+    }
+    if let Some(history) = data_file_path(cli.config.as_deref(), COMPILE_HISTORY_FILE) {
+        session = session.with_compile_history(history);
+    }
+    if let Some(fingerprints) = data_file_path(cli.config.as_deref(), FINGERPRINTS_FILE) {
+        session = session.with_fingerprints(fingerprints).with_force_rebuild(cli.force);
+    }
+    if let Some(settings_dir) = settings_dir(cli.config.as_deref()) {
+        session = session.with_log_files(settings.logs.resolve_dir(&settings_dir), settings.logs.keep_batches);
+    }
+    let report = session
+        .start_batch(maps)
+        .join()
+        .map_err(|_| "Compilation thread panicked".to_string())?;
+
+    Ok(report.is_success())
+}
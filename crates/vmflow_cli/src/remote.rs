@@ -0,0 +1,72 @@
+use std::{fs, path::Path};
+
+use compilation_core::JobEventHandler;
+use compilation_core::content::{collect_referenced_content, GameContent};
+use networking_core::{CompileServer, RemoteClient, RemoteJob, ServerConfig};
+use vmflow_config_types::{preset::Preset, GameConfiguration, VmfMap};
+
+/// Serves remote compile jobs until the process is stopped.
+pub fn serve(addr: &str, games: Vec<GameConfiguration>, allowed_compilers: Vec<String>, token: Option<String>) -> Result<(), String> {
+    let config = ServerConfig {
+        games,
+        work_dir: std::env::temp_dir().join("vmflow_remote"),
+        allowed_compilers,
+        token,
+    };
+    let server = CompileServer::bind(addr, config).map_err(|e| format!("Failed to listen on {addr}: {e}"))?;
+    let local_addr = server.local_addr().map_err(|e| e.to_string())?;
+    eprintln!("Compile server listening on {local_addr}");
+
+    server.run().map_err(|e| format!("Compile server stopped: {e}"))
+}
+
+/// Prints the games and compilers of a server.
+pub fn list(addr: &str, token: Option<&str>) -> Result<(), String> {
+    let client = RemoteClient::connect(addr, token).map_err(|e| format!("{addr}: {e}"))?;
+    for game in &client.server_info().games {
+        println!("{}", game.name);
+        println!("  compilers: {}", game.compilers.join(", "));
+    }
+    Ok(())
+}
+
+/// Compiles the maps one after the other on a server, returns whether every map compiled.
+///
+/// The game is looked up on the server by the name of the local game configuration.
+/// Loose custom content the maps use is uploaded along with them.
+pub fn compile(
+    addr: &str,
+    token: Option<&str>,
+    preset: &Preset,
+    game: &GameConfiguration,
+    maps: &[VmfMap],
+    reporter: &dyn JobEventHandler,
+) -> Result<bool, String> {
+    let game_content = GameContent::load(Path::new(&game.game_dir));
+    let mut success = true;
+
+    for map in maps {
+        let content = collect_referenced_content(&map.path, &game_content)
+            .map_err(|e| format!("Failed to read '{}': {e}", map.path.display()))?;
+        let job = RemoteJob {
+            game: game.name.clone(),
+            preset: preset.clone(),
+            map_path: map.path.clone(),
+            content,
+        };
+
+        let result = RemoteClient::connect(addr, token)
+            .and_then(|client| client.compile(&job, reporter))
+            .map_err(|e| format!("{addr}: {e}"))?;
+
+        if let Some(bsp) = &result.bsp {
+            let bsp_path = map.path.with_extension("bsp");
+            fs::write(&bsp_path, bsp).map_err(|e| format!("Failed to write '{}': {e}", bsp_path.display()))?;
+        }
+        let log_path = map.path.with_extension("log");
+        fs::write(&log_path, &result.log).map_err(|e| format!("Failed to write '{}': {e}", log_path.display()))?;
+
+        success &= result.report.is_success();
+    }
+    Ok(success)
+}
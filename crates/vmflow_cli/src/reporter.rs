@@ -0,0 +1,362 @@
+use std::time::Duration;
+
+use compilation_core::history::{format_timestamp, CompileRecord, StepRecord};
+use compilation_core::{format_point, BatchReport, CoreEvent, JobEventHandler, MapReport, MapStatus, Severity, StepOutcome};
+use serde_json::{json, Value};
+use vmflow_config_types::validation::IssueSeverity;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+
+/// Prints compilation events to stdout, as coloured text or as JSON lines.
+pub struct TerminalReporter {
+    pub json: bool,
+    pub color: bool,
+    /// Hides compiler output, only diagnostics and step results are printed.
+    pub quiet: bool,
+}
+
+impl JobEventHandler for TerminalReporter {
+    fn handle_event(&self, event: CoreEvent) {
+        if self.json {
+            println!("{}", event_to_json(&event));
+        } else if let Some(line) = self.event_to_text(&event) {
+            println!("{line}");
+        }
+    }
+}
+
+impl TerminalReporter {
+    fn paint(&self, style: &str, text: impl AsRef<str>) -> String {
+        if self.color {
+            format!("{style}{}{RESET}", text.as_ref())
+        } else {
+            text.as_ref().to_string()
+        }
+    }
+
+    fn event_to_text(&self, event: &CoreEvent) -> Option<String> {
+        let text = match event {
+            CoreEvent::BatchStarted => self.paint(BOLD, "Batch started"),
+            CoreEvent::ValidationIssues(issues) => issues.iter()
+                .map(|issue| match issue.severity {
+                    IssueSeverity::Error => self.paint(RED, format!("preset error: {issue}")),
+                    IssueSeverity::Warning => self.paint(YELLOW, format!("preset warning: {issue}")),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            CoreEvent::MapStarted(_, map) => self.paint(CYAN, format!("==> {map}")),
+            CoreEvent::StepStarted(_, map, step) => self.paint(BOLD, format!("[{map}] {step}")),
+
+            CoreEvent::StepLog(_, map, step, line) if !self.quiet => format!("[{map}] {step}: {line}"),
+            CoreEvent::StepWarn(_, map, step, line) => self.paint(YELLOW, format!("[{map}] {step}: {line}")),
+            CoreEvent::StepErr(_, map, step, line) if !self.quiet => self.paint(RED, format!("[{map}] {step}: {line}")),
+            // Diagnostics repeat a log line, they are only worth printing when logs are hidden
+            CoreEvent::StepDiagnostic(_, map, step, diagnostic) if self.quiet => {
+                let (style, label) = match diagnostic.severity {
+                    Severity::Error => (RED, "error"),
+                    Severity::Warning => (YELLOW, "warning"),
+                    Severity::Info => (DIM, "info"),
+                };
+                self.paint(style, format!("[{map}] {step} {label}: {}", diagnostic.message))
+            }
+
+            CoreEvent::StepProgress(_, map, step, progress)
+                if !progress.phase.is_empty() && progress.phase_fraction >= 1.0 =>
+            {
+                self.paint(DIM, format!(
+                    "[{map}] {step}: {} done ({:.0}% of batch)",
+                    progress.phase,
+                    progress.batch_fraction * 100.0
+                ))
+            }
+            CoreEvent::StepStatistic(_, map, step, name, value) => {
+                self.paint(DIM, format!("[{map}] {step}: {value} {name}"))
+            }
+
+            CoreEvent::LeakDetected(_, map, leak) => {
+                let entity = leak.entity.as_ref().map_or("unknown entity".to_string(), |entity| {
+                    format!("{} at ({})", entity.classname, format_point(&entity.origin))
+                });
+                let pointfile = leak.pointfile_path.as_ref()
+                    .map_or("not found".to_string(), |path| path.display().to_string());
+                self.paint(RED, format!(
+                    "[{map}] LEAK: {entity}, path length {:.0} units, pointfile: {pointfile}",
+                    leak.length()
+                ))
+            }
+
+            CoreEvent::BspSummary(_, map, summary) => self.paint(CYAN, format!("[{map}] BSP: {summary}")),
+            CoreEvent::LimitAlert(_, map, alert) => {
+                let style = if alert.is_error() { RED } else { YELLOW };
+                self.paint(style, format!("[{map}] LIMIT: {alert}"))
+            }
+
+            CoreEvent::StepFinished(_, map, step) => self.paint(GREEN, format!("[{map}] {step} finished")),
+            CoreEvent::StepFailed(_, map, step, error) => self.paint(RED, format!("[{map}] {step} failed: {error}")),
+            CoreEvent::StepSkipped(_, map, step, reason) => self.paint(DIM, format!("[{map}] {step} skipped: {reason}")),
+            CoreEvent::MapFinished(_, _, report) => self.map_summary(report, "==> "),
+            CoreEvent::BatchCompleted(report) => self.batch_summary(report),
+            CoreEvent::BatchCancelled => self.paint(YELLOW, "Batch cancelled"),
+            CoreEvent::CancellationRequested => self.paint(YELLOW, "Cancellation requested"),
+            _ => return None,
+        };
+        Some(text)
+    }
+
+    /// One line describing how a map ended.
+    fn map_summary(&self, report: &MapReport, prefix: &str) -> String {
+        let counts = format!(
+            "{} warning(s), {} error(s), {}",
+            report.warnings(),
+            report.errors(),
+            format_duration(report.duration)
+        );
+        match &report.status {
+            MapStatus::Succeeded => self.paint(GREEN, format!("{prefix}{} compiled ({counts})", report.map_name)),
+            MapStatus::Failed { step, exit_code, error } => {
+                let exit_code = exit_code.map(|code| format!(", exit code {code}")).unwrap_or_default();
+                self.paint(RED, format!("{prefix}{} failed at {step}{exit_code}: {error} ({counts})", report.map_name))
+            }
+            MapStatus::Cancelled => self.paint(YELLOW, format!("{prefix}{} cancelled", report.map_name)),
+            MapStatus::Skipped => self.paint(DIM, format!("{prefix}{} skipped", report.map_name)),
+            MapStatus::UpToDate => self.paint(DIM, format!("{prefix}{} skipped, unchanged since its last compile", report.map_name)),
+        }
+    }
+
+    /// Prints compile history records, with only the timing of `step` if given.
+    pub fn print_history(&self, records: &[CompileRecord], step: Option<&str>) {
+        if self.json {
+            records.iter().for_each(|record| println!("{}", json!(record)));
+            return;
+        }
+        if records.is_empty() {
+            println!("No compiles recorded");
+        }
+        for record in records {
+            let (style, status) = match &record.status {
+                MapStatus::Succeeded => (GREEN, "succeeded".to_string()),
+                MapStatus::Failed { step, .. } => (RED, format!("failed at {step}")),
+                MapStatus::Cancelled => (YELLOW, "cancelled".to_string()),
+                MapStatus::Skipped | MapStatus::UpToDate => (DIM, "skipped".to_string()),
+            };
+            println!("{}", self.paint(style, format!(
+                "{}  {} {status} in {}, preset {}, game {}, {} warning(s), {} error(s)",
+                format_timestamp(record.started_at),
+                record.map_name,
+                format_duration(record.duration()),
+                record.preset,
+                record.game,
+                record.warnings(),
+                record.errors()
+            )));
+
+            let steps: Vec<&StepRecord> = match step {
+                Some(name) => record.step(name).into_iter().collect(),
+                None => record.steps.iter().collect(),
+            };
+            for step in steps {
+                let exit_code = step.exit_code.map(|code| format!(", exit code {code}")).unwrap_or_default();
+                let parameters = step.parameters.iter().map(|part| format!(" {part}")).collect::<String>();
+                println!("{}", self.paint(DIM, format!(
+                    "    {} {} ({}{exit_code}){parameters}",
+                    step.name,
+                    format_duration(step.duration),
+                    step_outcome_name(step.outcome)
+                )));
+            }
+            if let Some(hash) = &record.bsp_hash {
+                println!("{}", self.paint(DIM, format!("    bsp sha256 {hash}")));
+            }
+            if let Some(log) = &record.log_file {
+                println!("{}", self.paint(DIM, format!("    log {}", log.display())));
+            }
+        }
+    }
+
+    fn batch_summary(&self, report: &BatchReport) -> String {
+        let mut lines = vec![self.paint(BOLD, format!(
+            "Batch {:?} in {}: {} warning(s), {} error(s)",
+            report.outcome,
+            format_duration(report.duration),
+            report.warnings(),
+            report.errors()
+        ))];
+        for map in &report.maps {
+            lines.push(self.map_summary(map, "  "));
+            for step in &map.steps {
+                lines.push(self.paint(DIM, format!(
+                    "    {} {} ({})",
+                    step.name,
+                    format_duration(step.duration),
+                    step_outcome_name(step.outcome)
+                )));
+            }
+            if let Some(log) = &map.log_file {
+                lines.push(self.paint(DIM, format!("    log {}", log.display())));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let total_seconds = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", total_seconds / 3600, (total_seconds % 3600) / 60, total_seconds % 60)
+}
+
+fn step_outcome_name(outcome: StepOutcome) -> &'static str {
+    match outcome {
+        StepOutcome::Succeeded => "succeeded",
+        StepOutcome::Failed => "failed",
+        StepOutcome::FailureIgnored => "failure ignored",
+        StepOutcome::Skipped => "skipped",
+    }
+}
+
+fn map_report_to_json(report: &MapReport) -> Value {
+    let (status, step, exit_code, error) = match &report.status {
+        MapStatus::Succeeded => ("succeeded", None, None, None),
+        MapStatus::Failed { step, exit_code, error } => ("failed", Some(step), *exit_code, Some(error)),
+        MapStatus::Cancelled => ("cancelled", None, None, None),
+        MapStatus::Skipped | MapStatus::UpToDate => ("skipped", None, None, None),
+    };
+    json!({
+        "map_id": report.map_id,
+        "map": report.map_name,
+        "path": report.path,
+        "status": status,
+        "up_to_date": report.status == MapStatus::UpToDate,
+        "failed_step": step,
+        "exit_code": exit_code,
+        "error": error,
+        "duration_secs": report.duration.as_secs_f64(),
+        "warnings": report.warnings(),
+        "errors": report.errors(),
+        "steps": report.steps.iter().map(|step| json!({
+            "name": step.name,
+            "outcome": step_outcome_name(step.outcome),
+            "duration_secs": step.duration.as_secs_f64(),
+            "exit_code": step.exit_code,
+            "warnings": step.warnings,
+            "errors": step.errors,
+        })).collect::<Vec<_>>(),
+        "bsp": report.bsp,
+        "log_file": report.log_file,
+    })
+}
+
+/// Converts an event into a single JSON object with an `event` tag.
+fn event_to_json(event: &CoreEvent) -> Value {
+    match event {
+        CoreEvent::BatchStarted => json!({ "event": "batch_started" }),
+        CoreEvent::ValidationIssues(issues) => json!({
+            "event": "validation_issues",
+            "issues": issues.iter().map(|issue| json!({
+                "step_idx": issue.step_idx,
+                "compiler": issue.compiler,
+                "parameter_idx": issue.parameter_idx,
+                "parameter": issue.parameter,
+                "severity": format!("{:?}", issue.severity).to_lowercase(),
+                "message": issue.kind.to_string(),
+            })).collect::<Vec<_>>(),
+        }),
+        CoreEvent::MapStarted(map_id, map) => json!({ "event": "map_started", "map_id": map_id, "map": map }),
+        CoreEvent::StepStarted(map_id, map, step) => {
+            json!({ "event": "step_started", "map_id": map_id, "map": map, "step": step })
+        }
+        CoreEvent::StepLog(map_id, map, step, line) => {
+            json!({ "event": "step_log", "map_id": map_id, "map": map, "step": step, "line": line })
+        }
+        CoreEvent::StepWarn(map_id, map, step, line) => {
+            json!({ "event": "step_warn", "map_id": map_id, "map": map, "step": step, "line": line })
+        }
+        CoreEvent::StepErr(map_id, map, step, line) => {
+            json!({ "event": "step_err", "map_id": map_id, "map": map, "step": step, "line": line })
+        }
+        CoreEvent::StepDiagnostic(map_id, map, step, diagnostic) => json!({
+            "event": "step_diagnostic",
+            "map_id": map_id,
+            "map": map,
+            "step": step,
+            "severity": format!("{:?}", diagnostic.severity).to_lowercase(),
+            "kind": format!("{:?}", diagnostic.kind),
+            "message": diagnostic.message,
+            "brush_id": diagnostic.brush_id,
+            "entity_id": diagnostic.entity_id,
+            "side_id": diagnostic.side_id,
+        }),
+        CoreEvent::StepProgress(map_id, map, step, progress) => json!({
+            "event": "step_progress",
+            "map_id": map_id,
+            "map": map,
+            "step": step,
+            "phase": progress.phase,
+            "phase_fraction": progress.phase_fraction,
+            "step_fraction": progress.step_fraction,
+            "map_fraction": progress.map_fraction,
+            "batch_fraction": progress.batch_fraction,
+        }),
+        CoreEvent::StepStatistic(map_id, map, step, name, value) => json!({
+            "event": "step_statistic", "map_id": map_id, "map": map, "step": step, "name": name, "value": value,
+        }),
+        CoreEvent::LeakDetected(map_id, map, leak) => json!({
+            "event": "leak_detected",
+            "map_id": map_id,
+            "map": map,
+            "pointfile": leak.pointfile_path,
+            "start": leak.start(),
+            "length": leak.length(),
+            "entity": leak.entity.as_ref().map(|entity| json!({
+                "classname": entity.classname,
+                "origin": entity.origin,
+            })),
+        }),
+        CoreEvent::BspSummary(map_id, map, summary) => json!({
+            "event": "bsp_summary",
+            "map_id": map_id,
+            "map": map,
+            "summary": summary,
+        }),
+        CoreEvent::LimitAlert(map_id, map, alert) => json!({
+            "event": "limit_alert",
+            "map_id": map_id,
+            "map": map,
+            "limit": alert.limit.name,
+            "used": alert.limit.used,
+            "max": alert.limit.max,
+            "kind": alert.kind,
+            "message": alert.to_string(),
+        }),
+        CoreEvent::StepFinished(map_id, map, step) => {
+            json!({ "event": "step_finished", "map_id": map_id, "map": map, "step": step })
+        }
+        CoreEvent::StepFailed(map_id, map, step, error) => {
+            json!({ "event": "step_failed", "map_id": map_id, "map": map, "step": step, "error": error })
+        }
+        CoreEvent::StepSkipped(map_id, map, step, reason) => {
+            json!({ "event": "step_skipped", "map_id": map_id, "map": map, "step": step, "reason": reason })
+        }
+        CoreEvent::MapFinished(_, _, report) => {
+            let mut value = map_report_to_json(report);
+            value["event"] = json!("map_finished");
+            value
+        }
+        CoreEvent::BatchCompleted(report) => json!({
+            "event": "batch_completed",
+            "outcome": format!("{:?}", report.outcome),
+            "success": report.is_success(),
+            "duration_secs": report.duration.as_secs_f64(),
+            "warnings": report.warnings(),
+            "errors": report.errors(),
+            "maps": report.maps.iter().map(map_report_to_json).collect::<Vec<_>>(),
+        }),
+        CoreEvent::BatchCancelled => json!({ "event": "batch_cancelled" }),
+        CoreEvent::CancellationRequested => json!({ "event": "cancellation_requested" }),
+    }
+}
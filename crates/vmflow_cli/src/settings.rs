@@ -0,0 +1,120 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use vmflow_config_types::{preset::Preset, GameConfiguration, LogSettings, CONFIG_APP_NAME, CONFIG_NAME, USER_COMPILERS_DIR};
+
+/// The part of the GUI settings the CLI needs. Other fields of the file are ignored.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CliSettings {
+    pub compile_presets: Vec<Preset>,
+    pub games: Vec<GameConfiguration>,
+    pub current_preset_index: usize,
+    pub current_game_index: usize,
+    pub logs: LogSettings,
+}
+
+impl CliSettings {
+    /// Loads the settings saved by the GUI, or the file at `path` if given.
+    ///
+    /// Unlike `confy::load`, a missing file is an error: the CLI never writes settings.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => confy::get_configuration_file_path(CONFIG_APP_NAME, CONFIG_NAME)
+                .map_err(|e| format!("Failed to locate the settings file: {e}"))?,
+        };
+        if !path.is_file() {
+            return Err(format!(
+                "Settings file '{}' does not exist. Configure a game and a preset in VMFlow first",
+                path.display()
+            ));
+        }
+
+        confy::load_path(&path).map_err(|e| format!("Failed to load '{}': {e}", path.display()))
+    }
+
+    /// Finds a preset by name, or returns the preset selected in the GUI.
+    pub fn find_preset(&self, name: Option<&str>) -> Result<&Preset, String> {
+        find_by_name(&self.compile_presets, name, self.current_preset_index, |p| &p.name, "preset")
+    }
+
+    /// Finds a game configuration by name, or returns the one selected in the GUI.
+    pub fn find_game(&self, name: Option<&str>) -> Result<&GameConfiguration, String> {
+        find_by_name(&self.games, name, self.current_game_index, |g| &g.name, "game configuration")
+    }
+}
+
+/// Loads the user compiler configs next to the settings file, invalid ones are reported on stderr.
+pub fn load_user_compilers(config: Option<&Path>) {
+    let Some(dir) = settings_dir(config).map(|dir| dir.join(USER_COMPILERS_DIR)) else {
+        return;
+    };
+    for error in compilers_service::load_user_configs(&dir) {
+        eprintln!("warning: compiler config {error}");
+    }
+}
+
+/// Folder of the settings file, `config` or the one saved by the GUI.
+pub fn settings_dir(config: Option<&Path>) -> Option<PathBuf> {
+    let settings_path = match config {
+        Some(path) => path.to_path_buf(),
+        None => confy::get_configuration_file_path(CONFIG_APP_NAME, CONFIG_NAME).ok()?,
+    };
+    Some(settings_path.parent()?.to_path_buf())
+}
+
+/// File named `name` next to the settings file, where the GUI keeps its data files too.
+pub fn data_file_path(config: Option<&Path>, name: &str) -> Option<PathBuf> {
+    settings_dir(config).map(|dir| dir.join(name))
+}
+
+fn find_by_name<'a, T>(
+    items: &'a [T],
+    name: Option<&str>,
+    current_idx: usize,
+    item_name: impl Fn(&T) -> &String,
+    what: &str,
+) -> Result<&'a T, String> {
+    let found = match name {
+        Some(name) => items.iter().find(|item| item_name(item).eq_ignore_ascii_case(name)),
+        None => items.get(current_idx),
+    };
+
+    found.ok_or_else(|| {
+        let available: Vec<&str> = items.iter().map(|item| item_name(item).as_str()).collect();
+        match name {
+            Some(name) => format!("No {what} named '{name}'. Available: {}", available.join(", ")),
+            None => format!("No {what} is selected. Available: {}", available.join(", ")),
+        }
+    })
+}
+
+/// Expands map arguments into .vmf files.
+///
+/// Arguments may be files, folders (searched recursively) or glob patterns.
+pub fn expand_map_paths(patterns: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut maps: Vec<PathBuf> = Vec::new();
+
+    for pattern in patterns {
+        let pattern = if Path::new(pattern).is_dir() {
+            format!("{}/**/*.vmf", pattern.trim_end_matches(['/', '\\']))
+        } else {
+            pattern.clone()
+        };
+
+        let paths = glob::glob(&pattern).map_err(|e| format!("Invalid map pattern '{pattern}': {e}"))?;
+        let before = maps.len();
+        for path in paths.filter_map(Result::ok) {
+            let is_vmf = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("vmf"));
+            if is_vmf && path.is_file() && !maps.contains(&path) {
+                maps.push(path);
+            }
+        }
+
+        if maps.len() == before {
+            return Err(format!("No .vmf files match '{pattern}'"));
+        }
+    }
+    Ok(maps)
+}
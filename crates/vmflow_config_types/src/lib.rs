@@ -1,9 +1,26 @@
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 use serde::{Deserialize, Serialize};
 
 pub mod selected_compiler;
 pub mod parameter_override;
 pub mod preset;
+pub mod migration;
+pub mod validation;
+
+/// Application name the settings are stored under with confy, shared by the GUI and the CLI.
+pub const CONFIG_APP_NAME: &str = "VMFlow_wrapper";
+/// Name of the settings file inside the confy config directory.
+pub const CONFIG_NAME: &str = "config";
+/// Folder next to the settings file holding user compiler configs (`*.toml`).
+pub const USER_COMPILERS_DIR: &str = "compilers";
+/// File next to the settings file holding the limit usage history of each compiled map.
+pub const LIMIT_BUDGETS_FILE: &str = "limit_budgets.json";
+/// File next to the settings file holding one JSON line per compiled map, see `compilation_core::history`.
+pub const COMPILE_HISTORY_FILE: &str = "compile_history.jsonl";
+/// File next to the settings file holding the input fingerprint of the last successful compile of each map.
+pub const FINGERPRINTS_FILE: &str = "fingerprints.json";
+/// Folder next to the settings file the compile logs are written to, unless [`LogSettings::dir`] is set.
+pub const LOGS_DIR: &str = "logs";
 
 #[derive(Default, Debug, Serialize, Deserialize, Clone, Hash)]
 pub struct VmfMap {
@@ -13,25 +30,77 @@ pub struct VmfMap {
     pub order_idx: usize,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Default, Serialize, Deserialize, Clone)]
 pub struct GameConfiguration {
     pub name: String,
     pub game_dir: String,
     pub bin_dir: String,
     pub output_dir: String,
     pub steam_app_id: Option<u32>,
-    pub custom_apps_paths: Vec<String>, // index -> compiler config
+    /// FGD declaring the entity classes of the game. Empty means every .fgd in `bin_dir`.
+    #[serde(default)]
+    pub fgd: String,
+    /// Executable of each compiler, by compiler id.
+    #[serde(default)]
+    pub compiler_paths: BTreeMap<String, String>,
+    /// Executables by compiler position, saved before compilers had ids, see [`migration`].
+    #[serde(default, rename = "custom_apps_paths", skip_serializing)]
+    pub legacy_apps_paths: Vec<String>,
+    #[serde(default)]
+    pub wine: WineConfiguration,
+}
+
+/// How Windows compilers of a game are launched on Unix-like systems.
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+pub struct WineConfiguration {
+    /// `WINEPREFIX` to run compilers in. Empty means `$WINEPREFIX` or `~/.wine`.
+    pub prefix: String,
+    /// Wine binary to launch compilers with (wine, wine64, Proton's wine). Empty means `wine`.
+    pub binary: String,
+    /// Extra environment variables passed to Wine.
+    pub env_vars: Vec<(String, String)>,
+}
+
+impl WineConfiguration {
+    /// Returns the Wine binary to use.
+    pub fn binary(&self) -> &str {
+        if self.binary.is_empty() { "wine" } else { &self.binary }
+    }
+}
+
+/// Where the full log of every compiled map is written and how long it is kept.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct LogSettings {
+    /// Folder of the logs. Empty means [`LOGS_DIR`] next to the settings file.
+    pub dir: String,
+    /// Batches whose logs are kept, the oldest are deleted. 0 keeps them all.
+    pub keep_batches: usize,
 }
 
-impl Default for GameConfiguration {
+impl Default for LogSettings {
     fn default() -> Self {
-        Self {
-            name: String::new(),
-            game_dir: String::new(),
-            bin_dir: String::new(),
-            output_dir: String::new(),
-            steam_app_id: None,
-            custom_apps_paths: vec![String::new(); compilers_service::total_definitions()],
+        Self { dir: String::new(), keep_batches: 20 }
+    }
+}
+
+impl LogSettings {
+    /// Folder of the logs, `settings_dir` being the folder of the settings file.
+    pub fn resolve_dir(&self, settings_dir: &std::path::Path) -> PathBuf {
+        if self.dir.is_empty() {
+            settings_dir.join(LOGS_DIR)
+        } else {
+            PathBuf::from(&self.dir)
         }
     }
 }
+
+impl GameConfiguration {
+    /// Returns the executable set for a compiler, if any.
+    pub fn compiler_path(&self, compiler_id: &str) -> Option<&str> {
+        self.compiler_paths.get(compiler_id)
+            .map(String::as_str)
+            .filter(|path| !path.is_empty())
+    }
+}
+
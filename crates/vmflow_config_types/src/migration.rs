@@ -0,0 +1,135 @@
+//! Converts settings saved when compilers and parameters were referenced by their position.
+//!
+//! Positions of built-in compilers and of their parameters are frozen below as they were in the last
+//! version saving them, the current lists have changed since. User compilers came after the built-ins,
+//! so they must be loaded before migrating.
+
+use compiler_data_model::CompilerConfig;
+
+use crate::{preset::Preset, GameConfiguration};
+
+/// Built-in compiler ids by their former position.
+const LEGACY_COMPILER_IDS: [&str; 9] = [
+    "vbsp", "vvis", "vrad", "game", "bspzip", "copy", "shutdown", "vbsp_info", "vpk",
+];
+
+/// Parameter ids of each built-in compiler by their former position. Compilers missing had no parameters.
+const LEGACY_PARAMETER_IDS: [(&str, &[&str]); 6] = [
+    ("vbsp", &[
+        "onlyents", "onlyprops", "nodetail", "notjunc", "nodrawtriggers", "allowdetailcracks", "nowater",
+        "leaktest", "micro", "alldetail", "fulldetail", "normal_priority", "verbose", "staticpropcombine",
+        "keepsources", "staticpropcombine_considervis", "staticpropcombine_autocombine",
+        "staticpropcombine_suggestrules", "staticpropcombine_mininstances", "staticpropcombine_printcombinerules",
+        "staticpropcombine_colorinstances", "combineignore_fastreflection", "combineignore_normals",
+        "combineignore_noshadow", "combineignore_novertexlighting", "combineignore_noflashlight",
+        "combineignore_noselfshadowing", "combineignore_disableshadowdepth", "allowdynamicpropsasstatic",
+    ]),
+    ("vvis", &[
+        "fast", "radius_override", "nosort", "tmpin", "tmpout", "threads", "low", "normal_priority", "verbose",
+    ]),
+    ("vrad", &[
+        "bounce", "ldr", "hdr", "both", "fast", "final", "extrasky", "lights", "smooth", "luxeldensity",
+        "staticproplighting", "staticproplightingfinal", "staticproplightingold", "staticpropbounce",
+        "staticpropsamplescale", "staticproppolys", "onlystaticprops", "nossprops", "textureshadows", "aoscale",
+        "threads", "low", "normal_priority", "noskyboxrecurse", "dumppropmaps", "noextra", "verbose",
+    ]),
+    ("game", &[
+        "tools", "novid", "dev", "console", "windowed", "width", "height", "noborder", "hijack", "insecure",
+        "makedevshots", "sv_cheats_1", "mp_enableroundwaittime_0", "command_line_argument",
+    ]),
+    ("bspzip", &["addlist", "deletecubemaps", "repack", "compress"]),
+    ("vpk", &["l", "x", "e", "a", "response_file", "file_path"]),
+];
+
+/// Replaces the positional references of presets and game configurations with ids.
+///
+/// Returns a message for every reference that could not be mapped. Unmapped overrides are removed,
+/// unmapped steps stay as a missing compiler so the user sees them in the preset.
+pub fn migrate_settings(presets: &mut [Preset], games: &mut [GameConfiguration]) -> Vec<String> {
+    let mut unmapped = Vec::new();
+    for preset in presets {
+        migrate_preset(preset, &mut unmapped);
+    }
+    for game in games {
+        migrate_game(game, &mut unmapped);
+    }
+    unmapped
+}
+
+/// Id of the compiler at a former position: a built-in, then the user compilers in load order.
+fn legacy_compiler_id(idx: usize) -> Option<&'static str> {
+    match idx.checked_sub(LEGACY_COMPILER_IDS.len()) {
+        None => Some(LEGACY_COMPILER_IDS[idx]),
+        Some(user_idx) => compilers_service::iter_configs()
+            .skip(compilers_service::builtin_definitions())
+            .nth(user_idx)
+            .map(|config| config.id.as_str()),
+    }
+}
+
+/// Id of the parameter at a former position of a compiler.
+///
+/// User compilers are the user's own files, their parameters keep the positions they have now.
+fn legacy_parameter_id(config: &'static CompilerConfig, idx: usize) -> Option<&'static str> {
+    if !LEGACY_COMPILER_IDS.contains(&config.id.as_str()) {
+        return config.parameters.get(idx).map(|parameter| parameter.id.as_str());
+    }
+    LEGACY_PARAMETER_IDS.iter()
+        .find(|(compiler_id, _)| *compiler_id == config.id)
+        .and_then(|(_, parameter_ids)| parameter_ids.get(idx).copied())
+        .filter(|parameter_id| config.find_parameter(parameter_id).is_some())
+}
+
+fn migrate_preset(preset: &mut Preset, unmapped: &mut Vec<String>) {
+    for (step_idx, step) in preset.apps.iter_mut().enumerate() {
+        if let Some(idx) = step.legacy_compiler_idx.take() {
+            match legacy_compiler_id(idx) {
+                Some(id) => step.compiler_id = id.to_string(),
+                None => unmapped.push(format!(
+                    "Preset '{}', step {}: no compiler at former index {idx}",
+                    preset.name,
+                    step_idx + 1
+                )),
+            }
+        }
+
+        let config = step.config();
+        step.parameters.retain_mut(|parameter| {
+            let Some(idx) = parameter.legacy_parameter_idx.take() else {
+                return true;
+            };
+            match legacy_parameter_id(config, idx) {
+                Some(parameter_id) => {
+                    parameter.compiler_id = config.id.clone();
+                    parameter.parameter_id = parameter_id.to_string();
+                    true
+                }
+                None => {
+                    let value = parameter.value.as_deref().map(|value| format!(" = '{value}'")).unwrap_or_default();
+                    unmapped.push(format!(
+                        "Preset '{}', {}: parameter at former index {idx}{value} removed, {} has no such parameter",
+                        preset.name, config.name, config.name
+                    ));
+                    false
+                }
+            }
+        });
+    }
+}
+
+fn migrate_game(game: &mut GameConfiguration, unmapped: &mut Vec<String>) {
+    for (idx, path) in std::mem::take(&mut game.legacy_apps_paths).into_iter().enumerate() {
+        if path.is_empty() {
+            continue;
+        }
+        match legacy_compiler_id(idx) {
+            Some(id) => {
+                game.compiler_paths.entry(id.to_string()).or_insert(path);
+            }
+            None => unmapped.push(format!(
+                "Game '{}': no compiler at former index {idx}, its path '{path}' was dropped",
+                game.name
+            )),
+        }
+    }
+}
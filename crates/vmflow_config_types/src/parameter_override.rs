@@ -4,26 +4,32 @@ use serde::{Deserialize, Serialize};
 /// Structure representing an override for a parameter.
 #[derive(Default, Serialize, Deserialize, Clone, Hash)]
 pub struct ParameterOverride {
-    pub compiler_idx: usize,
-    pub parameter_idx: usize,
+    #[serde(default)]
+    pub compiler_id: String,
+    #[serde(default)]
+    pub parameter_id: String,
     pub value: Option<String>,
     pub activated: bool,
+    /// Position of the parameter in overrides saved before parameters had ids, see [`crate::migration`].
+    #[serde(default, rename = "parameter_idx", skip_serializing)]
+    pub legacy_parameter_idx: Option<usize>,
 }
 
 impl ParameterOverride {
     /// Create a new parameter override
-    pub fn new(compiler_idx: usize, parameter_idx: usize) -> Self {
+    pub fn new(compiler_id: &str, parameter_id: &str) -> Self {
         Self {
-            compiler_idx,
-            parameter_idx,
+            compiler_id: compiler_id.to_string(),
+            parameter_id: parameter_id.to_string(),
             value: None,
             activated: true,
+            legacy_parameter_idx: None,
         }
     }
     
     /// Get parameter definition
     pub fn parameter(&self) -> Option<&'static Parameter> {
-        compilers_service::get_parameter(self.compiler_idx, self.parameter_idx)
+        compilers_service::get_parameter(&self.compiler_id, &self.parameter_id)
     }
     
     /// Get parameter name
@@ -60,6 +66,13 @@ impl ParameterOverride {
             .and_then(|p| p.default_value.as_deref())
     }
     
+    /// Get the value passed to the compiler: the override value or the default one
+    pub fn value_or_default(&self) -> Option<&str> {
+        self.value.as_deref()
+            .or_else(|| self.default_value())
+            .filter(|value| !value.is_empty())
+    }
+
     /// todo comm
     pub fn get_command_parts(&self) -> Option<Vec<String>> {
         if !self.activated {
@@ -69,9 +82,15 @@ impl ParameterOverride {
         self.parameter().map(|param| {
             match param.value_type {
                 compiler_data_model::ParameterType::Flag => vec![param.argument.clone()],
+                compiler_data_model::ParameterType::Bool => {
+                    let enabled = self.value_or_default()
+                        .and_then(compiler_data_model::parse_bool)
+                        .unwrap_or(false);
+                    vec![param.argument.clone(), if enabled { "1" } else { "0" }.to_string()]
+                }
                 _ => {
                     let value_str = self.value.as_ref()
-                        .or_else(|| param.default_value.as_ref())
+                        .or(param.default_value.as_ref())
                         .cloned()
                         .unwrap_or_default();
                     if param.argument.is_empty() && value_str.is_empty() {
@@ -6,6 +6,50 @@ use crate::selected_compiler::SelectedCompiler;
 pub struct Preset {
     pub name: String,
     pub apps: Vec<SelectedCompiler>,
+    #[serde(default)]
+    pub preflight: PreflightPolicy,
+    #[serde(default)]
+    pub budget: LimitBudget,
+}
+
+/// When the usage of the engine limits of a compiled map is worth a warning.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct LimitBudget {
+    /// Usage of a `MAX_MAP_*` limit warned about, in percent of the limit.
+    pub warn_percent: f32,
+    /// Growth of a limit usage since the last compile warned about, in percent of the limit.
+    pub jump_percent: f32,
+}
+
+impl Default for LimitBudget {
+    fn default() -> Self {
+        Self { warn_percent: 80.0, jump_percent: 10.0 }
+    }
+}
+
+/// Whether maps are checked for common mistakes before the first step, and what errors mean.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PreflightPolicy {
+    Off,
+    /// Problems are reported, the map compiles anyway.
+    #[default]
+    Warn,
+    /// Errors fail the map before VBSP runs.
+    Block,
+}
+
+impl PreflightPolicy {
+    pub const ALL: [PreflightPolicy; 3] = [Self::Off, Self::Warn, Self::Block];
+
+    /// Name shown in the preset editor.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Warn => "Report only",
+            Self::Block => "Block on errors",
+        }
+    }
 }
 
 impl Preset {
@@ -13,7 +57,12 @@ impl Preset {
         self.apps.push(SelectedCompiler::new(name));
     }
 
-    pub fn add_app_from_idx(&mut self, idx: usize) {
-        self.apps.push(SelectedCompiler::from_idx(idx));
+    /// Checks the activated steps and parameters, see [`crate::validation`].
+    pub fn validate(&self, game: Option<&crate::GameConfiguration>) -> Vec<crate::validation::ValidationIssue> {
+        crate::validation::validate_preset(self, game)
+    }
+
+    pub fn add_app_from_id(&mut self, id: &str) {
+        self.apps.push(SelectedCompiler::from_id(id));
     }
 }
\ No newline at end of file
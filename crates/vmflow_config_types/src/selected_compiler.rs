@@ -4,64 +4,114 @@ use super::parameter_override::ParameterOverride;
 
 #[derive(Default, Serialize, Deserialize, Clone, Hash)]
 pub struct SelectedCompiler {
-    pub compiler_idx: usize,
+    /// Id of the compiler config.
+    #[serde(default)]
+    pub compiler_id: String,
     pub activated: bool,
     pub parameters: Vec<ParameterOverride>,
+    #[serde(default)]
+    pub policy: StepPolicy,
+    /// Position of the compiler in presets saved before compilers had ids, see [`crate::migration`].
+    #[serde(default, rename = "compiler_idx", skip_serializing)]
+    pub legacy_compiler_idx: Option<usize>,
+}
+
+/// When a step of a preset runs, and what its failure means for the rest of the map.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StepPolicy {
+    /// Runs unless a previous step failed, a failure stops the map.
+    #[default]
+    StopOnFailure,
+    /// Like `StopOnFailure`, but a failure is ignored and the map goes on.
+    ContinueOnFailure,
+    /// Runs only if the step right before it ran and succeeded.
+    RunIfPreviousSucceeded,
+    /// Runs even after a failure, e.g. for cleanup or notifications.
+    AlwaysRun,
+    /// Runs only if the map source is newer than its compiled .bsp.
+    RunIfMapChanged,
+}
+
+impl StepPolicy {
+    pub const ALL: [StepPolicy; 5] = [
+        Self::StopOnFailure,
+        Self::ContinueOnFailure,
+        Self::RunIfPreviousSucceeded,
+        Self::AlwaysRun,
+        Self::RunIfMapChanged,
+    ];
+
+    /// Name shown in the preset editor.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::StopOnFailure => "Stop on failure",
+            Self::ContinueOnFailure => "Continue on failure",
+            Self::RunIfPreviousSucceeded => "Run if previous succeeded",
+            Self::AlwaysRun => "Always run",
+            Self::RunIfMapChanged => "Run if map changed",
+        }
+    }
 }
 
 
 impl SelectedCompiler {
     /// Create a new SelectedCompiler by compiler name
     pub fn new(name: &str) -> Self {
-        let compiler_idx = compilers_service::find_compiler_idx(name).unwrap_or(0);
-        Self {
-            compiler_idx,
-            activated: true,
-            parameters: Vec::new(),
-        }
+        let compiler_id = compilers_service::get_compiler_by_name(name)
+            .map(|config| config.id.clone())
+            .unwrap_or_default();
+        Self::from_id(&compiler_id)
     }
     
-    /// Create a new SelectedCompiler by compiler index
-    pub fn from_idx(idx: usize) -> Self {
+    /// Create a new SelectedCompiler by compiler id
+    pub fn from_id(id: &str) -> Self {
         Self {
-            compiler_idx: idx,
+            compiler_id: id.to_string(),
             activated: true,
-            parameters: Vec::new(),
+            ..Default::default()
         }
     }
 
     /// Get CompilerConfig reference
     pub fn config(&self) -> &'static CompilerConfig {
-        compilers_service::get_compiler(self.compiler_idx).unwrap() // todo? It's safe here, isn't it?
+        compilers_service::get_compiler_or_missing(&self.compiler_id)
     }
     
     /// Get compiler name
     pub fn name(&self) -> &'static str {
-        compilers_service::get_compiler(self.compiler_idx)
-            .map(|c| c.name.as_str())
-            .unwrap_or("unknown")
+        &self.config().name
     }
     
-    /// Add a parameter by index
+    /// Add a parameter by its index in the compiler config, along with the parameters it requires
     pub fn add_parameter(&mut self, parameter_idx: usize) -> usize {
-        let parm = compilers_service::get_parameter(self.compiler_idx, parameter_idx).unwrap(); // ? safety? todo
+        let parm = &self.config().parameters[parameter_idx];
         
         let value = match &parm.value_type {
             compiler_data_model::ParameterType::Flag => None,
+            compiler_data_model::ParameterType::Bool if parm.default_value.is_none() => Some("false".to_string()),
+            compiler_data_model::ParameterType::Choice if parm.default_value.is_none() => {
+                parm.choices.first().map(|choice| choice.value.clone())
+            }
             _ => parm.default_value.as_ref().map_or_else(
                 || Some(String::new()), 
                 |value| Some(value.clone())
             )
         };
 
-        self.parameters.push(ParameterOverride {
-            compiler_idx: self.compiler_idx,
-            parameter_idx,
-            value,
-            activated: true,
-        });
+        let mut parameter = ParameterOverride::new(&self.compiler_id, &parm.id);
+        parameter.value = value;
+        self.parameters.push(parameter);
+        let added_idx = self.parameters.len() - 1;
+
+        // Added after the parameter itself so requirement cycles end
+        for required in &parm.requires {
+            let already_set = self.parameters.iter().any(|p| p.parameter_id == *required);
+            if !already_set && let Some(required_idx) = self.config().parameters.iter().position(|p| p.id == *required) {
+                self.add_parameter(required_idx);
+            }
+        }
 
-        self.parameters.len() - 1
+        added_idx
     }
     
     /// Generate command TODO
@@ -0,0 +1,235 @@
+//! Checks parameter overrides against their type, their constraints and the game they compile for.
+
+use std::{fmt, path::Path};
+
+use compiler_data_model::{Parameter, ParameterType};
+use serde::{Deserialize, Serialize};
+
+use crate::{parameter_override::ParameterOverride, preset::Preset, selected_compiler::SelectedCompiler, GameConfiguration};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueSeverity {
+    /// Compiling may still work, e.g. a path an earlier step creates.
+    Warning,
+    /// The batch refuses to start.
+    Error,
+}
+
+/// What is wrong with a step or a parameter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum IssueKind {
+    UnknownCompiler(String),     // (compiler id)
+    UnknownParameter(String),    // (parameter id)
+    MissingValue,
+    NotAnInteger(String),        // (value)
+    NotANumber(String),          // (value)
+    NotABool(String),            // (value)
+    NotAChoice(String),          // (value)
+    BelowMinimum(f64, f64),      // (value, min)
+    AboveMaximum(f64, f64),      // (value, max)
+    PathNotFound(String),        // (path)
+    PatternMismatch(String),     // (pattern)
+    InvalidPattern(String),      // (regex error), a mistake in the compiler config
+    IncompatibleGame(u32),       // (steam app id)
+    MissingRequirement(String),  // (name of the required parameter)
+    Conflict(String),            // (name of the conflicting parameter)
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownCompiler(id) => write!(f, "compiler '{id}' is not loaded"),
+            Self::UnknownParameter(id) => write!(f, "parameter '{id}' does not exist"),
+            Self::MissingValue => write!(f, "a value is required"),
+            Self::NotAnInteger(value) => write!(f, "'{value}' is not an integer"),
+            Self::NotANumber(value) => write!(f, "'{value}' is not a number"),
+            Self::NotABool(value) => write!(f, "'{value}' is not true or false"),
+            Self::NotAChoice(value) => write!(f, "'{value}' is not one of the choices"),
+            Self::BelowMinimum(value, min) => write!(f, "{value} is below the minimum of {min}"),
+            Self::AboveMaximum(value, max) => write!(f, "{value} is above the maximum of {max}"),
+            Self::PathNotFound(path) => write!(f, "'{path}' does not exist"),
+            Self::PatternMismatch(pattern) => write!(f, "the value must match `{pattern}`"),
+            Self::InvalidPattern(error) => write!(f, "the compiler config has an invalid pattern: {error}"),
+            Self::IncompatibleGame(app_id) => write!(f, "not supported by the game (app id {app_id})"),
+            Self::MissingRequirement(name) => write!(f, "requires '{name}'"),
+            Self::Conflict(name) => write!(f, "cannot be used with '{name}'"),
+        }
+    }
+}
+
+/// An issue of a parameter override.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterIssue {
+    pub severity: IssueSeverity,
+    pub kind: IssueKind,
+}
+
+/// An issue of a preset, with the step and parameter it is about.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    /// Index of the step in the preset.
+    pub step_idx: usize,
+    pub compiler: String,
+    /// Index of the override in the step, `None` for issues of the step itself.
+    pub parameter_idx: Option<usize>,
+    pub parameter: Option<String>,
+    pub severity: IssueSeverity,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.parameter {
+            Some(parameter) => write!(f, "{} {parameter}: {}", self.compiler, self.kind),
+            None => write!(f, "{}: {}", self.compiler, self.kind),
+        }
+    }
+}
+
+/// Checks the activated steps and overrides of a preset. `game` enables the compatibility checks.
+pub fn validate_preset(preset: &Preset, game: Option<&GameConfiguration>) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    for (step_idx, step) in preset.apps.iter().enumerate().filter(|(_, step)| step.activated) {
+        let compiler = step.config();
+        if compilers_service::get_compiler(&step.compiler_id).is_none() {
+            issues.push(ValidationIssue {
+                step_idx,
+                compiler: compiler.name.clone(),
+                parameter_idx: None,
+                parameter: None,
+                severity: IssueSeverity::Error,
+                kind: IssueKind::UnknownCompiler(step.compiler_id.clone()),
+            });
+            continue;
+        }
+
+        let step_issues = validate_step_parameters(step, game);
+        for ((parameter_idx, parameter), parameter_issues) in step.parameters.iter().enumerate().zip(step_issues) {
+            if !parameter.activated {
+                continue;
+            }
+            issues.extend(parameter_issues.into_iter().map(|issue| ValidationIssue {
+                step_idx,
+                compiler: compiler.name.clone(),
+                parameter_idx: Some(parameter_idx),
+                parameter: Some(parameter.name().to_string()),
+                severity: issue.severity,
+                kind: issue.kind,
+            }));
+        }
+    }
+    issues
+}
+
+/// Checks every override of a step, their relations to the other activated overrides included.
+///
+/// Returns the issues of each override, in the order of `step.parameters`.
+pub fn validate_step_parameters(step: &SelectedCompiler, game: Option<&GameConfiguration>) -> Vec<Vec<ParameterIssue>> {
+    let active: Vec<&Parameter> = step.parameters.iter()
+        .filter(|parameter| parameter.activated)
+        .filter_map(ParameterOverride::parameter)
+        .collect();
+
+    step.parameters.iter()
+        .map(|parameter| {
+            let mut issues = validate_parameter(parameter, game);
+            if let Some(definition) = parameter.parameter() {
+                for required in &definition.requires {
+                    if !active.iter().any(|other| other.id == *required) {
+                        let name = step.config().find_parameter(required).map_or(required.as_str(), |p| p.name.as_str());
+                        issues.push(error(IssueKind::MissingRequirement(name.to_string())));
+                    }
+                }
+                for other in active.iter().filter(|other| other.id != definition.id && definition.conflicts(other)) {
+                    issues.push(error(IssueKind::Conflict(other.name.clone())));
+                }
+            }
+            issues
+        })
+        .collect()
+}
+
+/// Checks an override against the type and constraints of its parameter.
+pub fn validate_parameter(parameter: &ParameterOverride, game: Option<&GameConfiguration>) -> Vec<ParameterIssue> {
+    let Some(definition) = parameter.parameter() else {
+        return vec![error(IssueKind::UnknownParameter(parameter.parameter_id.clone()))];
+    };
+
+    let mut issues = Vec::new();
+    if let Some(app_id) = game.and_then(|game| game.steam_app_id)
+        && !is_compatible(definition, app_id)
+    {
+        issues.push(error(IssueKind::IncompatibleGame(app_id)));
+    }
+
+    let value = parameter.value_or_default().unwrap_or_default();
+    match definition.value_type {
+        ParameterType::Flag => return issues,
+        ParameterType::Integer | ParameterType::Float | ParameterType::Path | ParameterType::Choice if value.is_empty() => {
+            issues.push(error(IssueKind::MissingValue));
+            return issues;
+        }
+        ParameterType::String if value.is_empty() => return issues,
+        ParameterType::Integer => match value.parse::<i64>() {
+            Ok(number) => check_range(definition, number as f64, &mut issues),
+            Err(_) => issues.push(error(IssueKind::NotAnInteger(value.to_string()))),
+        },
+        ParameterType::Float => match value.parse::<f64>() {
+            Ok(number) => check_range(definition, number, &mut issues),
+            Err(_) => issues.push(error(IssueKind::NotANumber(value.to_string()))),
+        },
+        ParameterType::Bool if compiler_data_model::parse_bool(value).is_none() => {
+            issues.push(error(IssueKind::NotABool(value.to_string())));
+        }
+        ParameterType::Choice if definition.find_choice(value).is_none() => {
+            issues.push(error(IssueKind::NotAChoice(value.to_string())));
+        }
+        // Placeholders are resolved per map, and earlier steps may create the file
+        ParameterType::Path if !value.contains('$') && !Path::new(value).exists() => {
+            issues.push(ParameterIssue {
+                severity: IssueSeverity::Warning,
+                kind: IssueKind::PathNotFound(value.to_string()),
+            });
+        }
+        ParameterType::Path | ParameterType::String | ParameterType::Bool | ParameterType::Choice => {}
+    }
+
+    if let Some(pattern) = definition.constraints.as_ref().and_then(|c| c.regex_pattern.as_deref()) {
+        match regex::Regex::new(&format!("^(?:{pattern})$")) {
+            Ok(regex) if !regex.is_match(value) => issues.push(error(IssueKind::PatternMismatch(pattern.to_string()))),
+            Ok(_) => {}
+            Err(e) => issues.push(error(IssueKind::InvalidPattern(e.to_string()))),
+        }
+    }
+    issues
+}
+
+fn is_compatible(definition: &Parameter, app_id: u32) -> bool {
+    let Some(constraints) = &definition.constraints else {
+        return true;
+    };
+    let allowed = constraints.compatible_games.as_ref().is_none_or(|games| games.contains(&app_id));
+    let excluded = constraints.incompatible_games.as_ref().is_some_and(|games| games.contains(&app_id));
+    allowed && !excluded
+}
+
+fn check_range(definition: &Parameter, value: f64, issues: &mut Vec<ParameterIssue>) {
+    let Some(constraints) = &definition.constraints else {
+        return;
+    };
+    if let Some(min) = constraints.min_value
+        && value < min
+    {
+        issues.push(error(IssueKind::BelowMinimum(value, min)));
+    }
+    if let Some(max) = constraints.max_value
+        && value > max
+    {
+        issues.push(error(IssueKind::AboveMaximum(value, max)));
+    }
+}
+
+fn error(kind: IssueKind) -> ParameterIssue {
+    ParameterIssue { severity: IssueSeverity::Error, kind }
+}
@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use vmflow_config_types::{
+    migration::migrate_settings, parameter_override::ParameterOverride, preset::Preset, selected_compiler::SelectedCompiler,
+    GameConfiguration,
+};
+
+/// The part of the settings file holding compiler references.
+#[derive(Deserialize)]
+struct Settings {
+    compile_presets: Vec<Preset>,
+    games: Vec<GameConfiguration>,
+}
+
+fn load(name: &str) -> Settings {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/settings").join(name);
+    toml::from_str(&std::fs::read_to_string(path).unwrap()).expect("settings parse")
+}
+
+fn parameter_ids(preset: &Preset, step: usize) -> Vec<(&str, &str, Option<&str>)> {
+    preset.apps[step].parameters.iter()
+        .map(|parameter| (parameter.compiler_id.as_str(), parameter.parameter_id.as_str(), parameter.value.as_deref()))
+        .collect()
+}
+
+#[test]
+fn migrates_baseline_settings() {
+    let mut settings = load("baseline.toml");
+    let unmapped = migrate_settings(&mut settings.compile_presets, &mut settings.games);
+
+    let preset = &settings.compile_presets[0];
+    let steps: Vec<&str> = preset.apps.iter().map(|step| step.compiler_id.as_str()).collect();
+    assert_eq!(steps, ["vbsp", "vrad", "vpk", "game", "shutdown", ""]);
+    assert!(!preset.apps[4].activated);
+
+    assert_eq!(parameter_ids(preset, 0), [("vbsp", "allowdynamicpropsasstatic", None)]);
+    assert_eq!(parameter_ids(preset, 1), [("vrad", "bounce", Some("4")), ("vrad", "verbose", None)]);
+    assert!(!preset.apps[1].parameters[1].activated);
+    assert_eq!(parameter_ids(preset, 2), [("vpk", "response_file", Some("files.txt"))]);
+    // Positions the list has since moved: a parameter was inserted before the last one
+    assert_eq!(
+        parameter_ids(preset, 3),
+        [("game", "mp_enableroundwaittime_0", None), ("game", "command_line_argument", Some("+map test"))]
+    );
+    assert!(preset.apps.iter().all(|step| step.legacy_compiler_idx.is_none()));
+
+    let game = &settings.games[0];
+    assert!(game.legacy_apps_paths.is_empty());
+    assert_eq!(game.compiler_path("vbsp"), Some("C:/tools/vbsp.exe"));
+    assert_eq!(game.compiler_path("vpk"), Some("C:/tools/vpk.exe"));
+    assert_eq!(game.compiler_paths.len(), 2);
+
+    assert_eq!(unmapped.len(), 2, "{unmapped:?}");
+    assert!(unmapped[0].contains("parameter at former index 20 = 'stale'"));
+    assert!(unmapped[1].contains("step 6: no compiler at former index 42"));
+}
+
+#[test]
+fn migrated_settings_are_left_alone() {
+    let mut settings = load("baseline.toml");
+    migrate_settings(&mut settings.compile_presets, &mut settings.games);
+    let before: Vec<_> = settings.compile_presets[0].apps.iter().map(|step| step.compiler_id.clone()).collect();
+
+    assert!(migrate_settings(&mut settings.compile_presets, &mut settings.games).is_empty());
+    let after: Vec<_> = settings.compile_presets[0].apps.iter().map(|step| step.compiler_id.clone()).collect();
+    assert_eq!(before, after);
+}
+
+#[test]
+fn every_former_position_still_exists() {
+    // Parameter count of each built-in compiler by its former position
+    let counts = [29, 9, 27, 14, 4, 0, 0, 0, 6];
+    let apps = counts.iter().enumerate()
+        .map(|(compiler_idx, &count)| SelectedCompiler {
+            legacy_compiler_idx: Some(compiler_idx),
+            parameters: (0..count)
+                .map(|parameter_idx| ParameterOverride { legacy_parameter_idx: Some(parameter_idx), ..Default::default() })
+                .collect(),
+            ..Default::default()
+        })
+        .collect();
+    let mut presets = vec![Preset { name: "All".to_string(), apps, ..Default::default() }];
+
+    assert_eq!(migrate_settings(&mut presets, &mut []), Vec::<String>::new());
+    for step in &presets[0].apps {
+        assert!(compilers_service::get_compiler(&step.compiler_id).is_some(), "{}", step.compiler_id);
+        for parameter in &step.parameters {
+            assert!(parameter.parameter().is_some(), "{} {}", step.compiler_id, parameter.parameter_id);
+        }
+    }
+}
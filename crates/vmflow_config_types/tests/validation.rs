@@ -0,0 +1,120 @@
+use std::{path::PathBuf, sync::Once};
+
+use vmflow_config_types::{
+    parameter_override::ParameterOverride,
+    selected_compiler::SelectedCompiler,
+    validation::{validate_parameter, validate_step_parameters, IssueKind, IssueSeverity, ParameterIssue},
+    GameConfiguration,
+};
+
+/// Loads the `checker` compiler of `tests/compilers`.
+fn load_compilers() {
+    static LOAD: Once = Once::new();
+    LOAD.call_once(|| {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/compilers");
+        assert!(compilers_service::load_user_configs(&dir).is_empty());
+    });
+}
+
+fn parameter(id: &str, value: Option<&str>) -> ParameterOverride {
+    ParameterOverride {
+        value: value.map(str::to_string),
+        ..ParameterOverride::new("checker", id)
+    }
+}
+
+fn kinds(parameter: &ParameterOverride) -> Vec<IssueKind> {
+    load_compilers();
+    validate_parameter(parameter, None).into_iter().map(|issue| issue.kind).collect()
+}
+
+fn step(parameters: Vec<ParameterOverride>) -> SelectedCompiler {
+    SelectedCompiler { parameters, ..SelectedCompiler::from_id("checker") }
+}
+
+#[test]
+fn checks_ranges() {
+    assert_eq!(kinds(&parameter("threads", Some("8"))), []);
+    assert_eq!(kinds(&parameter("threads", Some("1"))), []);
+    assert_eq!(kinds(&parameter("threads", Some("0"))), [IssueKind::BelowMinimum(0.0, 1.0)]);
+    assert_eq!(kinds(&parameter("threads", Some("65"))), [IssueKind::AboveMaximum(65.0, 64.0)]);
+    assert_eq!(kinds(&parameter("threads", Some("1.5"))), [IssueKind::NotAnInteger("1.5".to_string())]);
+    assert_eq!(kinds(&parameter("threads", None)), [IssueKind::MissingValue]);
+
+    // The default value is checked when there is no override value
+    assert_eq!(kinds(&parameter("scale", None)), []);
+    assert_eq!(kinds(&parameter("scale", Some("1.5"))), [IssueKind::AboveMaximum(1.5, 1.0)]);
+    assert_eq!(kinds(&parameter("scale", Some("-3"))), []);
+    assert_eq!(kinds(&parameter("scale", Some("half"))), [IssueKind::NotANumber("half".to_string())]);
+}
+
+#[test]
+fn checks_patterns() {
+    assert_eq!(kinds(&parameter("label", Some("lower_case"))), []);
+    // The whole value must match
+    assert_eq!(kinds(&parameter("label", Some("lower case"))), [IssueKind::PatternMismatch("[a-z_]+".to_string())]);
+    assert_eq!(kinds(&parameter("label", Some("Upper"))), [IssueKind::PatternMismatch("[a-z_]+".to_string())]);
+    // An empty string is not passed to the compiler
+    assert_eq!(kinds(&parameter("label", None)), []);
+
+    let issues = validate_parameter(&parameter("broken", Some("anything")), None);
+    assert!(matches!(
+        &issues[..],
+        [ParameterIssue { severity: IssueSeverity::Error, kind: IssueKind::InvalidPattern(_) }]
+    ));
+}
+
+#[test]
+fn checks_unknown_parameters_and_games() {
+    assert_eq!(kinds(&ParameterOverride::new("checker", "missing")), [IssueKind::UnknownParameter("missing".to_string())]);
+
+    let game = |steam_app_id| GameConfiguration { steam_app_id, ..Default::default() };
+    let csgo = parameter("csgo", None);
+    assert_eq!(validate_parameter(&csgo, Some(&game(Some(730)))), []);
+    assert_eq!(validate_parameter(&csgo, Some(&game(None))), []);
+    assert_eq!(
+        validate_parameter(&csgo, Some(&game(Some(220)))).into_iter().map(|issue| issue.kind).collect::<Vec<_>>(),
+        [IssueKind::IncompatibleGame(220)]
+    );
+}
+
+#[test]
+fn checks_requirements() {
+    load_compilers();
+
+    let issues = validate_step_parameters(&step(vec![parameter("extra", None)]), None);
+    assert_eq!(issues, [vec![ParameterIssue {
+        severity: IssueSeverity::Error,
+        kind: IssueKind::MissingRequirement("Threads".to_string()),
+    }]]);
+
+    let issues = validate_step_parameters(&step(vec![parameter("extra", None), parameter("threads", Some("4"))]), None);
+    assert_eq!(issues, [vec![], vec![]]);
+
+    // A deactivated parameter does not count as set
+    let mut threads = parameter("threads", Some("4"));
+    threads.activated = false;
+    let issues = validate_step_parameters(&step(vec![parameter("extra", None), threads]), None);
+    assert_eq!(issues[0].len(), 1);
+}
+
+#[test]
+fn checks_conflicts_declared_on_either_side() {
+    load_compilers();
+
+    let issues = validate_step_parameters(&step(vec![parameter("fast", None), parameter("slow", None)]), None);
+    let kinds: Vec<Vec<IssueKind>> = issues.into_iter()
+        .map(|issues| issues.into_iter().map(|issue| issue.kind).collect())
+        .collect();
+    assert_eq!(kinds, [
+        vec![IssueKind::Conflict("Slow".to_string())],
+        vec![IssueKind::Conflict("Fast".to_string())],
+    ]);
+
+    let mut slow = parameter("slow", None);
+    slow.activated = false;
+    let issues = validate_step_parameters(&step(vec![parameter("fast", None), slow]), None);
+    assert_eq!(issues[0], []);
+    // A deactivated override still shows what activating it would cause
+    assert_eq!(issues[1].len(), 1);
+}